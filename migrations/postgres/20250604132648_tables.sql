@@ -0,0 +1,19 @@
+-- Add migration script here
+
+CREATE TABLE proofs (
+    id BIGSERIAL PRIMARY KEY,
+    proof TEXT NOT NULL -- JSON stored as text
+);
+
+CREATE TABLE epoch_updates (
+    uuid TEXT PRIMARY KEY,
+    epoch_number BIGINT NOT NULL,
+    slot_number BIGINT NOT NULL UNIQUE,
+    outputs TEXT, -- JSON stored as text
+    atlantic_id TEXT,
+    proof_id BIGINT,
+    FOREIGN KEY (proof_id) REFERENCES proofs(id)
+);
+
+CREATE INDEX idx_epoch_updates_slot ON epoch_updates(slot_number);
+CREATE INDEX idx_epoch_updates_epoch ON epoch_updates(epoch_number);
@@ -0,0 +1,14 @@
+-- Add migration script here
+
+CREATE TABLE attempts (
+    id BIGSERIAL PRIMARY KEY,
+    epoch_update_uuid TEXT NOT NULL,
+    attempt_number BIGINT NOT NULL,
+    error TEXT,
+    started_at TEXT NOT NULL DEFAULT (CURRENT_TIMESTAMP::TEXT),
+    finished_at TEXT,
+    FOREIGN KEY (epoch_update_uuid) REFERENCES epoch_updates(uuid),
+    UNIQUE (epoch_update_uuid, attempt_number)
+);
+
+CREATE INDEX idx_attempts_epoch_update ON attempts(epoch_update_uuid);
@@ -0,0 +1,8 @@
+-- Add migration script here
+
+CREATE TABLE inputs (
+    epoch_update_uuid TEXT PRIMARY KEY,
+    data BYTEA NOT NULL, -- zstd compressed JSON of RecursiveEpochInputs
+    created_at TEXT NOT NULL DEFAULT (CURRENT_TIMESTAMP::TEXT),
+    FOREIGN KEY (epoch_update_uuid) REFERENCES epoch_updates(uuid)
+);
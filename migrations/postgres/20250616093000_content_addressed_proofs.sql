@@ -0,0 +1,14 @@
+-- Add migration script here
+
+CREATE TABLE proof_blobs (
+    hash TEXT PRIMARY KEY,
+    data BYTEA NOT NULL, -- zstd compressed proof JSON
+    created_at TEXT NOT NULL DEFAULT (CURRENT_TIMESTAMP::TEXT)
+);
+
+-- Proof contents move to the proof store. `proof` is kept for rows written before this
+-- migration and is emptied once they have been moved by `Database::migrate_legacy_proofs`.
+ALTER TABLE proofs ADD COLUMN hash TEXT;
+ALTER TABLE proofs ADD COLUMN pinned BIGINT NOT NULL DEFAULT 0;
+
+CREATE UNIQUE INDEX idx_proofs_hash ON proofs(hash);
@@ -0,0 +1,14 @@
+-- Add migration script here
+
+CREATE TABLE attempts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    epoch_update_uuid TEXT NOT NULL,
+    attempt_number INTEGER NOT NULL,
+    error TEXT,
+    started_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    finished_at TEXT,
+    FOREIGN KEY (epoch_update_uuid) REFERENCES epoch_updates(uuid),
+    UNIQUE (epoch_update_uuid, attempt_number)
+);
+
+CREATE INDEX idx_attempts_epoch_update ON attempts(epoch_update_uuid);
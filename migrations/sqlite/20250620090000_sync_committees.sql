@@ -0,0 +1,15 @@
+-- Add migration script here
+
+-- Sync committee members only change once per period, so their public keys are cached
+-- instead of being looked up again for every epoch.
+CREATE TABLE sync_committees (
+    network TEXT NOT NULL,
+    period INTEGER NOT NULL,
+    pubkeys BLOB NOT NULL, -- compressed public keys in committee order, 48 bytes each
+    aggregate_pubkey TEXT NOT NULL,
+    committee_keys_root TEXT NOT NULL,
+    committee_hash TEXT NOT NULL,
+    validated INTEGER NOT NULL DEFAULT 0, -- 1 once checked against a proven sync committee update
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (network, period)
+);
@@ -0,0 +1,6 @@
+-- Add migration script here
+
+ALTER TABLE epoch_updates ADD COLUMN wrap_atlantic_id TEXT;
+ALTER TABLE epoch_updates ADD COLUMN wrap_status TEXT;
+ALTER TABLE epoch_updates ADD COLUMN wrap_error TEXT;
+ALTER TABLE epoch_updates ADD COLUMN fact_hash TEXT;
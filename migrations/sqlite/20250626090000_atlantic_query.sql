@@ -0,0 +1,6 @@
+-- Add migration script here
+
+ALTER TABLE epoch_updates ADD COLUMN atlantic_status TEXT;
+ALTER TABLE epoch_updates ADD COLUMN atlantic_step TEXT;
+ALTER TABLE epoch_updates ADD COLUMN atlantic_error TEXT;
+ALTER TABLE epoch_updates ADD COLUMN atlantic_phases TEXT;
@@ -0,0 +1,3 @@
+-- Add migration script here
+
+ALTER TABLE epoch_updates ADD COLUMN job_params TEXT;
@@ -0,0 +1,177 @@
+use std::{
+    borrow::Cow,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use cairo_vm::types::{layout_name::LayoutName, program::Program};
+
+use crate::error::Error;
+
+/// Entrypoint of the compiled programs
+const ENTRYPOINT: &str = "main";
+
+/// Where a compiled Cairo program is read from
+#[derive(Debug, Clone)]
+pub enum ProgramSource {
+    /// Compiled program JSON on disk. Relative paths are resolved against the working directory.
+    Path(PathBuf),
+    /// Compiled program JSON, e.g. embedded with `include_bytes!`
+    Bytes(Cow<'static, [u8]>),
+}
+
+impl ProgramSource {
+    /// Returns the absolute path of the program, `None` for embedded programs
+    pub fn resolved_path(&self) -> Result<Option<PathBuf>, Error> {
+        match self {
+            ProgramSource::Path(path) => Ok(Some(absolute(path)?)),
+            ProgramSource::Bytes(_) => Ok(None),
+        }
+    }
+
+    /// Reads and parses the program
+    pub fn load(&self) -> Result<Program, Error> {
+        let bytes = match self {
+            ProgramSource::Bytes(bytes) => Cow::Borrowed(bytes.as_ref()),
+            ProgramSource::Path(path) => {
+                let path = absolute(path)?;
+                let bytes = std::fs::read(&path).map_err(|source| match source.kind() {
+                    std::io::ErrorKind::NotFound => Error::ProgramNotFound(path.clone()),
+                    _ => Error::ProgramRead {
+                        path: path.clone(),
+                        source,
+                    },
+                })?;
+                Cow::Owned(bytes)
+            }
+        };
+        Ok(Program::from_bytes(&bytes, Some(ENTRYPOINT))?)
+    }
+}
+
+fn absolute(path: &Path) -> Result<PathBuf, Error> {
+    if path.is_absolute() {
+        Ok(path.to_path_buf())
+    } else {
+        Ok(std::env::current_dir()?.join(path))
+    }
+}
+
+impl From<&str> for ProgramSource {
+    fn from(path: &str) -> Self {
+        ProgramSource::Path(PathBuf::from(path))
+    }
+}
+
+impl From<&Path> for ProgramSource {
+    fn from(path: &Path) -> Self {
+        ProgramSource::Path(path.to_path_buf())
+    }
+}
+
+impl fmt::Display for ProgramSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProgramSource::Path(path) => write!(f, "{}", path.display()),
+            ProgramSource::Bytes(bytes) => write!(f, "<embedded program, {} bytes>", bytes.len()),
+        }
+    }
+}
+
+/// How a program is run
+#[derive(Debug, Clone)]
+pub struct RunnerConfig {
+    /// The compiled program
+    pub program: ProgramSource,
+    /// Layout the builtins and memory are laid out for
+    pub layout: LayoutName,
+    /// Run in proof mode, as needed to prove the trace directly instead of through a PIE
+    pub proof_mode: bool,
+    /// Accept programs using builtins the layout does not have
+    pub allow_missing_builtins: bool,
+    /// Directory `trace.bin` and `memory.bin` are written to, and the AIR inputs in proof mode
+    pub output_dir: Option<PathBuf>,
+    /// Do not pad the trace to the next power of two in proof mode
+    pub disable_trace_padding: bool,
+    /// Abort the run after this many steps
+    pub step_limit: Option<usize>,
+}
+
+impl RunnerConfig {
+    /// Runs `program` like the PIEs submitted for proving: `all_cairo` layout, not in proof
+    /// mode and without writing the trace
+    pub fn new(program: impl Into<ProgramSource>) -> Self {
+        Self {
+            program: program.into(),
+            layout: LayoutName::all_cairo,
+            proof_mode: false,
+            allow_missing_builtins: true,
+            output_dir: None,
+            disable_trace_padding: false,
+            step_limit: None,
+        }
+    }
+}
+
+/// Parses a layout name such as `all_cairo` or `recursive_with_poseidon`
+pub fn parse_layout(name: &str) -> Result<LayoutName, Error> {
+    Ok(match name {
+        "plain" => LayoutName::plain,
+        "small" => LayoutName::small,
+        "dex" => LayoutName::dex,
+        "recursive" => LayoutName::recursive,
+        "starknet" => LayoutName::starknet,
+        "starknet_with_keccak" => LayoutName::starknet_with_keccak,
+        "recursive_large_output" => LayoutName::recursive_large_output,
+        "recursive_with_poseidon" => LayoutName::recursive_with_poseidon,
+        "all_solidity" => LayoutName::all_solidity,
+        "all_cairo" => LayoutName::all_cairo,
+        "all_cairo_stwo" => LayoutName::all_cairo_stwo,
+        // The dynamic layout needs parameters the runner does not take
+        other => return Err(Error::UnknownLayout(other.to_string())),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_known_layouts() {
+        assert_eq!(parse_layout("all_cairo").unwrap(), LayoutName::all_cairo);
+        assert_eq!(
+            parse_layout("recursive_with_poseidon").unwrap(),
+            LayoutName::recursive_with_poseidon
+        );
+        assert_eq!(parse_layout("all_cairo_stwo").unwrap(), LayoutName::all_cairo_stwo);
+    }
+
+    #[test]
+    fn rejects_unknown_and_dynamic_layouts() {
+        for name in ["dynamic", "all-cairo", ""] {
+            match parse_layout(name) {
+                Err(Error::UnknownLayout(layout)) => assert_eq!(layout, name),
+                other => panic!("expected an unknown layout error for {:?}, got {:?}", name, other),
+            }
+        }
+    }
+
+    #[test]
+    fn reports_missing_program_with_absolute_path() {
+        let source = ProgramSource::from("build/does_not_exist.json");
+
+        let expected = std::env::current_dir().unwrap().join("build/does_not_exist.json");
+        assert_eq!(source.resolved_path().unwrap(), Some(expected.clone()));
+        match source.load() {
+            Err(Error::ProgramNotFound(path)) => assert_eq!(path, expected),
+            other => panic!("expected a missing program error, got {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn embedded_program_has_no_path() {
+        let source = ProgramSource::Bytes(Cow::Borrowed(b"{}"));
+        assert_eq!(source.resolved_path().unwrap(), None);
+        assert!(matches!(source.load(), Err(Error::Program(_))));
+    }
+}
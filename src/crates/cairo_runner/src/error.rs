@@ -8,6 +8,7 @@ use cairo_vm::{
     },
     Felt252,
 };
+use std::path::PathBuf;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -51,4 +52,23 @@ pub enum Error {
     IlegalInputValue,
     #[error("Runner Error: {0}")]
     Runner(#[from] RunnerError),
+    #[error("Program hash mismatch: expected {expected:#x}, computed {actual:#x}. Was the program recompiled?")]
+    ProgramHashMismatch { expected: Felt252, actual: Felt252 },
+    #[error("Program data contains relocatable values and cannot be hashed")]
+    RelocatableProgramData,
+    #[error("Program output has {actual} values, expected {expected}")]
+    OutputLength { expected: usize, actual: usize },
+    #[error("Compiled program {} not found. Relative paths are resolved against the working directory; build it with `make build-cairo` or set the program path.", .0.display())]
+    ProgramNotFound(PathBuf),
+    #[error("Failed to read compiled program {}: {source}", path.display())]
+    ProgramRead {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Unknown layout {0}")]
+    UnknownLayout(String),
+    #[error("Run exceeded the step limit of {0} steps")]
+    StepLimitExceeded(usize),
+    #[error("Program output does not match the expected outputs (actual != expected):\n  {}", .0.join("\n  "))]
+    OutputMismatch(Vec<String>),
 }
@@ -10,7 +10,8 @@ use cairo_vm::{
     },
     types::exec_scope::ExecutionScopes,
     vm::{
-        errors::hint_errors::HintError, runners::cairo_runner::ResourceTracker,
+        errors::hint_errors::HintError,
+        runners::cairo_runner::{ResourceTracker, RunResources},
         vm_core::VirtualMachine,
     },
     Felt252,
@@ -49,17 +50,29 @@ pub struct CustomHintProcessor {
     // Add the builtin hint processor
     builtin_hint_proc: BuiltinHintProcessor,
     pub recursive_epoch_update: RecursiveEpochUpdateCairo,
+    /// Program hash of the running program, written into the `program_hash` hint variable
+    pub program_hash: Felt252,
+    /// Steps left before the run is aborted
+    run_resources: RunResources,
 }
 
 impl CustomHintProcessor {
-    pub fn new(recursive_epoch_update: RecursiveEpochUpdateCairo) -> Self {
+    pub fn new(recursive_epoch_update: RecursiveEpochUpdateCairo, program_hash: Felt252) -> Self {
         Self {
             hints: Self::hints(),
             builtin_hint_proc: BuiltinHintProcessor::new_empty(),
             recursive_epoch_update,
+            program_hash,
+            run_resources: RunResources::default(),
         }
     }
 
+    /// Aborts the run after `step_limit` steps, if given
+    pub fn with_step_limit(mut self, step_limit: Option<usize>) -> Self {
+        self.run_resources = step_limit.map(RunResources::new).unwrap_or_default();
+        self
+    }
+
     fn hints() -> HashMap<String, HintImpl> {
         let mut hints = HashMap::<String, HintImpl>::new();
         hints.insert(
@@ -237,4 +250,20 @@ impl HintProcessorLogic for CustomHintProcessor {
     }
 }
 
-impl ResourceTracker for CustomHintProcessor {}
+impl ResourceTracker for CustomHintProcessor {
+    fn consumed(&self) -> bool {
+        self.run_resources.consumed()
+    }
+
+    fn consume_step(&mut self) {
+        self.run_resources.consume_step()
+    }
+
+    fn get_n_steps(&self) -> Option<usize> {
+        self.run_resources.get_n_steps()
+    }
+
+    fn run_resources(&self) -> &RunResources {
+        &self.run_resources
+    }
+}
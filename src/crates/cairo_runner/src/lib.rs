@@ -1,113 +1,202 @@
+pub mod config;
 pub mod error;
 pub mod hints;
 pub mod recursive_epoch;
 use bincode::enc::write::Writer;
 
 pub mod hint_processor;
+pub mod program_hash;
 pub mod types;
 use cairo_vm::{
     cairo_run::{
         self, cairo_run_program_with_initial_scope, write_encoded_memory, write_encoded_trace,
     },
-    types::{exec_scope::ExecutionScopes, layout_name::LayoutName, program::Program},
-    vm::{errors::trace_errors::TraceError, runners::cairo_pie::CairoPie},
+    hint_processor::hint_processor_definition::HintProcessor,
+    types::{
+        exec_scope::ExecutionScopes, layout_name::LayoutName, program::Program,
+        relocatable::Relocatable,
+    },
+    vm::{
+        errors::{
+            cairo_run_errors::CairoRunError, trace_errors::TraceError,
+            vm_errors::VirtualMachineError,
+        },
+        runners::{cairo_pie::CairoPie, cairo_runner::CairoRunner},
+    },
+    Felt252,
 };
+pub use config::{parse_layout, ProgramSource, RunnerConfig};
 use error::Error;
 use hint_processor::CustomHintProcessor;
-use recursive_epoch::RecursiveEpochUpdateCairo;
+use recursive_epoch::{RecursiveEpochOutputsCairo, RecursiveEpochUpdateCairo};
 use std::io;
-use std::{io::Write, path::Path};
-
-fn load_program(path: &str) -> Result<Program, Error> {
-    // Check if it's an absolute path that doesn't exist, try relative
-    let final_path = if path.starts_with('/') && !std::path::Path::new(path).exists() {
-        // Try converting absolute path to relative
-        let relative_path = path.strip_prefix('/').unwrap_or(path);
-        println!(
-            "Absolute path not found, trying relative: {}",
-            relative_path
-        );
-        relative_path
-    } else {
-        path
-    };
-
-    let program_file = std::fs::read(final_path).map_err(Error::IO)?;
-    let cairo_run_config = cairo_run::CairoRunConfig {
-        allow_missing_builtins: Some(true),
-        layout: LayoutName::all_cairo,
-        ..Default::default()
-    };
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
 
-    let program = Program::from_bytes(&program_file, Some(cairo_run_config.entrypoint))?;
-    Ok(program)
+/// Reads and parses a compiled program, see `ProgramSource::load`
+pub fn load_program(source: &ProgramSource) -> Result<Program, Error> {
+    source.load()
 }
 
-pub fn run(path: &str, update: RecursiveEpochUpdateCairo) -> Result<CairoPie, Error> {
-    let program = load_program(path)?;
-    let cairo_run_config = cairo_run::CairoRunConfig {
-        allow_missing_builtins: Some(true),
-        layout: LayoutName::all_cairo,
-        ..Default::default()
+/// Loads a program and returns its program hash, checked against `expected` if given
+fn load_verified_program(
+    source: &ProgramSource,
+    expected: Option<Felt252>,
+) -> Result<(Program, Felt252), Error> {
+    let program = load_program(source)?;
+    let program_hash = match expected {
+        Some(expected) => program_hash::verify_program_hash(&program, expected)?,
+        None => program_hash::compute_program_hash(&program)?,
     };
-    let mut hint_processor = CustomHintProcessor::new(update);
-    let mut exec_scopes = ExecutionScopes::new();
-    exec_scopes.insert_value("program_object", program.clone());
-
-    let cairo_runner = cairo_run_program_with_initial_scope(
-        &program,
-        &cairo_run_config,
-        &mut hint_processor,
-        exec_scopes,
-    )?;
-    tracing::info!("{:?}", cairo_runner.get_execution_resources());
+    tracing::debug!("Program hash of {}: {:#x}", source, program_hash);
+    Ok((program, program_hash))
+}
 
+/// Runs the recursive update program and returns its PIE.
+///
+/// # Arguments
+/// * `config` - Program, layout and run options
+/// * `update` - Inputs and expected outputs of the epoch update
+/// * `expected_program_hash` - Refuse to run a program with a different hash, if given
+pub fn run(
+    config: &RunnerConfig,
+    update: RecursiveEpochUpdateCairo,
+    expected_program_hash: Option<Felt252>,
+) -> Result<CairoPie, Error> {
+    let cairo_runner = execute(config, update, expected_program_hash)?;
     let pie = cairo_runner.get_cairo_pie()?;
     Ok(pie)
 }
 
-pub fn run_stwo(path: &str, update: RecursiveEpochUpdateCairo, output_dir: &str) -> Result<(), Error> {
-    let program = load_program(path)?;
-    let cairo_run_config = cairo_run::CairoRunConfig {
-        allow_missing_builtins: None, // Optional
+/// Runs the recursive update program in proof mode with the Stwo layout and writes the trace,
+/// memory and AIR inputs to `output_dir`.
+pub fn run_stwo(
+    config: &RunnerConfig,
+    update: RecursiveEpochUpdateCairo,
+    expected_program_hash: Option<Felt252>,
+    output_dir: &str,
+) -> Result<(), Error> {
+    let config = RunnerConfig {
         layout: LayoutName::all_cairo_stwo,
-        relocate_mem: true,
-        trace_enabled: true,
         proof_mode: true,
-        ..Default::default()
+        allow_missing_builtins: false,
+        output_dir: Some(PathBuf::from(output_dir)),
+        ..config.clone()
     };
+    execute(&config, update, expected_program_hash)?;
+    Ok(())
+}
 
-    let mut hint_processor = CustomHintProcessor::new(update);
+/// Runs the program as configured, checks its output and writes the trace files if requested
+fn execute(
+    config: &RunnerConfig,
+    update: RecursiveEpochUpdateCairo,
+    expected_program_hash: Option<Felt252>,
+) -> Result<CairoRunner, Error> {
+    let (program, program_hash) = load_verified_program(&config.program, expected_program_hash)?;
+    let write_trace = config.output_dir.is_some();
+    let cairo_run_config = cairo_run::CairoRunConfig {
+        allow_missing_builtins: Some(config.allow_missing_builtins),
+        layout: config.layout,
+        proof_mode: config.proof_mode,
+        disable_trace_padding: config.disable_trace_padding,
+        trace_enabled: write_trace,
+        relocate_mem: write_trace,
+        ..Default::default()
+    };
+    let expected_output = update.outputs.clone();
+    let mut hint_processor =
+        CustomHintProcessor::new(update, program_hash).with_step_limit(config.step_limit);
     let mut exec_scopes = ExecutionScopes::new();
     exec_scopes.insert_value("program_object", program.clone());
 
-    let cairo_runner = cairo_run_program_with_initial_scope(
+    let mut cairo_runner = run_program(
         &program,
         &cairo_run_config,
         &mut hint_processor,
         exec_scopes,
+        config.step_limit,
     )?;
-
     tracing::info!("{:?}", cairo_runner.get_execution_resources());
+    check_output(&mut cairo_runner, &expected_output)?;
 
-    generate_stwo_files(&cairo_runner, output_dir)?;
-    Ok(())
+    if let Some(output_dir) = &config.output_dir {
+        write_trace_files(&cairo_runner, output_dir, config.proof_mode)?;
+    }
+    Ok(cairo_runner)
 }
 
-fn generate_stwo_files(
-    cairo_runner: &cairo_vm::vm::runners::cairo_runner::CairoRunner,
-    output_dir: &str,
+/// Runs `program`, reporting a run stopped by the step limit of the hint processor as
+/// `Error::StepLimitExceeded`
+fn run_program(
+    program: &Program,
+    cairo_run_config: &cairo_run::CairoRunConfig,
+    hint_processor: &mut dyn HintProcessor,
+    exec_scopes: ExecutionScopes,
+    step_limit: Option<usize>,
+) -> Result<CairoRunner, Error> {
+    cairo_run_program_with_initial_scope(program, cairo_run_config, hint_processor, exec_scopes)
+        .map_err(|e| match (e, step_limit) {
+            (CairoRunError::VirtualMachine(VirtualMachineError::UnfinishedExecution), Some(limit)) => {
+                Error::StepLimitExceeded(limit)
+            }
+            (e, _) => e.into(),
+        })
+}
+
+/// Reads the values the program wrote to the output builtin segment
+pub fn read_output_segment(cairo_runner: &mut CairoRunner) -> Result<Vec<Felt252>, Error> {
+    let base = cairo_runner.vm.get_output_builtin_mut()?.base();
+    cairo_runner.vm.segments.compute_effective_sizes();
+    let size = cairo_runner
+        .vm
+        .segments
+        .get_segment_used_size(base)
+        .unwrap_or_default();
+    let output = cairo_runner
+        .vm
+        .get_integer_range(Relocatable::from((base as isize, 0)), size)?
+        .into_iter()
+        .map(|value| value.into_owned())
+        .collect();
+    Ok(output)
+}
+
+/// Decodes the program output and compares it with the natively computed outputs, so a
+/// disagreement between the Cairo program and the Rust implementation is caught before the
+/// run is submitted to a prover.
+fn check_output(
+    cairo_runner: &mut CairoRunner,
+    expected: &RecursiveEpochOutputsCairo,
+) -> Result<RecursiveEpochOutputsCairo, Error> {
+    let output = read_output_segment(cairo_runner)?;
+    let actual = RecursiveEpochOutputsCairo::from_output_segment(&output)?;
+    let diff = actual.diff(expected);
+    if !diff.is_empty() {
+        return Err(Error::OutputMismatch(diff));
+    }
+    tracing::debug!("Program output matches the expected outputs");
+    Ok(actual)
+}
+
+/// Writes the trace and memory of a run, and the AIR inputs of a proof mode run
+fn write_trace_files(
+    cairo_runner: &CairoRunner,
+    output_dir: &Path,
+    proof_mode: bool,
 ) -> Result<(), Error> {
     std::fs::create_dir_all(output_dir)?;
 
-    let memory_path = Path::new(output_dir).join("memory.bin");
+    let memory_path = output_dir.join("memory.bin");
     let memory_file = std::fs::File::create(&memory_path)?;
     let mut memory_writer =
         FileWriter::new(io::BufWriter::with_capacity(50 * 1024 * 1024, memory_file));
     write_encoded_memory(&cairo_runner.relocated_memory, &mut memory_writer)?;
     memory_writer.flush()?;
 
-    let trace_path = Path::new(output_dir).join("trace.bin");
+    let trace_path = output_dir.join("trace.bin");
     let relocated_trace = cairo_runner
         .relocated_trace
         .as_ref()
@@ -118,11 +207,16 @@ fn generate_stwo_files(
     write_encoded_trace(relocated_trace, &mut trace_writer)?;
     trace_writer.flush()?;
 
+    // The AIR inputs only exist for proof mode runs
+    if !proof_mode {
+        return Ok(());
+    }
+
     // 1. Generate air_public_inputs.json
-    let public_input = cairo_runner.get_air_public_input();
-    let public_input_json = serde_json::to_string_pretty(&public_input.unwrap()).unwrap();
+    let public_input = cairo_runner.get_air_public_input()?;
+    let public_input_json = serde_json::to_string_pretty(&public_input).unwrap();
     std::fs::write(
-        Path::new(output_dir).join("air_public_inputs.json"),
+        output_dir.join("air_public_inputs.json"),
         public_input_json,
     )?;
 
@@ -132,7 +226,7 @@ fn generate_stwo_files(
         private_input.to_serializable("trace.bin".to_string(), "memory.bin".to_string());
     let private_input_json = serde_json::to_string_pretty(&private_input_serializable).unwrap();
     std::fs::write(
-        Path::new(output_dir).join("air_private_inputs.json"),
+        output_dir.join("air_private_inputs.json"),
         private_input_json,
     )?;
 
@@ -171,3 +265,55 @@ impl FileWriter {
         self.buf_writer.flush()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{borrow::Cow, collections::HashMap};
+
+    use cairo_vm::{
+        hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor,
+        vm::runners::cairo_runner::RunResources,
+    };
+
+    use super::*;
+
+    /// Compiled Cairo 0 program whose `main` is `jmp rel 0`, so it never finishes
+    const ENDLESS_PROGRAM: &str = r#"{
+        "attributes": [],
+        "builtins": [],
+        "data": ["0x10780017fff7fff", "0x0"],
+        "debug_info": null,
+        "hints": {},
+        "identifiers": {
+            "__main__.main": { "decorators": [], "pc": 0, "type": "function" }
+        },
+        "main_scope": "__main__",
+        "prime": "0x800000000000011000000000000000000000000000000000000000000000001",
+        "reference_manager": { "references": [] }
+    }"#;
+
+    #[test]
+    fn reports_exceeded_step_limit() {
+        let program = ProgramSource::Bytes(Cow::Borrowed(ENDLESS_PROGRAM.as_bytes()))
+            .load()
+            .unwrap();
+        let cairo_run_config = cairo_run::CairoRunConfig {
+            layout: LayoutName::plain,
+            ..Default::default()
+        };
+        let mut hint_processor = BuiltinHintProcessor::new(HashMap::new(), RunResources::new(100));
+
+        let result = run_program(
+            &program,
+            &cairo_run_config,
+            &mut hint_processor,
+            ExecutionScopes::new(),
+            Some(100),
+        );
+        assert!(
+            matches!(result, Err(Error::StepLimitExceeded(100))),
+            "{:?}",
+            result.err()
+        );
+    }
+}
@@ -0,0 +1,51 @@
+//! Poseidon program hash of a compiled Cairo program.
+//!
+//! Matches `cairo-hash-program --use_poseidon`: the hash of
+//! `[bootloader_version, main, n_builtins, ...builtins, ...program_data]`, with builtin names
+//! encoded as ASCII felts. The recursive update verifies proofs of itself, so the hash of the
+//! loaded program is what its hint reports as `program_hash`.
+
+use cairo_vm::{
+    types::{program::Program, relocatable::MaybeRelocatable},
+    Felt252,
+};
+use starknet_crypto::poseidon_hash_many;
+
+use crate::error::Error;
+
+/// Bootloader version included in the program header
+const BOOTLOADER_VERSION: u64 = 0;
+
+/// Computes the Poseidon program hash of `program`
+pub fn compute_program_hash(program: &Program) -> Result<Felt252, Error> {
+    let stripped = program.get_stripped_program()?;
+
+    let mut data_chain = vec![
+        Felt252::from(BOOTLOADER_VERSION),
+        Felt252::from(stripped.main),
+        Felt252::from(stripped.builtins.len()),
+    ];
+    data_chain.extend(
+        stripped
+            .builtins
+            .iter()
+            .map(|builtin| Felt252::from_bytes_be_slice(builtin.to_str().as_bytes())),
+    );
+    for item in &stripped.data {
+        match item {
+            MaybeRelocatable::Int(value) => data_chain.push(*value),
+            MaybeRelocatable::RelocatableValue(_) => return Err(Error::RelocatableProgramData),
+        }
+    }
+
+    Ok(poseidon_hash_many(&data_chain))
+}
+
+/// Checks the computed program hash against the expected one
+pub fn verify_program_hash(program: &Program, expected: Felt252) -> Result<Felt252, Error> {
+    let actual = compute_program_hash(program)?;
+    if actual != expected {
+        return Err(Error::ProgramHashMismatch { expected, actual });
+    }
+    Ok(actual)
+}
@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
 use crate::{
+    error::Error,
     hint_processor::CustomHintProcessor,
     types::{Bytes32, Felt, G1PointCairo, G2PointCairo, UInt384, Uint256, Uint256Bits32},
 };
@@ -27,7 +28,7 @@ pub struct RecursiveEpochUpdateCairo {
     pub outputs: RecursiveEpochOutputsCairo,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
 pub struct RecursiveEpochOutputsCairo {
     pub beacon_header_root: Uint256,
     pub beacon_state_root: Uint256,
@@ -39,6 +40,74 @@ pub struct RecursiveEpochOutputsCairo {
     pub next_committee_hash: Uint256,
 }
 
+impl RecursiveEpochOutputsCairo {
+    /// Number of felts the recursive update writes to the output segment
+    pub const N_FELTS: usize = 13;
+
+    /// Decodes the output segment written by `write_circuit_output`.
+    ///
+    /// 256 bit values are written as a low and a high 128 bit limb, in that order.
+    pub fn from_output_segment(output: &[Felt252]) -> Result<Self, Error> {
+        if output.len() != Self::N_FELTS {
+            return Err(Error::OutputLength {
+                expected: Self::N_FELTS,
+                actual: output.len(),
+            });
+        }
+        let uint256 = |index: usize| {
+            Uint256(output[index].to_biguint() + (output[index + 1].to_biguint() << 128))
+        };
+
+        Ok(Self {
+            beacon_header_root: uint256(0),
+            beacon_state_root: uint256(2),
+            beacon_height: Felt(output[4]),
+            n_signers: Felt(output[5]),
+            execution_header_root: uint256(6),
+            execution_header_height: Felt(output[8]),
+            current_committee_hash: uint256(9),
+            next_committee_hash: uint256(11),
+        })
+    }
+
+    /// Lists the fields that differ from `other`, one line per field
+    pub fn diff(&self, other: &Self) -> Vec<String> {
+        let mut diff = Vec::new();
+        let mut compare = |name: &str, left: String, right: String| {
+            if left != right {
+                diff.push(format!("{}: {} != {}", name, left, right));
+            }
+        };
+        let hex = |value: &Uint256| format!("{:#066x}", value.0);
+
+        compare("beacon_header_root", hex(&self.beacon_header_root), hex(&other.beacon_header_root));
+        compare("beacon_state_root", hex(&self.beacon_state_root), hex(&other.beacon_state_root));
+        compare("beacon_height", self.beacon_height.0.to_string(), other.beacon_height.0.to_string());
+        compare("n_signers", self.n_signers.0.to_string(), other.n_signers.0.to_string());
+        compare(
+            "execution_header_root",
+            hex(&self.execution_header_root),
+            hex(&other.execution_header_root),
+        );
+        compare(
+            "execution_header_height",
+            self.execution_header_height.0.to_string(),
+            other.execution_header_height.0.to_string(),
+        );
+        compare(
+            "current_committee_hash",
+            hex(&self.current_committee_hash),
+            hex(&other.current_committee_hash),
+        );
+        compare(
+            "next_committee_hash",
+            hex(&self.next_committee_hash),
+            hex(&other.next_committee_hash),
+        );
+        diff
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct RecursiveEpochInputsCairo {
     pub epoch_update: EpochUpdateCairo,
@@ -202,10 +271,7 @@ impl CustomHintProcessor {
             &hint_data.ids_data,
             &hint_data.ap_tracking,
         )?;
-        let program_hash = Felt252::from_hex_unchecked(
-            "0x6305ea579daa2cd35f92ce5c41fa3467a7b44c4d69f9849844aff9d552620e",
-        );
-        vm.insert_value(program_hash_ptr, program_hash)?;
+        vm.insert_value(program_hash_ptr, self.program_hash)?;
 
         Ok(())
     }
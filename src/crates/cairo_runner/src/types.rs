@@ -218,6 +218,12 @@ pub struct G1PointCairo {
     y: UInt384,
 }
 
+impl G1PointCairo {
+    pub fn new(x: UInt384, y: UInt384) -> Self {
+        Self { x, y }
+    }
+}
+
 impl CairoType for G1PointCairo {
     fn from_memory(vm: &VirtualMachine, address: Relocatable) -> Result<Self, HintError> {
         let x = UInt384::from_memory(vm, address)?;
@@ -248,6 +254,12 @@ pub struct G2PointCairo {
     y1: UInt384,
 }
 
+impl G2PointCairo {
+    pub fn new(x0: UInt384, x1: UInt384, y0: UInt384, y1: UInt384) -> Self {
+        Self { x0, x1, y0, y1 }
+    }
+}
+
 impl CairoType for G2PointCairo {
     fn from_memory(vm: &VirtualMachine, address: Relocatable) -> Result<Self, HintError> {
         let x0 = UInt384::from_memory(vm, address)?;
@@ -0,0 +1,283 @@
+//! Read-only inspection of the `epoch_updates` table for the `status`, `history` and
+//! `fact-hash` commands.
+
+use bankai_core::{
+    db::{EpochUpdate, EpochUpdateFilter, Status},
+    prover::{self, verify, RetryTarget},
+    BankaiClient,
+};
+use serde_json::json;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::warn;
+
+use crate::BankaiCliError;
+
+/// Prints the latest epoch update, the in-flight Atlantic job, the lag to head and the
+/// committee hashes of the latest proven state.
+pub async fn status(bankai: &BankaiClient, as_json: bool) -> Result<(), BankaiCliError> {
+    let latest = bankai
+        .db
+        .list_epoch_updates(&EpochUpdateFilter::default(), 1, 0)
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .next();
+
+    let in_flight = bankai
+        .db
+        .get_in_flight_epoch_update()
+        .await
+        .map_err(db_error)?;
+
+    // Tracking the query also stores its step, error and phases with the epoch update
+    let mut atlantic_query = None;
+    let has_atlantic = !bankai.config.atlantic.api_key.is_empty();
+    if let Some(update) = in_flight.as_ref().filter(|u| has_atlantic && u.atlantic_id.is_some()) {
+        match prover::track_atlantic_query(bankai, update).await {
+            Ok(query) => atlantic_query = Some(query),
+            Err(e) => warn!("⚠️  Failed to query Atlantic status: {}", e),
+        }
+    }
+    let in_flight = match (in_flight, &atlantic_query) {
+        (Some(update), Some(_)) => bankai
+            .db
+            .get_epoch_update_by_uuid(&update.uuid)
+            .await
+            .map_err(db_error)?,
+        (in_flight, _) => in_flight,
+    };
+
+    let latest_proven = bankai
+        .db
+        .get_latest_epoch_update()
+        .await
+        .map_err(db_error)?;
+
+    let head_slot = if bankai.config.beacon.rpc_url.is_empty() {
+        None
+    } else {
+        match bankai.client.get_head_slot().await {
+            Ok(slot) => Some(slot),
+            Err(e) => {
+                warn!("⚠️  Failed to fetch head slot: {}", e);
+                None
+            }
+        }
+    };
+    let lag = match (head_slot, latest_proven.as_ref()) {
+        (Some(head), Some(update)) => Some(head as i64 - update.slot_number),
+        _ => None,
+    };
+
+    let outputs = latest_proven.as_ref().and_then(|u| u.outputs.as_ref());
+
+    if as_json {
+        let value = json!({
+            "latest": latest,
+            "in_flight": in_flight,
+            "atlantic_query": atlantic_query,
+            "head_slot": head_slot,
+            "lag_slots": lag,
+            "current_committee_hash": outputs.map(|o| o.current_committee_hash),
+            "next_committee_hash": outputs.map(|o| o.next_committee_hash),
+        });
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        return Ok(());
+    }
+
+    println!("Latest epoch update");
+    match &latest {
+        Some(update) => print_table(std::slice::from_ref(update)),
+        None => println!("  (none)"),
+    }
+
+    println!();
+    println!("In-flight job");
+    match &in_flight {
+        Some(update) => {
+            println!("  UUID:            {}", update.uuid);
+            println!("  Epoch:           {}", update.epoch_number);
+            println!("  Status:          {}", update.status);
+            println!("  Atlantic ID:     {}", display_opt(&update.atlantic_id));
+            if let Some(params) = &update.job_params {
+                println!(
+                    "  Job:             {} / {} ({} steps)",
+                    params.declared_job_size, params.layout, params.n_steps
+                );
+            }
+            println!(
+                "  Atlantic status: {}",
+                display_opt(&atlantic_query.as_ref().map(|q| q.status))
+            );
+            println!("  Atlantic step:   {}", display_opt(&update.atlantic_step));
+            if let Some(error) = &update.atlantic_error {
+                println!("  Atlantic error:  {}", error);
+            }
+            print_phases(update);
+        }
+        None => println!("  (none)"),
+    }
+
+    println!();
+    println!("Chain");
+    println!("  Head slot:       {}", display_opt(&head_slot));
+    println!("  Lag (slots):     {}", display_opt(&lag));
+    println!(
+        "  Current committee hash: {}",
+        display_opt(&outputs.map(|o| o.current_committee_hash))
+    );
+    println!(
+        "  Next committee hash:    {}",
+        display_opt(&outputs.map(|o| o.next_committee_hash))
+    );
+
+    Ok(())
+}
+
+/// Prints the observed Atlantic phases of an epoch update with their durations
+fn print_phases(update: &EpochUpdate) {
+    if update.atlantic_phases.is_empty() {
+        return;
+    }
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    println!("  Phases:");
+    for phase in &update.atlantic_phases {
+        let running = if phase.finished_at.is_none() { " (running)" } else { "" };
+        println!("    {:<24}  {:>6}s{}", phase.name, phase.duration_secs(now), running);
+    }
+}
+
+/// Prints one page of epoch updates matching `filter`.
+pub async fn history(
+    bankai: &BankaiClient,
+    filter: EpochUpdateFilter,
+    page: u64,
+    page_size: u64,
+    as_json: bool,
+) -> Result<(), BankaiCliError> {
+    let total = bankai
+        .db
+        .count_epoch_updates(&filter)
+        .await
+        .map_err(db_error)?;
+    let updates = bankai
+        .db
+        .list_epoch_updates(&filter, page_size, page * page_size)
+        .await
+        .map_err(db_error)?;
+
+    if as_json {
+        let value = json!({
+            "page": page,
+            "page_size": page_size,
+            "total": total,
+            "epoch_updates": updates,
+        });
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        return Ok(());
+    }
+
+    if updates.is_empty() {
+        println!("No epoch updates found");
+    } else {
+        print_table(&updates);
+    }
+    let pages = total.div_ceil(page_size.max(1));
+    println!();
+    println!("Page {} of {} ({} epoch updates)", page + 1, pages.max(1), total);
+
+    Ok(())
+}
+
+/// Prints the fact hash of an epoch update, computed from its stored outputs.
+///
+/// With `check_proof`, the fact is also computed from the public input of the stored proof,
+/// which must match. A fact hash stored by `wrap` is compared as well.
+pub async fn fact_hash(
+    bankai: &BankaiClient,
+    target: Option<RetryTarget>,
+    check_proof: bool,
+    program_path: &str,
+) -> Result<(), BankaiCliError> {
+    let update = prover::find_epoch_update(bankai, target, Some(Status::Done))
+        .await
+        .map_err(prover_error)?;
+    let fact = prover::epoch_fact(bankai, &update, program_path).map_err(prover_error)?;
+    let fact_hash = format!("{:#x}", fact.fact_hash);
+
+    println!("Epoch update {} (epoch {})", update.uuid, update.epoch_number);
+    println!("  Program hash:     {:#x}", fact.program_hash);
+    println!("  Output hash:      {:#x}", fact.output_hash);
+    println!("  Fact hash:        {}", fact_hash);
+    println!("  Stored fact hash: {}", display_opt(&update.fact_hash));
+
+    if let Some(stored) = &update.fact_hash {
+        if *stored != fact_hash {
+            return Err(BankaiCliError::ProofGenerationError(format!(
+                "Stored fact hash {} differs from the computed {}",
+                stored, fact_hash
+            )));
+        }
+    }
+
+    if check_proof {
+        let proof_id = update.proof_id.ok_or_else(|| {
+            BankaiCliError::ProofGenerationError(format!("Epoch update {} has no stored proof", update.uuid))
+        })?;
+        let proof = bankai
+            .db
+            .get_proof(proof_id)
+            .await
+            .map_err(db_error)?
+            .ok_or_else(|| BankaiCliError::ProofGenerationError(format!("Proof {} not found", proof_id)))?;
+        let proof: serde_json::Value = serde_json::from_str(&proof.proof)?;
+        let proof_fact = verify::proof_fact(&proof)
+            .map_err(|e| BankaiCliError::ProofGenerationError(format!("Invalid proof {}: {}", proof_id, e)))?;
+
+        println!("  Proof fact hash:  {:#x}", proof_fact.fact_hash);
+        if proof_fact != fact {
+            return Err(BankaiCliError::ProofGenerationError(format!(
+                "Proof {} registers fact {:#x}, expected {}",
+                proof_id, proof_fact.fact_hash, fact_hash
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+fn print_table(updates: &[EpochUpdate]) {
+    println!(
+        "  {:<36}  {:>8}  {:>10}  {:<10}  {:<36}  {}",
+        "UUID", "EPOCH", "SLOT", "STATUS", "ATLANTIC ID", "ERROR"
+    );
+    for update in updates {
+        println!(
+            "  {:<36}  {:>8}  {:>10}  {:<10}  {:<36}  {}",
+            update.uuid,
+            update.epoch_number,
+            update.slot_number,
+            update.status,
+            display_opt(&update.atlantic_id),
+            update.error_reason.as_deref().unwrap_or(""),
+        );
+    }
+}
+
+fn display_opt<T: std::fmt::Display>(value: &Option<T>) -> String {
+    match value {
+        Some(value) => value.to_string(),
+        None => "-".to_string(),
+    }
+}
+
+fn db_error(e: impl std::fmt::Display) -> BankaiCliError {
+    BankaiCliError::ProofGenerationError(format!("Database error: {}", e))
+}
+
+fn prover_error(e: impl std::fmt::Display) -> BankaiCliError {
+    BankaiCliError::ProofGenerationError(e.to_string())
+}
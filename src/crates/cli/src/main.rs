@@ -1,9 +1,13 @@
-use bankai_core::{db::Status, fetcher::recursive_epoch_input::{RecursiveEpochInputs, RecursiveEpochUpdate}, utils::{constants::{GENESIS_EPOCH, SLOTS_PER_EPOCH}, hashing::get_committee_hash}, BankaiClient};
+use bankai_core::{api, clients::atlantic::AtlanticStatus, db::{EpochUpdateFilter, Status}, metrics::{self, metrics}, prover::{self, wrap::WrapOutcome, RetryTarget}, fetcher::recursive_epoch_input::{RecursiveEpochInputs, RecursiveEpochUpdate}, utils::{config::{BankaiConfig, ConfigError}, constants::{GENESIS_EPOCH, SLOTS_PER_EPOCH}, hashing::get_committee_hash}, BankaiClient, BankaiError};
 use clap::{Parser, Subcommand};
 use dotenv::from_filename;
 use tracing::{Level, info, warn, error, debug};
 use tracing_subscriber::FmtSubscriber;
-use std::time::Instant;
+use std::{path::PathBuf, sync::Arc, time::Instant};
+
+mod inspect;
+mod reconcile;
+mod watch;
 
 #[derive(Subcommand)]
 enum Commands {
@@ -14,11 +18,122 @@ enum Commands {
     /// Fetch proof data from the network
     #[command(subcommand)]
     Fetch(FetchCommands),
+
+    /// Manage stored proofs
+    #[command(subcommand)]
+    Proofs(ProofCommands),
+
+    /// Reset a failed epoch update and submit it to the prover again
+    Retry {
+        /// UUID or epoch number of the failed epoch update
+        target: String,
+        /// Fetch the inputs from the beacon node again instead of reusing the stored ones
+        #[arg(long)]
+        refetch: bool,
+    },
+
+    /// Show the latest epoch update, the in-flight job and the lag to head
+    Status {
+        /// Print the result as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// List past epoch updates
+    History {
+        /// Only show updates with this status (fetching, trace_gen, proving, done, error)
+        #[arg(long)]
+        status: Option<Status>,
+        /// Only show updates from this epoch onwards
+        #[arg(long)]
+        from_epoch: Option<u64>,
+        /// Only show updates up to and including this epoch
+        #[arg(long)]
+        to_epoch: Option<u64>,
+        /// Only show updates whose error reason contains this text
+        #[arg(long)]
+        error: Option<String>,
+        /// Page to show, starting at 0
+        #[arg(long, default_value_t = 0)]
+        page: u64,
+        /// Number of updates per page
+        #[arg(long, default_value_t = 20)]
+        page_size: u64,
+        /// Print the result as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Follow the beacon node event stream and prove finalized epochs as they arrive
+    Watch {
+        /// Also submit finished proofs for verification on Starknet
+        #[arg(long)]
+        wrap: bool,
+    },
+
+    /// Submit the proof of a done epoch update for verification on Starknet, or resume it
+    Wrap {
+        /// UUID or epoch number of the epoch update (defaults to the latest done one)
+        target: Option<String>,
+        /// Wait until Atlantic has verified the proof
+        #[arg(long)]
+        wait: bool,
+    },
+
+    /// Compare the Bankai contract on Starknet with the local epoch updates
+    Reconcile {
+        /// Number of latest done epoch updates to check
+        #[arg(long, default_value_t = 100)]
+        limit: u64,
+        /// Print the result as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Compute the fact hash the proof of an epoch update registers on Starknet
+    FactHash {
+        /// UUID or epoch number of the epoch update (defaults to the latest done one)
+        target: Option<String>,
+        /// Also compute the fact from the public input of the stored proof
+        #[arg(long)]
+        check_proof: bool,
+    },
+
+    /// Compute the Poseidon program hash of the compiled recursive update program
+    ProgramHash {
+        /// Compiled program to hash (defaults to program.recursive_update from the config)
+        #[arg(long)]
+        program: Option<String>,
+    },
+}
+
+impl Commands {
+    /// Returns true for commands that work on the database only and therefore run without a
+    /// beacon node URL or Atlantic API key. `status` still reports the head slot and the
+    /// Atlantic job if they are configured.
+    fn is_local(&self) -> bool {
+        matches!(
+            self,
+            Commands::Status { .. }
+                | Commands::History { .. }
+                | Commands::FactHash { .. }
+                | Commands::Proofs(_)
+                | Commands::Fetch(FetchCommands::Inputs { .. })
+        )
+    }
 }
 
 #[derive(Subcommand)]
 enum FetchCommands {
     Genesis,
+    /// Export the stored proving inputs of an epoch update
+    Inputs {
+        /// UUID of the epoch update
+        uuid: String,
+        /// Export output to a JSON file
+        #[arg(long, short)]
+        export: Option<String>,
+    },
     /// Fetch a sync committee update proof for a given slot
     RecursiveEpoch {
         /// Export output to a JSON file
@@ -27,9 +142,27 @@ enum FetchCommands {
     },
 }
 
+#[derive(Subcommand)]
+enum ProofCommands {
+    /// Keep a proof even if it is no longer needed for recursion
+    Pin { proof_id: i64 },
+    /// Allow a pinned proof to be pruned again
+    Unpin { proof_id: i64 },
+    /// Remove all proofs that are neither needed for recursion nor pinned
+    Prune {
+        /// Only list the proofs that would be removed
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
 #[derive(Subcommand)]
 enum ProveCommands {
-    Genesis,
+    Genesis {
+        /// Run the Cairo program and print the job parameters instead of submitting
+        #[arg(long)]
+        dry_run: bool,
+    },
     RecursiveEpoch {
         #[arg(long, short)]
         fast_forward: Option<u64>,
@@ -37,6 +170,9 @@ enum ProveCommands {
         simulate: bool,
         #[arg(long, short)]
         export: Option<String>,
+        /// Run the Cairo program and print the job parameters instead of submitting
+        #[arg(long)]
+        dry_run: bool,
     },
 }
 
@@ -50,18 +186,31 @@ enum ProveCommands {
                   This tool helps generate, verify, and manage proofs for recursive epoch updates."
 )]
 struct Cli {
-    /// Optional RPC URL (defaults to RPC_URL_BEACON environment variable)
+    /// Config file (defaults to BANKAI_CONFIG, then ./bankai.toml if present)
+    #[arg(long, short)]
+    config: Option<PathBuf>,
+
+    /// Beacon RPC URL, overrides the config file and BEACON_RPC_URL
     #[arg(long, short)]
     rpc_url: Option<String>,
 
+    /// Database URL, overrides the config file and DATABASE_URL
+    #[arg(long)]
+    database_url: Option<String>,
+
+    /// Serve Prometheus metrics on this port while the command runs
+    #[arg(long)]
+    metrics_port: Option<u16>,
+
     #[command(subcommand)]
     command: Commands,
 }
 
 #[tokio::main]
 async fn main() -> Result<(), BankaiCliError> {
-    // Load .env.sepolia file
+    // Environment files only fill variables that are not set yet, see BankaiConfig::load
     from_filename(".env.local").ok();
+    from_filename(".env.sepolia").ok();
 
     let subscriber = FmtSubscriber::builder()
         .with_max_level(Level::INFO)
@@ -73,31 +222,142 @@ async fn main() -> Result<(), BankaiCliError> {
     let start_time = Instant::now();
 
     let cli = Cli::parse();
-    
+
+    let mut config = BankaiConfig::load(cli.config.as_deref())?;
+    if let Some(rpc_url) = cli.rpc_url {
+        config.beacon.rpc_url = rpc_url;
+    }
+    if let Some(database_url) = cli.database_url {
+        config.database.url = database_url;
+    }
+
+    // Hashing the program needs neither the beacon node nor the database
+    if let Commands::ProgramHash { program } = &cli.command {
+        return print_program_hash(&config, program.as_deref());
+    }
+
     info!("🔌 Initializing Bankai client...");
-    let bankai = BankaiClient::new(false).await;
+    let bankai = if cli.command.is_local() {
+        BankaiClient::new_local(config).await?
+    } else {
+        BankaiClient::new(config).await?
+    };
+    let bankai = Arc::new(bankai);
+    let program_path = bankai.config.program.recursive_update.clone();
     info!("✅ Bankai client initialized successfully");
 
+    if let Some(port) = cli.metrics_port {
+        let routes = api::router(bankai.clone());
+        tokio::spawn(async move {
+            if let Err(e) = metrics::serve(port, routes).await {
+                error!("💥 Metrics server failed: {}", e);
+            }
+        });
+    }
+
     match cli.command {
+        // Handled before the client is initialized
+        Commands::ProgramHash { .. } => {}
+        Commands::Proofs(cmd) => match cmd {
+            ProofCommands::Pin { proof_id } | ProofCommands::Unpin { proof_id } => {
+                let pinned = matches!(cmd, ProofCommands::Pin { .. });
+                bankai.db.set_proof_pinned(proof_id, pinned).await
+                    .map_err(|e| BankaiCliError::ProofGenerationError(format!("Database error: {}", e)))?;
+                info!("📌 Proof {} {}", proof_id, if pinned { "pinned" } else { "unpinned" });
+            }
+            ProofCommands::Prune { dry_run } => {
+                let ids = if dry_run {
+                    bankai.db.get_prunable_proofs().await
+                } else {
+                    bankai.db.prune_proofs().await
+                }
+                .map_err(|e| BankaiCliError::ProofGenerationError(format!("Database error: {}", e)))?;
+
+                let verb = if dry_run { "Would remove" } else { "Removed" };
+                info!("🧹 {} {} proofs: {:?}", verb, ids.len(), ids);
+            }
+        },
+        Commands::Retry { target, refetch } => {
+            info!("🔁 Retrying failed epoch update {}...", target);
+            let atlantic_id = prover::retry_epoch_update(&bankai, RetryTarget::from(target.as_str()), refetch, &program_path)
+                .await
+                .map_err(|e| BankaiCliError::ProofGenerationError(format!("Retry failed: {}", e)))?;
+            info!("✅ Epoch update resubmitted to Atlantic with ID: {}", atlantic_id);
+        }
+        Commands::Watch { wrap } => {
+            watch::watch(&bankai, &program_path, wrap).await?;
+        }
+        Commands::Wrap { target, wait } => {
+            let update = prover::wrap::find_wrap_target(&bankai, target.as_deref().map(RetryTarget::from))
+                .await
+                .map_err(|e| BankaiCliError::ProofGenerationError(format!("Failed to find epoch update: {}", e)))?;
+            info!("🔗 Verifying epoch {} on Starknet (UUID: {})", update.epoch_number, update.uuid);
+            match prover::wrap::wrap_epoch_update(&bankai, &update, wait, true)
+                .await
+                .map_err(|e| BankaiCliError::ProofGenerationError(format!("Starknet verification failed: {}", e)))?
+            {
+                WrapOutcome::Verified { fact_hash } => {
+                    info!("✅ Fact hash: {}", fact_hash.as_deref().unwrap_or("unknown"));
+                }
+                WrapOutcome::Pending { wrap_atlantic_id } => {
+                    warn!("⏳ Wrapped proof {} not verified yet. Run wrap again to resume", wrap_atlantic_id);
+                }
+            }
+        }
+        Commands::Reconcile { limit, json } => {
+            reconcile::reconcile(&bankai, limit, json).await?;
+        }
+        Commands::FactHash { target, check_proof } => {
+            inspect::fact_hash(&bankai, target.as_deref().map(RetryTarget::from), check_proof, &program_path).await?;
+        }
+        Commands::Status { json } => {
+            inspect::status(&bankai, json).await?;
+        }
+        Commands::History { status, from_epoch, to_epoch, error, page, page_size, json } => {
+            let filter = EpochUpdateFilter {
+                status,
+                from_epoch,
+                to_epoch,
+                error_reason: error,
+            };
+            inspect::history(&bankai, filter, page, page_size, json).await?;
+        }
         Commands::Fetch(cmd) => match cmd {
             FetchCommands::Genesis => {
                 info!("📥 Fetching genesis committee information...");
                 let proof: RecursiveEpochUpdate = RecursiveEpochInputs::new(&bankai.client, &bankai.db, None)
                     .await
-                    .map_err(|e| BankaiCliError::ProofGenerationError(format!("Failed to generate genesis inputs: {}", e)))?
-                    .into();
+                    .and_then(|inputs| Ok(RecursiveEpochUpdate::try_from(inputs)?))
+                    .map_err(|e| BankaiCliError::ProofGenerationError(format!("Failed to generate genesis inputs: {}", e)))?;
                 let committee_hash = get_committee_hash(proof.inputs.epoch_update.aggregate_pub.0);
                 info!("✅ Genesis committee hash: {}", committee_hash);
             }
+            FetchCommands::Inputs { uuid, export } => {
+                info!("📥 Loading stored proving inputs for {}...", uuid);
+                let inputs = bankai.db.get_inputs(&uuid).await
+                    .map_err(|e| BankaiCliError::ProofGenerationError(format!("Database error: {}", e)))?
+                    .ok_or_else(|| BankaiCliError::ProofGenerationError(format!("No inputs stored for epoch update {}", uuid)))?;
+
+                let json = serde_json::to_string_pretty(&inputs)?;
+                match export {
+                    Some(path) => {
+                        std::fs::write(&path, json)?;
+                        info!("💾 Inputs exported to: {}", path);
+                    }
+                    None => println!("{}", json),
+                }
+            }
             FetchCommands::RecursiveEpoch { export } => {
                 info!("📥 Fetching recursive epoch update data...");
                 let proof: RecursiveEpochUpdate = RecursiveEpochInputs::new(&bankai.client, &bankai.db, None)
                     .await
-                    .map_err(|e| BankaiCliError::ProofGenerationError(format!("Failed to generate recursive epoch inputs: {}", e)))?
-                    .into();
+                    .and_then(|inputs| Ok(RecursiveEpochUpdate::try_from(inputs)?))
+                    .map_err(|e| BankaiCliError::ProofGenerationError(format!("Failed to generate recursive epoch inputs: {}", e)))?;
 
                 debug!("🧮 Running Cairo program for validation...");
-                let _pie = cairo_runner::run("cairo/build/recursive_update.json", proof.into())
+                let expected_program_hash = bankai.config.program.expected_hash()?;
+                let runner_config = bankai.config.program.runner_config(&program_path)?;
+                let _pie = cairo_runner::run(&runner_config, proof.into(), expected_program_hash)
                     .map_err(|e| BankaiCliError::ProofGenerationError(format!("Cairo runner failed: {}", e)))?;
                 debug!("✅ Cairo program executed successfully");
 
@@ -114,7 +374,7 @@ async fn main() -> Result<(), BankaiCliError> {
             }
         },
         Commands::Prove(cmd) => match cmd {
-            ProveCommands::Genesis => {
+            ProveCommands::Genesis { dry_run } => {
                 info!("🔍 Checking for existing genesis proof...");
                 if let Some(_) = bankai.db.get_latest_epoch_update().await
                     .map_err(|e| BankaiCliError::ProofGenerationError(format!("Database error: {}", e)))? {
@@ -124,165 +384,207 @@ async fn main() -> Result<(), BankaiCliError> {
                 info!("🏗️  Generating genesis proof...");
                 let proof: RecursiveEpochUpdate = RecursiveEpochInputs::new(&bankai.client, &bankai.db, None)
                     .await
-                    .map_err(|e| BankaiCliError::ProofGenerationError(format!("Failed to generate genesis inputs: {}", e)))?
-                    .into();
+                    .and_then(|inputs| Ok(RecursiveEpochUpdate::try_from(inputs)?))
+                    .map_err(|e| BankaiCliError::ProofGenerationError(format!("Failed to generate genesis inputs: {}", e)))?;
 
                 let epoch = proof.inputs.epoch_update.header.slot / SLOTS_PER_EPOCH;
                 let slot = proof.inputs.epoch_update.header.slot;
                 info!("📊 Genesis proof details - Epoch: {}, Slot: {}", epoch, slot);
+                if dry_run {
+                    return print_job_plan(&bankai, &program_path, proof);
+                }
                 
                 let uuid = bankai.db.create_epoch_update(epoch.clone(), slot, proof.outputs.clone()).await
                     .map_err(|e| BankaiCliError::ProofGenerationError(format!("Failed to create epoch update record: {}", e)))?;
                 info!("🆔 Created epoch update record with UUID: {}", uuid);
 
-                let result = async {
-                    info!("🔄 Updating status to TraceGen...");
-                    bankai.db.update_status(&uuid, Status::TraceGen).await?;
-                    
-                    info!("🧮 Running Cairo program to generate PIE...");
-                    let pie = cairo_runner::run("cairo/build/recursive_update.json", proof.into())
-                        .map_err(|e| format!("Cairo runner failed: {}", e))?;
-                    info!("✅ PIE generated successfully");
-
-                    info!("🚀 Submitting proof to Atlantic...");
-                    let altantic_id = bankai.atlantic_client.submit_stone(pie, format!("epoch_{}", epoch)).await
-                        .map_err(|e| format!("Atlantic submission failed: {}", e))?;
-                    info!("✅ Proof submitted to Atlantic with ID: {}", altantic_id);
-                    
-                    bankai.db.add_atlantic_id(&uuid, &altantic_id).await?;
-                    bankai.db.update_status(&uuid, Status::Proving).await?;
-                    info!("🔄 Status updated to Proving");
-
-                    Ok::<(), Box<dyn std::error::Error>>(())
-                }.await;
-
-                if let Err(e) = result {
-                    let error_msg = format!("Genesis proof generation failed: {}", e);
-                    error!("❌ {}", error_msg);
-                    if let Err(db_err) = bankai.db.update_error(&uuid, &error_msg).await {
-                        error!("💥 Failed to update error status in database: {}", db_err);
-                    }
-                    return Err(BankaiCliError::ProofGenerationError(error_msg));
-                }
+                prover::prove_epoch_update(&bankai, &uuid, proof, &program_path)
+                    .await
+                    .map_err(|e| BankaiCliError::ProofGenerationError(format!("Genesis proof generation failed: {}", e)))?;
             }
-            ProveCommands::RecursiveEpoch { simulate, export, fast_forward } => {
-                info!("🔍 Looking for previous epoch update...");
-                let prev_epoch = match bankai.db.get_latest_epoch_update().await
-                    .map_err(|e| BankaiCliError::ProofGenerationError(format!("Database error: {}", e)))? {
-                    Some(epoch_update) => {
-                        info!("✅ Found previous epoch update - Epoch: {}, UUID: {}", epoch_update.epoch_number, epoch_update.uuid);
-                        epoch_update
-                    },
-                    None => return Err(BankaiCliError::ProofGenerationError("No previous epoch update found. Please run genesis first".to_string())),
-                };
-
-                let atlantic_id = prev_epoch.atlantic_id.as_ref()
-                    .ok_or_else(|| BankaiCliError::ProofGenerationError("Previous epoch update has no Atlantic ID".to_string()))?;
-                
-                info!("🔍 Checking Atlantic batch status for ID: {}", atlantic_id);
-                let status = bankai.atlantic_client.check_batch_status(atlantic_id).await
-                    .map_err(|e| BankaiCliError::ProofGenerationError(format!("Failed to check Atlantic batch status: {}", e)))?;
-                
-                info!("📊 Atlantic batch status: {}", status);
-                match status.as_str() {
-                    "FAILED" => {
-                        let error_msg = format!("Proving failed for Atlantic ID: {}", atlantic_id);
-                        error!("❌ {}", error_msg);
-                        bankai.db.update_error(&prev_epoch.uuid, "Proving failed").await
-                            .map_err(|e| BankaiCliError::ProofGenerationError(format!("Failed to update error status: {}", e)))?;
-                        return Err(BankaiCliError::ProofGenerationError(error_msg));
-                    }
-                    "DONE" => {
-                        info!("🎉 Proof completed! Fetching from Atlantic...");
-                        let proof = bankai.atlantic_client.fetch_proof(atlantic_id).await
-                            .map_err(|e| BankaiCliError::ProofGenerationError(format!("Failed to fetch proof: {}", e)))?;
-                        
-                        let proof_id = bankai.db.add_proof(&proof.proof.to_string()).await
-                            .map_err(|e| BankaiCliError::ProofGenerationError(format!("Failed to add proof to database: {}", e)))?;
-                        
-                        bankai.db.update_proof_id(&prev_epoch.uuid, proof_id).await
-                            .map_err(|e| BankaiCliError::ProofGenerationError(format!("Failed to update proof ID: {}", e)))?;
-                        bankai.db.update_status(&prev_epoch.uuid, Status::Done).await
-                            .map_err(|e| BankaiCliError::ProofGenerationError(format!("Failed to update status: {}", e)))?;
-                        
-                        info!("✅ Proof fetched and stored successfully");
-                    }
-                    _ => {
-                        warn!("⏳ Proof not ready yet (status: {}). Please try again later", status);
-                        return Ok(());
-                    }
-                }
-
-                if simulate {
-                    info!("🧪 Running simulation mode...");
+            ProveCommands::RecursiveEpoch { simulate, export, fast_forward, dry_run } => {
+                if dry_run {
+                    info!("🏗️  Generating recursive epoch inputs for a dry run...");
                     let proof: RecursiveEpochUpdate = RecursiveEpochInputs::new(&bankai.client, &bankai.db, fast_forward)
                         .await
-                        .map_err(|e| BankaiCliError::ProofGenerationError(format!("Failed to generate simulation inputs: {}", e)))?
-                        .into();
-                    
-                    let sync_committee_info = serde_json::to_string_pretty(&proof.inputs.sync_committee_update)?;
-                    info!("🔍 Sync committee update info:");
-                    println!("{}", sync_committee_info);
-                    return Ok(());
-                }
-
-                if let Some(ff) = fast_forward {
-                    info!("⚡ Fast-forwarding {} epochs", ff);
+                        .and_then(|inputs| Ok(RecursiveEpochUpdate::try_from(inputs)?))
+                        .map_err(|e| BankaiCliError::ProofGenerationError(format!("Failed to generate recursive epoch inputs: {}", e)))?;
+                    return print_job_plan(&bankai, &program_path, proof);
                 }
+                prove_recursive_epoch(&bankai, &program_path, simulate, fast_forward, export).await?;
+            }
+        }
+    }
 
-                info!("🏗️  Generating recursive epoch proof...");
-                let proof: RecursiveEpochUpdate = RecursiveEpochInputs::new(&bankai.client, &bankai.db, fast_forward)
-                    .await
-                    .map_err(|e| BankaiCliError::ProofGenerationError(format!("Failed to generate recursive epoch inputs: {}", e)))?
-                    .into();
-                
-                let epoch = proof.inputs.epoch_update.header.slot / SLOTS_PER_EPOCH;
-                let slot = proof.inputs.epoch_update.header.slot;
-                info!("📊 Recursive epoch proof details - Target Epoch: {}, Slot: {}", epoch, slot);
-                
-                let uuid = bankai.db.create_epoch_update(epoch.clone(), slot, proof.outputs.clone()).await
-                    .map_err(|e| BankaiCliError::ProofGenerationError(format!("Failed to create epoch update record: {}", e)))?;
-                info!("🆔 Created epoch update record with UUID: {}", uuid);
+    let duration = start_time.elapsed();
+    info!("🏁 Bankai CLI completed successfully in {:.2?}", duration);
+    Ok(())
+}
 
-                let result = async {
-                    info!("🔄 Updating status to TraceGen...");
-                    // bankai.db.update_status(&uuid, Status::TraceGen).await?;
-                    
-                    info!("🧮 Running Cairo program to generate PIE...");
-                    let pie = cairo_runner::run("cairo/build/recursive_update.json", proof.into())
-                        .map_err(|e| format!("Cairo runner failed: {}", e))?;
-                    info!("✅ PIE generated successfully");
-
-                    info!("🚀 Submitting proof to Atlantic...");
-                    let altantic_id = bankai.atlantic_client.submit_stone(pie, format!("epoch_{}", epoch)).await
-                        .map_err(|e| format!("Atlantic submission failed: {}", e))?;
-                    info!("✅ Proof submitted to Atlantic with ID: {}", altantic_id);
-                    
-                    bankai.db.add_atlantic_id(&uuid, &altantic_id).await?;
-                    bankai.db.update_status(&uuid, Status::Proving).await?;
-                    info!("🔄 Status updated to Proving");
-
-                    Ok::<(), Box<dyn std::error::Error>>(())
-                }.await;
-
-                if let Err(e) = result {
-                    let error_msg = format!("Recursive epoch proof generation failed: {}", e);
-                    error!("❌ {}", error_msg);
-                    if let Err(db_err) = bankai.db.update_error(&uuid, &error_msg).await {
-                        error!("💥 Failed to update error status in database: {}", db_err);
-                    }
-                    return Err(BankaiCliError::ProofGenerationError(error_msg));
-                }
+/// Stores the proof of the previous epoch update once Atlantic is done, then submits the next one
+///
+/// # Arguments
+/// * `bankai` - The Bankai client
+/// * `program_path` - Path to the compiled recursive update program
+/// * `simulate` - Only print the sync committee update instead of proving
+/// * `fast_forward` - Number of epochs to skip after the latest epoch update
+/// * `export` - Path to export the proof to
+pub(crate) async fn prove_recursive_epoch(
+    bankai: &BankaiClient,
+    program_path: &str,
+    simulate: bool,
+    fast_forward: Option<u64>,
+    export: Option<String>,
+) -> Result<(), BankaiCliError> {
+    info!("🔍 Looking for previous epoch update...");
+    let prev_epoch = match bankai.db.get_latest_epoch_update().await
+        .map_err(|e| BankaiCliError::ProofGenerationError(format!("Database error: {}", e)))? {
+        Some(epoch_update) => {
+            info!("✅ Found previous epoch update - Epoch: {}, UUID: {}", epoch_update.epoch_number, epoch_update.uuid);
+            epoch_update
+        },
+        None => return Err(BankaiCliError::ProofGenerationError("No previous epoch update found. Please run genesis first".to_string())),
+    };
+
+    match bankai.client.get_head_slot().await {
+        Ok(head_slot) => metrics()
+            .proving_lag_slots
+            .set(head_slot as i64 - prev_epoch.slot_number),
+        Err(e) => warn!("⚠️  Failed to fetch head slot for lag metric: {}", e),
+    }
 
-                if let Some(path) = export {
-                    warn!("⚠️  Export functionality not implemented for recursive epoch proving yet");
-                }
+    let atlantic_id = prev_epoch.atlantic_id.as_ref()
+        .ok_or_else(|| BankaiCliError::ProofGenerationError("Previous epoch update has no Atlantic ID".to_string()))?;
+    
+    info!("🔍 Checking Atlantic batch status for ID: {}", atlantic_id);
+    let query = prover::track_atlantic_query(bankai, &prev_epoch).await
+        .map_err(|e| BankaiCliError::ProofGenerationError(format!("Failed to check Atlantic batch status: {}", e)))?;
+    
+    info!("📊 Atlantic batch status: {} ({})", query.status, query.phase());
+    match query.status {
+        AtlanticStatus::Failed => {
+            let reason = query.error_reason.as_deref().unwrap_or("no reason given");
+            let error_msg = format!("Proving failed for Atlantic ID {}: {}", atlantic_id, reason);
+            error!("❌ {}", error_msg);
+            bankai.db.update_error(&prev_epoch.uuid, &format!("Proving failed: {}", reason)).await
+                .map_err(|e| BankaiCliError::ProofGenerationError(format!("Failed to update error status: {}", e)))?;
+            return Err(BankaiCliError::ProofGenerationError(error_msg));
+        }
+        AtlanticStatus::Done => {
+            info!("🎉 Proof completed! Fetching from Atlantic...");
+            let proof = bankai.atlantic_client.fetch_proof(atlantic_id).await
+                .map_err(|e| BankaiCliError::ProofGenerationError(format!("Failed to fetch proof: {}", e)))?;
+
+            info!("🔏 Verifying proof public input...");
+            let expected_outputs = prev_epoch.outputs.as_ref()
+                .ok_or_else(|| BankaiCliError::ProofGenerationError("Previous epoch update has no stored outputs".to_string()))?;
+            if let Err(e) = prover::verify_fetched_proof(bankai, &proof.proof, expected_outputs, program_path) {
+                let error_msg = format!("Rejected proof for Atlantic ID {}: {}", atlantic_id, e);
+                error!("❌ {}", error_msg);
+                bankai.db.update_error(&prev_epoch.uuid, &error_msg).await
+                    .map_err(|e| BankaiCliError::ProofGenerationError(format!("Failed to update error status: {}", e)))?;
+                return Err(BankaiCliError::ProofGenerationError(error_msg));
             }
+
+            let proof_id = bankai.db.add_proof(&proof.proof.to_string()).await
+                .map_err(|e| BankaiCliError::ProofGenerationError(format!("Failed to add proof to database: {}", e)))?;
+            
+            bankai.db.update_proof_id(&prev_epoch.uuid, proof_id).await
+                .map_err(|e| BankaiCliError::ProofGenerationError(format!("Failed to update proof ID: {}", e)))?;
+            bankai.db.update_status(&prev_epoch.uuid, Status::Done).await
+                .map_err(|e| BankaiCliError::ProofGenerationError(format!("Failed to update status: {}", e)))?;
+            bankai.db.finish_attempt(&prev_epoch.uuid, None).await
+                .map_err(|e| BankaiCliError::ProofGenerationError(format!("Failed to finish proving attempt: {}", e)))?;
+            
+            metrics().epochs_proven_total.inc();
+            info!("✅ Proof fetched and stored successfully");
+        }
+        _ => {
+            warn!("⏳ Proof not ready yet (status: {}). Please try again later", query.phase());
+            return Ok(());
         }
     }
 
-    let duration = start_time.elapsed();
-    info!("🏁 Bankai CLI completed successfully in {:.2?}", duration);
+    if simulate {
+        info!("🧪 Running simulation mode...");
+        let proof: RecursiveEpochUpdate = RecursiveEpochInputs::new(&bankai.client, &bankai.db, fast_forward)
+            .await
+            .and_then(|inputs| Ok(RecursiveEpochUpdate::try_from(inputs)?))
+            .map_err(|e| BankaiCliError::ProofGenerationError(format!("Failed to generate simulation inputs: {}", e)))?;
+        
+        let sync_committee_info = serde_json::to_string_pretty(&proof.inputs.sync_committee_update)?;
+        info!("🔍 Sync committee update info:");
+        println!("{}", sync_committee_info);
+        return Ok(());
+    }
+
+    if let Some(ff) = fast_forward {
+        info!("⚡ Fast-forwarding {} epochs", ff);
+    }
+
+    info!("🏗️  Generating recursive epoch proof...");
+    let proof: RecursiveEpochUpdate = RecursiveEpochInputs::new(&bankai.client, &bankai.db, fast_forward)
+        .await
+        .and_then(|inputs| Ok(RecursiveEpochUpdate::try_from(inputs)?))
+        .map_err(|e| BankaiCliError::ProofGenerationError(format!("Failed to generate recursive epoch inputs: {}", e)))?;
+    
+    let epoch = proof.inputs.epoch_update.header.slot / SLOTS_PER_EPOCH;
+    let slot = proof.inputs.epoch_update.header.slot;
+    info!("📊 Recursive epoch proof details - Target Epoch: {}, Slot: {}", epoch, slot);
+    
+    let uuid = bankai.db.create_epoch_update(epoch.clone(), slot, proof.outputs.clone()).await
+        .map_err(|e| BankaiCliError::ProofGenerationError(format!("Failed to create epoch update record: {}", e)))?;
+    info!("🆔 Created epoch update record with UUID: {}", uuid);
+
+    prover::prove_epoch_update(bankai, &uuid, proof, program_path)
+        .await
+        .map_err(|e| BankaiCliError::ProofGenerationError(format!("Recursive epoch proof generation failed: {}", e)))?;
+
+    if let Some(path) = export {
+        warn!("⚠️  Export functionality not implemented for recursive epoch proving yet");
+    }
+    Ok(())
+}
+
+/// Runs the Cairo program for `proof` and prints the parameters it would be submitted with
+fn print_job_plan(
+    bankai: &BankaiClient,
+    program_path: &str,
+    proof: RecursiveEpochUpdate,
+) -> Result<(), BankaiCliError> {
+    let epoch = proof.inputs.epoch_update.header.slot / SLOTS_PER_EPOCH;
+    let params = prover::plan_epoch_update(bankai, proof, program_path)
+        .map_err(|e| BankaiCliError::ProofGenerationError(format!("Dry run failed: {}", e)))?;
+
+    println!("Dry run for epoch {}, nothing was submitted", epoch);
+    println!("  Declared job size: {}", params.declared_job_size);
+    println!("  Layout:            {}", params.layout);
+    println!("  Steps:             {}", params.n_steps);
+    println!("  Builtins:          {}", params.builtins.join(", "));
+    println!("  Because:");
+    for reason in &params.reasons {
+        println!("    - {}", reason);
+    }
+    Ok(())
+}
+
+/// Prints the program hash of `program` and whether it matches the configured one
+fn print_program_hash(config: &BankaiConfig, program: Option<&str>) -> Result<(), BankaiCliError> {
+    let path = program.unwrap_or(&config.program.recursive_update);
+    let program = cairo_runner::load_program(&path.into())
+        .map_err(|e| BankaiCliError::ProofGenerationError(format!("Failed to load program {}: {}", path, e)))?;
+    let program_hash = cairo_runner::program_hash::compute_program_hash(&program)
+        .map_err(|e| BankaiCliError::ProofGenerationError(format!("Failed to hash program {}: {}", path, e)))?;
+
+    println!("RecursiveUpdateProgramHash: {:#x}", program_hash);
+    match config.program.expected_hash()? {
+        Some(expected) if expected == program_hash => info!("✅ Matches the configured program hash"),
+        Some(expected) => warn!(
+            "⚠️  Configured program hash is {:#x}, update program.recursive_update_hash",
+            expected
+        ),
+        None => info!("ℹ️  No expected program hash configured"),
+    }
     Ok(())
 }
 
@@ -294,4 +596,8 @@ pub enum BankaiCliError {
     IoError(#[from] std::io::Error),
     #[error("Proof generation error: {0}")]
     ProofGenerationError(String),
+    #[error("{0}")]
+    ConfigError(#[from] ConfigError),
+    #[error("{0}")]
+    ClientError(#[from] BankaiError),
 }
\ No newline at end of file
@@ -0,0 +1,190 @@
+//! Comparison of the Bankai contract state on Starknet with the local `epoch_updates` table for
+//! the `reconcile` command.
+
+use std::collections::HashMap;
+
+use bankai_core::{
+    clients::starknet::StarknetClient,
+    db::{EpochUpdate, EpochUpdateFilter, Status},
+    utils::constants::SLOTS_PER_SYNC_COMMITTEE,
+    BankaiClient,
+};
+use serde::Serialize;
+use serde_json::json;
+use tracing::{info, warn};
+
+use crate::BankaiCliError;
+
+/// Result of comparing one done epoch update with the contract
+#[derive(Debug, Serialize)]
+#[serde(tag = "result", rename_all = "snake_case")]
+enum Finding {
+    /// The contract stores the same epoch
+    Matches,
+    /// The slot is after the latest verified slot, the update has not landed yet
+    NotLanded { wrap_status: Option<String> },
+    /// The slot is not after the latest verified slot, but the contract has no epoch for it
+    Missing,
+    /// The contract stores different values for the slot
+    Mismatch { differences: Vec<String> },
+    /// The epoch update has no stored outputs to compare
+    NoOutputs,
+}
+
+impl Finding {
+    fn is_problem(&self) -> bool {
+        matches!(self, Finding::Missing | Finding::Mismatch { .. })
+    }
+
+    fn describe(&self) -> (&'static str, String) {
+        match self {
+            Finding::Matches => ("ok", String::new()),
+            Finding::NotLanded { wrap_status } => (
+                "not_landed",
+                format!("wrap status: {}", wrap_status.as_deref().unwrap_or("-")),
+            ),
+            Finding::Missing => ("missing", "no epoch stored on-chain".to_string()),
+            Finding::Mismatch { differences } => ("mismatch", differences.join("; ")),
+            Finding::NoOutputs => ("no_outputs", String::new()),
+        }
+    }
+}
+
+/// Compares the latest `limit` done epoch updates with the Bankai contract.
+///
+/// Returns an error if an update that should be on-chain is missing or disagrees with it.
+pub async fn reconcile(bankai: &BankaiClient, limit: u64, as_json: bool) -> Result<(), BankaiCliError> {
+    let starknet = StarknetClient::new(&bankai.config.starknet).map_err(starknet_error)?;
+    let latest_slot = starknet.get_latest_epoch_slot().await.map_err(starknet_error)?;
+    let latest_committee_id = starknet.get_latest_committee_id().await.map_err(starknet_error)?;
+    info!(
+        "⛓️  Contract state - Latest slot: {}, Latest committee: {}",
+        latest_slot, latest_committee_id
+    );
+
+    let filter = EpochUpdateFilter {
+        status: Some(Status::Done),
+        ..Default::default()
+    };
+    let updates = bankai
+        .db
+        .list_epoch_updates(&filter, limit, 0)
+        .await
+        .map_err(|e| BankaiCliError::ProofGenerationError(format!("Database error: {}", e)))?;
+
+    if let Some(latest_local) = updates.first() {
+        if latest_slot > latest_local.slot_number as u64 {
+            warn!(
+                "⚠️  Contract is at slot {}, ahead of the latest local epoch update at slot {}",
+                latest_slot, latest_local.slot_number
+            );
+        }
+    }
+
+    let mut committee_hashes = HashMap::new();
+    let mut findings = Vec::with_capacity(updates.len());
+    for update in &updates {
+        let finding = check_update(&starknet, update, latest_slot, latest_committee_id, &mut committee_hashes).await?;
+        findings.push((update, finding));
+    }
+    let problems = findings.iter().filter(|(_, finding)| finding.is_problem()).count();
+
+    if as_json {
+        let rows: Vec<_> = findings
+            .iter()
+            .map(|(update, finding)| {
+                json!({
+                    "uuid": update.uuid,
+                    "epoch_number": update.epoch_number,
+                    "slot_number": update.slot_number,
+                    "finding": finding,
+                })
+            })
+            .collect();
+        let value = json!({
+            "latest_slot": latest_slot,
+            "latest_committee_id": latest_committee_id,
+            "epoch_updates": rows,
+        });
+        println!("{}", serde_json::to_string_pretty(&value)?);
+    } else {
+        println!(
+            "  {:<36}  {:>8}  {:>10}  {:<10}  {}",
+            "UUID", "EPOCH", "SLOT", "RESULT", "DETAILS"
+        );
+        for (update, finding) in &findings {
+            let (result, details) = finding.describe();
+            println!(
+                "  {:<36}  {:>8}  {:>10}  {:<10}  {}",
+                update.uuid, update.epoch_number, update.slot_number, result, details
+            );
+        }
+    }
+
+    if problems > 0 {
+        return Err(BankaiCliError::ProofGenerationError(format!(
+            "{} of {} epoch updates are missing on-chain or disagree with the contract",
+            problems,
+            findings.len()
+        )));
+    }
+    info!("✅ All landed epoch updates match the contract");
+    Ok(())
+}
+
+async fn check_update(
+    starknet: &StarknetClient,
+    update: &EpochUpdate,
+    latest_slot: u64,
+    latest_committee_id: u64,
+    committee_hashes: &mut HashMap<u64, alloy_primitives::FixedBytes<32>>,
+) -> Result<Finding, BankaiCliError> {
+    let Some(outputs) = &update.outputs else {
+        return Ok(Finding::NoOutputs);
+    };
+    let slot = update.slot_number as u64;
+    if slot > latest_slot {
+        return Ok(Finding::NotLanded {
+            wrap_status: update.wrap_status.clone(),
+        });
+    }
+
+    let on_chain = starknet.get_epoch_proof(slot).await.map_err(starknet_error)?;
+    if on_chain.is_empty() {
+        return Ok(Finding::Missing);
+    }
+    let mut differences = on_chain.diff(outputs);
+
+    // Committees are stored per sync committee period. The header of a slot is signed by the
+    // committee of the next slot's period, so the last slot of a period uses the next committee.
+    let committee_id = (slot + 1) / SLOTS_PER_SYNC_COMMITTEE;
+    if committee_id <= latest_committee_id {
+        let committee_hash = match committee_hashes.get(&committee_id) {
+            Some(hash) => *hash,
+            None => {
+                let hash = starknet
+                    .get_committee_hash(committee_id)
+                    .await
+                    .map_err(starknet_error)?;
+                committee_hashes.insert(committee_id, hash);
+                hash
+            }
+        };
+        if committee_hash != outputs.current_committee_hash {
+            differences.push(format!(
+                "committee_hash: on-chain {}, local {}",
+                committee_hash, outputs.current_committee_hash
+            ));
+        }
+    }
+
+    Ok(if differences.is_empty() {
+        Finding::Matches
+    } else {
+        Finding::Mismatch { differences }
+    })
+}
+
+fn starknet_error(e: impl std::fmt::Display) -> BankaiCliError {
+    BankaiCliError::ProofGenerationError(format!("Starknet error: {}", e))
+}
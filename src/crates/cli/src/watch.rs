@@ -0,0 +1,174 @@
+//! Event-driven proving loop
+//!
+//! Follows the beacon node event stream and runs the recursive epoch flow whenever a new epoch
+//! is finalized or the head enters a new sync committee period.
+
+use bankai_core::{
+    clients::events::{BeaconEvent, EventSubscriber, DEFAULT_TOPICS},
+    fetcher::recursive_epoch_input::EpochUpdate,
+    prover::{wrap, ProverError},
+    utils::constants::{EPOCHS_PER_SYNC_COMMITTEE, SLOTS_PER_EPOCH, SLOTS_PER_SYNC_COMMITTEE},
+    BankaiClient,
+};
+use tokio::sync::mpsc;
+use tracing::{debug, error, info};
+
+use crate::{prove_recursive_epoch, BankaiCliError};
+
+/// Number of events buffered while an update is being proven
+const EVENT_BUFFER: usize = 64;
+
+/// Proves new epochs as they are finalized, until the process is stopped
+///
+/// # Arguments
+/// * `bankai` - The Bankai client
+/// * `program_path` - Path to the compiled recursive update program
+/// * `wrap` - Submit finished proofs for verification on Starknet
+pub async fn watch(bankai: &BankaiClient, program_path: &str, wrap: bool) -> Result<(), BankaiCliError> {
+    let (sender, mut receiver) = mpsc::channel(EVENT_BUFFER);
+    let subscriber = EventSubscriber::new(&bankai.config.beacon.rpc_url, DEFAULT_TOPICS);
+    tokio::spawn(async move { subscriber.run(sender).await });
+
+    info!("👀 Watching the beacon chain for finalized epochs...");
+    let mut last_finalized_epoch = None;
+    let mut head_period = None;
+    while let Some(event) = receiver.recv().await {
+        debug!("📨 Beacon event: {:?}", event);
+        let finalized_epoch = match event {
+            BeaconEvent::FinalizedCheckpoint { epoch, .. } => epoch,
+            BeaconEvent::LightClientFinalityUpdate { finalized_slot, .. } => {
+                finalized_slot / SLOTS_PER_EPOCH
+            }
+            BeaconEvent::Head { slot, .. } => {
+                let period = slot / SLOTS_PER_SYNC_COMMITTEE;
+                let previous_period = head_period.replace(period);
+                if previous_period.is_none_or(|previous| previous >= period) {
+                    continue;
+                }
+                info!("🔄 Head entered sync committee period {}", period);
+                if let Err(e) = EpochUpdate::prefetch_sync_committee(&bankai.client, &bankai.db, slot).await {
+                    error!("❌ Failed to prefetch sync committee for period {}: {}", period, e);
+                }
+                // The committee update of the previous period may be provable now
+                match last_finalized_epoch {
+                    Some(epoch) => epoch,
+                    None => continue,
+                }
+            }
+        };
+
+        if last_finalized_epoch.is_some_and(|last| finalized_epoch < last) {
+            continue;
+        }
+        last_finalized_epoch = Some(finalized_epoch);
+
+        if let Err(e) = prove_finalized(bankai, program_path, finalized_epoch).await {
+            error!("❌ Epoch update for finalized epoch {} failed: {}", finalized_epoch, e);
+        }
+        if wrap {
+            wrap_latest(bankai).await;
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs the recursive epoch flow towards `finalized_epoch`
+async fn prove_finalized(
+    bankai: &BankaiClient,
+    program_path: &str,
+    finalized_epoch: u64,
+) -> Result<(), BankaiCliError> {
+    let latest = bankai
+        .db
+        .get_latest_epoch_update()
+        .await
+        .map_err(|e| BankaiCliError::ProofGenerationError(format!("Database error: {}", e)))?
+        .ok_or_else(|| {
+            BankaiCliError::ProofGenerationError(
+                "No previous epoch update found. Please run genesis first".to_string(),
+            )
+        })?;
+    let latest_epoch = latest.epoch_number as u64;
+
+    let committee_known = latest
+        .outputs
+        .as_ref()
+        .is_some_and(|outputs| !outputs.next_committee_hash.is_zero());
+    let target_epoch = finalized_epoch.min(max_target_epoch(latest_epoch, committee_known));
+    if target_epoch <= latest_epoch {
+        debug!("⏸️  Nothing to prove beyond epoch {}", latest_epoch);
+        return Ok(());
+    }
+
+    info!("🎯 Finalized epoch {}, proving towards epoch {}", finalized_epoch, target_epoch);
+    let fast_forward = target_epoch - latest_epoch - 1;
+    prove_recursive_epoch(
+        bankai,
+        program_path,
+        false,
+        (fast_forward > 0).then_some(fast_forward),
+        None,
+    )
+    .await
+}
+
+/// Submits or resumes the Starknet verification of the latest done epoch update. A failed
+/// verification is left for an explicit `wrap`, since every submission is paid.
+async fn wrap_latest(bankai: &BankaiClient) {
+    let result = async {
+        let update = wrap::find_wrap_target(bankai, None).await?;
+        wrap::wrap_epoch_update(bankai, &update, false, false).await
+    }
+    .await;
+    match result {
+        Ok(_) | Err(ProverError::NotFound(_)) => {}
+        Err(ProverError::NotRetryable(e)) => debug!("⏸️  {}", e),
+        Err(e) => error!("❌ Starknet verification failed: {}", e),
+    }
+}
+
+/// Returns the last epoch whose header is signed by a committee known after `latest_epoch`.
+///
+/// The header of an epoch is signed in the slot after its last slot. Without a proven next
+/// committee the target has to stay in the current period, otherwise it may reach into the
+/// next one.
+fn max_target_epoch(latest_epoch: u64, committee_known: bool) -> u64 {
+    let period = (latest_epoch + 1) / EPOCHS_PER_SYNC_COMMITTEE;
+    let last_period = if committee_known { period + 1 } else { period };
+    (last_period + 1) * EPOCHS_PER_SYNC_COMMITTEE - 2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn target_stays_in_the_signing_period_without_next_committee() {
+        assert_eq!(max_target_epoch(0, false), 254);
+        // The header of epoch 254 is the last one signed by the committee of period 0
+        assert_eq!(max_target_epoch(254, false), 254);
+        assert_eq!(max_target_epoch(255, false), 510);
+    }
+
+    #[test]
+    fn known_next_committee_extends_target_by_a_period() {
+        assert_eq!(max_target_epoch(0, true), 510);
+        assert_eq!(max_target_epoch(254, true), 510);
+        assert_eq!(max_target_epoch(510, true), 766);
+    }
+
+    #[test]
+    fn target_headers_are_signed_by_a_known_committee() {
+        for latest in [0, 100, 254, 255, 256, 1000] {
+            for known in [false, true] {
+                let target = max_target_epoch(latest, known);
+                let signing_period = (target + 1) / EPOCHS_PER_SYNC_COMMITTEE;
+                let latest_period = (latest + 1) / EPOCHS_PER_SYNC_COMMITTEE;
+                assert_eq!(signing_period, latest_period + known as u64, "latest {} known {}", latest, known);
+                let next_period = (target + 2) / EPOCHS_PER_SYNC_COMMITTEE;
+                assert_eq!(next_period, signing_period + 1, "latest {} known {}", latest, known);
+            }
+        }
+    }
+}
@@ -0,0 +1,159 @@
+//! End-to-end run of the CLI against the mock beacon node and Atlantic API.
+//!
+//! The flow needs recorded beacon responses and the proofs Atlantic returned for them, because
+//! the proofs are checked against the outputs of the recorded epochs, and the compiled Cairo
+//! program. Record them once against a real node (see `test_support/fixtures/README.md`).
+//! Synthetic fixtures do not work here, as the program checks the committee signatures, so the
+//! test is ignored until the recording is committed and runs with `-- --ignored`. It fails if
+//! the fixtures or the program are missing.
+
+use std::{
+    path::{Path, PathBuf},
+    process::Output,
+};
+
+use bankai_core::db::{Database, EpochUpdateFilter, Status};
+use bankai_test_support::{
+    atlantic::{AtlanticBehavior, MockAtlantic},
+    beacon::{BeaconFixtures, MockBeacon},
+};
+use serde_json::Value;
+use tokio::process::Command;
+
+fn workspace_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../../..")
+}
+
+fn fixtures_dir() -> PathBuf {
+    std::env::var("BANKAI_FIXTURES")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| workspace_dir().join("src/crates/test_support/fixtures/flow"))
+}
+
+/// Reads the proofs Atlantic returned, in submission order
+fn load_proofs(dir: &Path) -> Vec<Value> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    paths.sort();
+    paths
+        .iter()
+        .map(|path| serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap())
+        .collect()
+}
+
+async fn cli(config: &Path, args: &[&str]) -> Output {
+    let output = Command::new(env!("CARGO_BIN_EXE_cli"))
+        .arg("--config")
+        .arg(config)
+        .args(args)
+        .env_remove("BANKAI_CONFIG")
+        .output()
+        .await
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "cli {:?} failed:\n{}\n{}",
+        args,
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "needs recorded fixtures in test_support/fixtures/flow and the compiled program"]
+async fn genesis_recursive_and_committee_update() {
+    let fixtures = fixtures_dir();
+    let program = workspace_dir().join("cairo/build/recursive_update.json");
+    assert!(
+        fixtures.join("flow.json").exists(),
+        "Missing fixtures in {}, see src/crates/test_support/fixtures/README.md",
+        fixtures.display()
+    );
+    assert!(program.exists(), "Missing {}, run make build-cairo", program.display());
+    let flow: Value =
+        serde_json::from_str(&std::fs::read_to_string(fixtures.join("flow.json")).unwrap()).unwrap();
+    let recursive_runs = flow["recursive_runs"].as_u64().unwrap() as usize;
+
+    let beacon = MockBeacon::start(BeaconFixtures::load(fixtures.join("beacon")).unwrap())
+        .await
+        .unwrap();
+    let atlantic = MockAtlantic::start(AtlanticBehavior {
+        proofs: load_proofs(&fixtures.join("proofs")),
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+
+    let dir = std::env::temp_dir().join(format!("bankai_flow_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let database_url = format!("sqlite:{}", dir.join("bankai.db").display());
+    let atlantic_config = atlantic.config();
+    let config = dir.join("bankai.toml");
+    std::fs::write(
+        &config,
+        format!(
+            r#"
+network = "{network}"
+
+[beacon]
+rpc_url = "{beacon}"
+request_delay_ms = 0
+ssz = false
+sync_committee_source = "light_client"
+
+[atlantic]
+endpoint = "{endpoint}"
+api_key = "{api_key}"
+proof_registry = "{registry}"
+
+[database]
+url = "{database_url}"
+
+[program]
+recursive_update = "{program}"
+"#,
+            network = flow["network"].as_str().unwrap_or("sepolia"),
+            beacon = beacon.url(),
+            endpoint = atlantic_config.endpoint,
+            api_key = atlantic_config.api_key,
+            registry = atlantic_config.proof_registry.unwrap(),
+            program = program.display(),
+        ),
+    )
+    .unwrap();
+
+    cli(&config, &["prove", "genesis"]).await;
+    for _ in 0..recursive_runs {
+        cli(&config, &["prove", "recursive-epoch"]).await;
+    }
+
+    assert_eq!(atlantic.submissions().len(), recursive_runs + 1);
+
+    let db = Database::new(&database_url, None).await.unwrap();
+    let updates = db
+        .list_epoch_updates(&EpochUpdateFilter::default(), 100, 0)
+        .await
+        .unwrap();
+    assert_eq!(updates.len(), recursive_runs + 1);
+    // Newest first: the last submission is still proving, every earlier one is done
+    assert_eq!(updates[0].status, Status::Proving.as_str());
+    for update in &updates[1..] {
+        assert_eq!(update.status, Status::Done.as_str(), "{}", update.uuid);
+        assert!(update.proof_id.is_some());
+    }
+
+    // The recorded epochs cross a sync committee period
+    let committees: Vec<_> = updates
+        .iter()
+        .filter_map(|update| update.outputs.as_ref().map(|o| o.current_committee_hash))
+        .collect();
+    assert!(
+        committees.windows(2).any(|pair| pair[0] != pair[1]),
+        "no committee update in the recorded flow"
+    );
+
+    std::fs::remove_dir_all(&dir).ok();
+}
@@ -0,0 +1,142 @@
+//! Compares fetching the blocks of an epoch update as JSON and as SSZ.
+//!
+//! Needs a live beacon node, so it is not run by `cargo bench` unless `BEACON_RPC_URL` is set:
+//!
+//! ```bash
+//! BEACON_RPC_URL=https://... cargo bench -p bankai-core --bench beacon_transport
+//! ```
+//!
+//! Every epoch fetches the block body and the sync aggregate, like an epoch update does. The
+//! wall time and the peak heap usage are reported per epoch for both transports. Set
+//! `BENCH_EPOCHS` to change the number of epochs, 4 by default. Epochs whose last slot is empty
+//! are skipped.
+//!
+//! The beacon state download of committee updates is not measured, as it is still done by
+//! `beacon-state-proof` over JSON. Moving it to `BeaconRpcClient` is tracked as a separate
+//! request.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+use bankai_core::{
+    clients::beacon_chain::{BeaconError, BeaconRpcClient},
+    utils::{config::BankaiConfig, constants::SLOTS_PER_EPOCH},
+};
+
+/// Allocator tracking the current and the peak heap usage
+struct PeakAlloc;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for PeakAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let current = CURRENT.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(current, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: PeakAlloc = PeakAlloc;
+
+struct Measurement {
+    elapsed: Duration,
+    peak_bytes: usize,
+}
+
+async fn measure_epoch(client: &BeaconRpcClient, slot: u64) -> Measurement {
+    let baseline = CURRENT.load(Ordering::Relaxed);
+    PEAK.store(baseline, Ordering::Relaxed);
+    let start = Instant::now();
+
+    client.get_block_body(slot).await.expect("failed to fetch block body");
+    client.get_sync_aggregate(slot).await.expect("failed to fetch sync aggregate");
+
+    Measurement {
+        elapsed: start.elapsed(),
+        peak_bytes: PEAK.load(Ordering::Relaxed) - baseline,
+    }
+}
+
+fn client(rpc_url: &str, ssz: bool) -> BeaconRpcClient {
+    let mut config = BankaiConfig::default();
+    config.beacon.rpc_url = rpc_url.to_string();
+    config.beacon.ssz = ssz;
+    // Rate limiting would dominate the timings
+    config.beacon.request_delay_ms = 0;
+    BeaconRpcClient::new(rpc_url.to_string(), config)
+}
+
+#[tokio::main]
+async fn main() {
+    let Ok(rpc_url) = std::env::var("BEACON_RPC_URL") else {
+        eprintln!("BEACON_RPC_URL is not set, skipping the beacon transport benchmark");
+        return;
+    };
+    let epochs: u64 = std::env::var("BENCH_EPOCHS")
+        .ok()
+        .and_then(|epochs| epochs.parse().ok())
+        .unwrap_or(4);
+
+    let json_client = client(&rpc_url, false);
+    let ssz_client = client(&rpc_url, true);
+
+    // Stay clear of the head, where blocks may still be missing
+    let head = json_client.get_head_slot().await.expect("failed to fetch head slot");
+    let last_epoch = head / SLOTS_PER_EPOCH - 2;
+
+    println!("{:>8} {:>12} {:>12} {:>14} {:>14}", "epoch", "json ms", "ssz ms", "json peak KiB", "ssz peak KiB");
+    let (mut json_total, mut ssz_total) = (Duration::ZERO, Duration::ZERO);
+    let (mut json_peak, mut ssz_peak) = (0, 0);
+    let mut measured = 0u128;
+    for epoch in (last_epoch - epochs + 1)..=last_epoch {
+        let slot = epoch * SLOTS_PER_EPOCH + SLOTS_PER_EPOCH - 1;
+        match json_client.get_header(slot).await {
+            Ok(_) => {}
+            Err(BeaconError::EmptySlot(_)) => {
+                println!("{:>8} skipped, slot {} is empty", epoch, slot);
+                continue;
+            }
+            Err(e) => panic!("failed to fetch header of slot {}: {}", slot, e),
+        }
+        let json = measure_epoch(&json_client, slot).await;
+        let ssz = measure_epoch(&ssz_client, slot).await;
+        println!(
+            "{:>8} {:>12} {:>12} {:>14} {:>14}",
+            epoch,
+            json.elapsed.as_millis(),
+            ssz.elapsed.as_millis(),
+            json.peak_bytes / 1024,
+            ssz.peak_bytes / 1024,
+        );
+        json_total += json.elapsed;
+        ssz_total += ssz.elapsed;
+        json_peak = json_peak.max(json.peak_bytes);
+        ssz_peak = ssz_peak.max(ssz.peak_bytes);
+        measured += 1;
+    }
+    if measured == 0 {
+        println!("no epoch measured");
+        return;
+    }
+
+    println!(
+        "mean per epoch: json {} ms / {} KiB peak, ssz {} ms / {} KiB peak",
+        json_total.as_millis() / measured,
+        json_peak / 1024,
+        ssz_total.as_millis() / measured,
+        ssz_peak / 1024,
+    );
+}
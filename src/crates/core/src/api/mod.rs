@@ -0,0 +1,73 @@
+//! HTTP API Module
+//!
+//! Read-only endpoints served next to `/metrics`. They expose values derived from the local
+//! state, such as the fact hash an epoch update registers on Starknet, so other services do not
+//! need database access to look them up.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use serde_json::json;
+
+use crate::{
+    db::Status,
+    prover::{self, ProverError, RetryTarget},
+    BankaiClient,
+};
+
+/// Builds the API routes for `bankai`
+///
+/// # Routes
+/// * `GET /epochs/{target}/fact` - Fact of the epoch update with this UUID or epoch number,
+///   or of the latest done one for `latest`
+pub fn router(bankai: Arc<BankaiClient>) -> Router {
+    Router::new()
+        .route("/epochs/:target/fact", get(epoch_fact))
+        .with_state(bankai)
+}
+
+async fn epoch_fact(
+    State(bankai): State<Arc<BankaiClient>>,
+    Path(target): Path<String>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let target = match target.as_str() {
+        "latest" => None,
+        target => Some(RetryTarget::from(target)),
+    };
+    let update = prover::find_epoch_update(&bankai, target, Some(Status::Done)).await?;
+    let fact = prover::epoch_fact(&bankai, &update, &bankai.config.program.recursive_update)?;
+
+    Ok(Json(json!({
+        "uuid": update.uuid,
+        "epoch_number": update.epoch_number,
+        "program_hash": format!("{:#x}", fact.program_hash),
+        "output_hash": format!("{:#x}", fact.output_hash),
+        "fact_hash": format!("{:#x}", fact.fact_hash),
+        "stored_fact_hash": update.fact_hash,
+        "wrap_status": update.wrap_status,
+    })))
+}
+
+struct ApiError(ProverError);
+
+impl From<ProverError> for ApiError {
+    fn from(e: ProverError) -> Self {
+        Self(e)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = match self.0 {
+            ProverError::NotFound(_) => StatusCode::NOT_FOUND,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, Json(json!({ "error": self.0.to_string() }))).into_response()
+    }
+}
@@ -4,8 +4,7 @@
 //! and verification. It handles file uploads, proof submissions, and status polling for
 //! batch processing operations.
 
-use std::env;
-
+use bytes::Bytes;
 use cairo_vm::vm::runners::cairo_pie::CairoPie;
 use futures::StreamExt;
 use reqwest::{
@@ -18,9 +17,10 @@ use tokio::{
     fs,
     time::{sleep, Duration},
 };
-use tokio_util::io::ReaderStream;
 use tracing::{debug, error, info, trace};
 
+use crate::utils::config::AtlanticConfig;
+
 // use crate::types::traits::ProofType;
 
 /// Client for interacting with the Atlantic API service.
@@ -31,6 +31,9 @@ use tracing::{debug, error, info, trace};
 pub struct AtlanticClient {
     endpoint: String,
     api_key: String,
+    proof_registry: Option<String>,
+    poll_interval: Duration,
+    max_poll_attempts: usize,
     pub client: reqwest::Client,
 }
 
@@ -40,6 +43,248 @@ pub struct StarkProof {
     pub proof: serde_json::Value,
 }
 
+/// Declared job size of an Atlantic query, which selects the prover machine
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum JobSize {
+    XS,
+    S,
+    M,
+    L,
+}
+
+impl JobSize {
+    /// Returns the size as expected by the API
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobSize::XS => "XS",
+            JobSize::S => "S",
+            JobSize::M => "M",
+            JobSize::L => "L",
+        }
+    }
+}
+
+impl std::fmt::Display for JobSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Parameters a PIE is submitted to Atlantic with, and the execution resources they were
+/// derived from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobParams {
+    pub declared_job_size: JobSize,
+    pub layout: String,
+    /// Cairo steps of the run
+    pub n_steps: usize,
+    /// Builtins used by the run
+    pub builtins: Vec<String>,
+    /// Why the size and layout were chosen
+    pub reasons: Vec<String>,
+}
+
+/// Size of the chunks a PIE is uploaded in
+const UPLOAD_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Zips a PIE for submission.
+///
+/// The PIE is written to a uniquely named temporary file, so concurrent runs do not collide,
+/// read back into memory and removed again.
+///
+/// # Arguments
+/// * `pie` - The PIE to zip
+///
+/// # Returns
+/// * `Result<Vec<u8>, AtlanticError>` - The zip archive
+pub fn pie_zip(pie: &CairoPie) -> Result<Vec<u8>, AtlanticError> {
+    let path = std::env::temp_dir().join(format!("bankai_pie_{}.zip", uuid::Uuid::new_v4()));
+    let result = pie
+        .write_zip_file(&path, true)
+        .and_then(|_| std::fs::read(&path))
+        .map_err(AtlanticError::from);
+    if let Err(e) = std::fs::remove_file(&path) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            error!("Failed to remove temporary PIE {}: {}", path.display(), e);
+        }
+    }
+    result
+}
+
+/// Streams `data` in chunks, logging every 10% that has been handed to the connection.
+///
+/// The chunks are slices of one shared buffer, so the PIE is not copied again for the upload.
+fn upload_stream(data: Vec<u8>, name: String) -> Body {
+    let data = Bytes::from(data);
+    let total = data.len();
+    let mut sent = 0;
+    let mut reported = 0;
+    let chunks = (0..total)
+        .step_by(UPLOAD_CHUNK_SIZE)
+        .map(move |start| data.slice(start..total.min(start + UPLOAD_CHUNK_SIZE)));
+    let stream = futures::stream::iter(chunks).map(move |chunk| {
+        sent += chunk.len();
+        let percent = sent * 100 / total.max(1);
+        if percent >= reported + 10 || sent == total {
+            reported = percent - percent % 10;
+            info!("📤 Uploading {}: {}% ({} of {} bytes)", name, percent, sent, total);
+        }
+        Ok::<_, std::io::Error>(chunk)
+    });
+    Body::wrap_stream(stream)
+}
+
+/// Status of an Atlantic query
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AtlanticStatus {
+    /// Submitted and waiting to be picked up
+    Received,
+    /// Being processed, see `AtlanticQuery::step`
+    InProgress,
+    /// Finished successfully
+    Done,
+    /// Failed, see `AtlanticQuery::error_reason`
+    Failed,
+    /// A status this client does not know yet
+    #[serde(other)]
+    Unknown,
+}
+
+impl AtlanticStatus {
+    /// Returns the status as reported by the API
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AtlanticStatus::Received => "RECEIVED",
+            AtlanticStatus::InProgress => "IN_PROGRESS",
+            AtlanticStatus::Done => "DONE",
+            AtlanticStatus::Failed => "FAILED",
+            AtlanticStatus::Unknown => "UNKNOWN",
+        }
+    }
+
+    /// Returns true once the query will not change anymore
+    pub fn is_final(&self) -> bool {
+        matches!(self, AtlanticStatus::Done | AtlanticStatus::Failed)
+    }
+}
+
+impl std::fmt::Display for AtlanticStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Metadata of an Atlantic query, as returned by `atlantic-query/{id}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AtlanticQuery {
+    pub id: String,
+    pub external_id: Option<String>,
+    pub status: AtlanticStatus,
+    /// Step currently being processed, e.g. `TRACE_GENERATION` or `PROOF_GENERATION`
+    pub step: Option<String>,
+    /// Steps the query goes through
+    #[serde(default)]
+    pub steps: Vec<String>,
+    pub error_reason: Option<String>,
+    pub layout: Option<String>,
+    pub declared_job_size: Option<String>,
+    pub job_size: Option<String>,
+    pub program_hash: Option<String>,
+    /// Fact hash registered by an L2 verification query
+    pub integrity_fact_hash: Option<String>,
+    pub created_at: Option<String>,
+    pub completed_at: Option<String>,
+}
+
+impl AtlanticQuery {
+    /// Name of the phase the query is in: its step while processing, its status otherwise
+    pub fn phase(&self) -> &str {
+        match (&self.status, &self.step) {
+            (AtlanticStatus::InProgress, Some(step)) => step,
+            (status, _) => status.as_str(),
+        }
+    }
+
+    /// Unix timestamp of `createdAt`, `None` if it is missing or malformed
+    pub fn created_at_secs(&self) -> Option<u64> {
+        self.created_at.as_deref().and_then(parse_timestamp)
+    }
+
+    /// Unix timestamp of `completedAt`, `None` if it is missing or malformed
+    pub fn completed_at_secs(&self) -> Option<u64> {
+        self.completed_at.as_deref().and_then(parse_timestamp)
+    }
+}
+
+/// Parses an RFC 3339 timestamp as reported by Atlantic, e.g. `2025-06-01T12:00:00.000Z`
+fn parse_timestamp(value: &str) -> Option<u64> {
+    let timestamp = chrono::DateTime::parse_from_rfc3339(value).ok()?.timestamp();
+    u64::try_from(timestamp).ok()
+}
+
+/// Time spent in one phase of an Atlantic query
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AtlanticPhase {
+    /// Step or status name, see `AtlanticQuery::phase`
+    pub name: String,
+    /// Unix timestamp the phase started: `createdAt` for the first phase, `completedAt` for a
+    /// final one and the first observation for the steps in between
+    pub started_at: u64,
+    /// Unix timestamp the next phase started
+    pub finished_at: Option<u64>,
+}
+
+impl AtlanticPhase {
+    /// Seconds spent in the phase, or so far if it is still running
+    pub fn duration_secs(&self, now: u64) -> u64 {
+        self.finished_at.unwrap_or(now).saturating_sub(self.started_at)
+    }
+}
+
+/// Records the phase of `query`, closing the previous phase if it changed.
+///
+/// Atlantic only reports when a query was created and completed, so the first phase starts at
+/// `createdAt` and a final phase at `completedAt`. Steps in between are timed by the polls
+/// that first see them, and `now` stands in for a timestamp Atlantic did not report.
+///
+/// # Arguments
+/// * `phases` - Phases observed so far, oldest first
+/// * `query` - The freshly fetched query
+/// * `now` - Unix timestamp of the observation
+pub fn observe_phase(phases: &mut Vec<AtlanticPhase>, query: &AtlanticQuery, now: u64) {
+    let name = query.phase();
+    let reported = if query.status.is_final() {
+        query.completed_at_secs()
+    } else if phases.is_empty() {
+        query.created_at_secs()
+    } else {
+        None
+    };
+    let mut started_at = reported.unwrap_or(now);
+    if let Some(last) = phases.last_mut() {
+        if last.name == name {
+            return;
+        }
+        // Keep the phases ordered if the clocks disagree
+        started_at = started_at.max(last.started_at);
+        last.finished_at.get_or_insert(started_at);
+    }
+    phases.push(AtlanticPhase {
+        name: name.to_string(),
+        started_at,
+        // Final phases have no duration
+        finished_at: query.status.is_final().then_some(started_at),
+    });
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AtlanticQueryResponse {
+    atlantic_query: AtlanticQuery,
+}
+
 /// Possible errors that can occur during Atlantic API operations.
 #[derive(Debug, Error)]
 pub enum AtlanticError {
@@ -61,60 +306,55 @@ pub enum AtlanticError {
     /// JSON decoding errors
     #[error("Decoding Error: {0}")]
     Decoding(#[from] serde_json::Error),
+    /// Missing client configuration
+    #[error("Configuration error: {0}")]
+    Config(String),
 }
 
 impl AtlanticClient {
     /// Creates a new Atlantic client instance.
     ///
     /// # Arguments
-    /// * `endpoint` - The base URL for the Atlantic API
-    /// * `api_key` - Authentication key for API access
-    pub fn new(endpoint: String, api_key: String) -> Self {
+    /// * `config` - Endpoint, API key, proof registry and polling settings
+    pub fn new(config: AtlanticConfig) -> Self {
         Self {
-            endpoint,
-            api_key,
+            endpoint: config.endpoint,
+            api_key: config.api_key,
+            proof_registry: config.proof_registry,
+            poll_interval: Duration::from_secs(config.poll_interval_secs),
+            max_poll_attempts: config.max_poll_attempts,
             client: reqwest::Client::new(),
         }
     }
 
     /// Submits a batch for proof generation.
     ///
-    /// Uploads a PIE file to the Atlantic API and initiates proof generation.
-    /// Displays progress during file upload.
+    /// Uploads a zipped PIE to the Atlantic API and initiates proof generation.
+    /// Logs the upload progress.
     ///
     /// # Arguments
-    /// * `pie` - The generate Pie
+    /// * `pie_zip` - The zipped PIE, see `pie_zip`
+    /// * `params` - Declared job size and layout of the query
+    /// * `name` - External ID of the query
     ///
     /// # Returns
     /// * `Result<String, AtlanticError>` - The Atlantic query ID on success
     pub async fn submit_stone(
         &self,
-        pie: CairoPie,
+        pie_zip: Vec<u8>,
+        params: &JobParams,
         name: String,
     ) -> Result<String, AtlanticError> {
-        let pie_path = std::env::temp_dir().join(format!("{}.zip", name));
-        pie.write_zip_file(&pie_path, true)?;
-        let file = fs::File::open(pie_path.clone()).await?;
-
-        // Get file metadata to determine total size
-        let stream = ReaderStream::new(file);
-        let progress_stream = stream.map(|chunk| chunk);  // Simply pass through the chunks
-
-        let file_part = Part::stream(Body::wrap_stream(progress_stream))
-            .file_name(
-                pie_path
-                    .file_name()
-                    .unwrap_or_default()
-                    .to_string_lossy()
-                    .to_string(),
-            )
+        let length = pie_zip.len() as u64;
+        let file_part = Part::stream_with_length(upload_stream(pie_zip, name.clone()), length)
+            .file_name(format!("{}.zip", name))
             .mime_str("application/zip")?;
 
         // Build the form with updated API parameters
         let form = Form::new()
             .part("pieFile", file_part)
-            .text("declaredJobSize", "XS")
-            .text("layout", "dynamic")
+            .text("declaredJobSize", params.declared_job_size.as_str())
+            .text("layout", params.layout.clone())
             .text("cairoVm", "rust")
             .text("cairoVersion", "cairo0")
             .text("result", "PROOF_GENERATION")
@@ -220,13 +460,13 @@ impl AtlanticClient {
     /// # Returns
     /// * `Result<StarkProof, AtlanticError>` - The generated STARK proof
     pub async fn fetch_proof(&self, batch_id: &str) -> Result<StarkProof, AtlanticError> {
+        let proof_registry = self.proof_registry.as_ref().ok_or_else(|| {
+            AtlanticError::Config("atlantic.proof_registry (PROOF_REGISTRY) is not set".into())
+        })?;
+
         let response = self
             .client
-            .get(format!(
-                "{}/{}/proof.json",
-                env::var("PROOF_REGISTRY").unwrap(),
-                batch_id
-            ))
+            .get(format!("{}/{}/proof.json", proof_registry, batch_id))
             .header("accept", "application/json")
             .send()
             .await?;
@@ -244,8 +484,32 @@ impl AtlanticClient {
     /// * `batch_id` - The ID of the batch to check
     ///
     /// # Returns
-    /// * `Result<String, AtlanticError>` - The current status of the batch
-    pub async fn check_batch_status(&self, batch_id: &str) -> Result<String, AtlanticError> {
+    /// * `Result<AtlanticStatus, AtlanticError>` - The current status of the batch
+    pub async fn check_batch_status(&self, batch_id: &str) -> Result<AtlanticStatus, AtlanticError> {
+        let query = self.get_query(batch_id).await?;
+        debug!("Atlantic query {} status: {}", batch_id, query.status);
+        Ok(query.status)
+    }
+
+    /// Returns the fact hash registered by a finished L2 verification query.
+    ///
+    /// # Arguments
+    /// * `batch_id` - The ID of the wrapped proof query
+    ///
+    /// # Returns
+    /// * `Result<Option<String>, AtlanticError>` - The Integrity fact hash, if Atlantic reports one
+    pub async fn fetch_fact_hash(&self, batch_id: &str) -> Result<Option<String>, AtlanticError> {
+        Ok(self.get_query(batch_id).await?.integrity_fact_hash)
+    }
+
+    /// Fetches the metadata of an Atlantic query.
+    ///
+    /// # Arguments
+    /// * `batch_id` - The ID of the query
+    ///
+    /// # Returns
+    /// * `Result<AtlanticQuery, AtlanticError>` - Status, step, timing and error of the query
+    pub async fn get_query(&self, batch_id: &str) -> Result<AtlanticQuery, AtlanticError> {
         let response = self
             .client
             .get(format!("{}/atlantic-query/{}", self.endpoint, batch_id))
@@ -254,15 +518,28 @@ impl AtlanticClient {
             .send()
             .await?;
 
-        let response_data: serde_json::Value = response.json().await?;
-
-        let status = response_data["atlanticQuery"]["status"]
-            .as_str()
-            .ok_or_else(|| AtlanticError::InvalidResponse("Missing status field".into()))?;
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(AtlanticError::InvalidResponse(format!(
+                "Query {} lookup failed: {}",
+                batch_id, error_text
+            )));
+        }
 
-        println!("Status: {}", status);
+        let response: AtlanticQueryResponse = serde_json::from_str(&response.text().await?)?;
+        Ok(response.atlantic_query)
+    }
 
-        Ok(status.to_string())
+    /// Polls the batch status with the configured interval until completion or failure.
+    ///
+    /// # Arguments
+    /// * `batch_id` - The ID of the batch to poll
+    ///
+    /// # Returns
+    /// * `Result<bool, AtlanticError>` - True if batch completed successfully
+    pub async fn wait_for_batch(&self, batch_id: &str) -> Result<bool, AtlanticError> {
+        self.poll_batch_status_until_done(batch_id, self.poll_interval, self.max_poll_attempts)
+            .await
     }
 
     /// Polls the batch status until completion or failure.
@@ -282,23 +559,25 @@ impl AtlanticClient {
     ) -> Result<bool, AtlanticError> {
         for attempt in 1..=max_retries {
             debug!("Pooling Atlantic for update... {}", batch_id);
-            let status = self.check_batch_status(batch_id).await?;
+            let query = self.get_query(batch_id).await?;
+            let status = query.status;
 
-            if status == "DONE" {
+            if status == AtlanticStatus::Done {
                 return Ok(true);
             }
 
-            if status == "FAILED" {
+            if status == AtlanticStatus::Failed {
                 return Err(AtlanticError::AtlanticProcessingError(format!(
-                    "Atlantic processing failed for query {}",
-                    batch_id
+                    "Atlantic processing failed for query {}: {}",
+                    batch_id,
+                    query.error_reason.as_deref().unwrap_or("no reason given")
                 )));
             }
 
             trace!(
-                "Batch {} not completed yet. Status: {}. Pooling attempt {}/{}",
+                "Batch {} not completed yet. Phase: {}. Pooling attempt {}/{}",
                 batch_id,
-                status,
+                query.phase(),
                 attempt,
                 max_retries
             );
@@ -311,3 +590,107 @@ impl AtlanticClient {
         )))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CREATED_AT: u64 = 1_748_779_200;
+
+    fn query(status: &str, step: Option<&str>, completed_at: Option<&str>) -> AtlanticQuery {
+        serde_json::from_value(serde_json::json!({
+            "id": "01JWQ3ZK8Y",
+            "status": status,
+            "step": step,
+            "createdAt": "2025-06-01T12:00:00.000Z",
+            "completedAt": completed_at,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn unknown_status_is_not_an_error() {
+        let query = query("PAUSED", None, None);
+        assert_eq!(query.status, AtlanticStatus::Unknown);
+        assert!(!query.status.is_final());
+        assert_eq!(query.phase(), "UNKNOWN");
+    }
+
+    #[test]
+    fn parses_reported_timestamps() {
+        let query = query("DONE", None, Some("2025-06-01T12:15:30.250Z"));
+        assert_eq!(query.created_at_secs(), Some(CREATED_AT));
+        assert_eq!(query.completed_at_secs(), Some(CREATED_AT + 930));
+
+        let mut malformed = query.clone();
+        malformed.created_at = Some("yesterday".to_string());
+        assert_eq!(malformed.created_at_secs(), None);
+    }
+
+    #[test]
+    fn phases_are_timed_by_atlantic_timestamps() {
+        let mut phases = Vec::new();
+        // Polls only happen long after the query was created and completed
+        observe_phase(&mut phases, &query("RECEIVED", None, None), CREATED_AT + 600);
+        observe_phase(&mut phases, &query("RECEIVED", None, None), CREATED_AT + 660);
+        observe_phase(
+            &mut phases,
+            &query("IN_PROGRESS", Some("TRACE_GENERATION"), None),
+            CREATED_AT + 720,
+        );
+        observe_phase(
+            &mut phases,
+            &query("DONE", None, Some("2025-06-01T12:15:00.000Z")),
+            CREATED_AT + 3600,
+        );
+
+        let started: Vec<(&str, u64)> = phases
+            .iter()
+            .map(|phase| (phase.name.as_str(), phase.started_at))
+            .collect();
+        assert_eq!(
+            started,
+            vec![
+                ("RECEIVED", CREATED_AT),
+                ("TRACE_GENERATION", CREATED_AT + 720),
+                ("DONE", CREATED_AT + 900),
+            ]
+        );
+        assert_eq!(phases[0].duration_secs(0), 720);
+        assert_eq!(phases[1].duration_secs(0), 180);
+        assert_eq!(phases[2].finished_at, Some(CREATED_AT + 900));
+    }
+
+    #[test]
+    fn phases_fall_back_to_observation_time() {
+        let mut phases = Vec::new();
+        let mut received = query("RECEIVED", None, None);
+        received.created_at = None;
+        observe_phase(&mut phases, &received, CREATED_AT + 60);
+        observe_phase(&mut phases, &query("FAILED", None, None), CREATED_AT + 120);
+
+        assert_eq!(phases[0].started_at, CREATED_AT + 60);
+        assert_eq!(phases[0].finished_at, Some(CREATED_AT + 120));
+        assert_eq!(phases[1].started_at, CREATED_AT + 120);
+    }
+
+    #[test]
+    fn phases_stay_ordered_when_completed_before_last_observation() {
+        let mut phases = Vec::new();
+        observe_phase(&mut phases, &query("RECEIVED", None, None), CREATED_AT);
+        observe_phase(
+            &mut phases,
+            &query("IN_PROGRESS", Some("PROOF_GENERATION"), None),
+            CREATED_AT + 600,
+        );
+        // Atlantic's clock is behind the local one
+        observe_phase(
+            &mut phases,
+            &query("DONE", None, Some("2025-06-01T12:09:00.000Z")),
+            CREATED_AT + 660,
+        );
+
+        assert_eq!(phases[1].finished_at, Some(CREATED_AT + 600));
+        assert_eq!(phases[2].started_at, CREATED_AT + 600);
+    }
+}
@@ -1,16 +1,24 @@
+use std::collections::HashMap;
+
 use alloy_rpc_types_beacon::{
     events::light_client_finality::SyncAggregate, header::HeaderResponse,
 };
+use alloy_primitives::{Bytes, FixedBytes};
 use itertools::Itertools;
-use reqwest::Client;
-use serde_json::Value;
+use reqwest::{
+    header::{ACCEPT, CONTENT_TYPE},
+    Client,
+};
+use serde_json::{json, Value};
 use thiserror::Error;
 use tokio::time::{sleep, Duration};
 use tracing::warn;
 
-use beacon_types::{eth_spec::MainnetEthSpec, BeaconBlockBody, FullPayload};
+use beacon_types::{
+    eth_spec::MainnetEthSpec, BeaconBlock, BeaconBlockBody, ForkName, FullPayload, SignedBeaconBlock,
+};
 
-use crate::{fetcher::sync_committee_input::SyncCommitteeValidatorPubs, utils::{config::BankaiConfig, constants}};
+use crate::{fetcher::sync_committee_input::SyncCommitteeValidatorPubs, metrics::{metrics, normalize_route}, utils::{bls::PointError, config::BankaiConfig, constants}};
 
 #[derive(Debug, Error)]
 pub enum BeaconError {
@@ -26,6 +34,27 @@ pub enum BeaconError {
     InvalidResponse(String),
     #[error("Parse int error: {0}")]
     ParseInt(#[from] std::num::ParseIntError),
+    #[error("Invalid validator key: {0}")]
+    InvalidPoint(#[from] PointError),
+    #[error("Validator {index} not found in the state at slot {slot}")]
+    ValidatorNotFound { index: u64, slot: u64 },
+    #[error("Validator {index} is not activated yet at slot {slot} (status: {status})")]
+    InactiveValidator { index: u64, slot: u64, status: String },
+}
+
+/// Number of validators requested per call to the `validators` endpoint
+const VALIDATORS_PER_REQUEST: usize = 128;
+
+/// Prefer SSZ, but accept JSON from nodes that do not support it
+const SSZ_ACCEPT: &str = "application/octet-stream;q=1.0,application/json;q=0.9";
+const SSZ_CONTENT_TYPE: &str = "application/octet-stream";
+/// Header naming the fork of an SSZ encoded response
+const CONSENSUS_VERSION_HEADER: &str = "Eth-Consensus-Version";
+
+/// A block as returned by the beacon node
+enum BlockPayload {
+    Ssz(Box<SignedBeaconBlock<MainnetEthSpec>>),
+    Json(Value),
 }
 
 /// A client for interacting with the Ethereum Beacon Chain RPC endpoints.
@@ -34,7 +63,7 @@ pub enum BeaconError {
 pub struct BeaconRpcClient {
     provider: Client,
     pub rpc_url: String,
-    _config: BankaiConfig,
+    config: BankaiConfig,
 }
 
 impl BeaconRpcClient {
@@ -42,24 +71,57 @@ impl BeaconRpcClient {
     ///
     /// # Arguments
     /// * `rpc_url` - The base URL for the Beacon Chain RPC endpoint
+    /// * `config` - The Bankai configuration, used for rate limiting and retries
     pub fn new(rpc_url: String, config: BankaiConfig) -> Self {
         Self {
             provider: reqwest::Client::new(),
             rpc_url,
-            _config: config,
+            config,
         }
     }
 
+    /// Returns the configuration the client was created with
+    pub fn config(&self) -> &BankaiConfig {
+        &self.config
+    }
+
     /// Makes an HTTP GET request and returns the JSON response.
     /// This is a helper method used by all other RPC calls.
     async fn get_json(&self, route: &str) -> Result<Value, BeaconError> {
+        self.request_json(route, None).await
+    }
+
+    /// Makes an HTTP POST request with a JSON body and returns the JSON response.
+    async fn post_json(&self, route: &str, body: &Value) -> Result<Value, BeaconError> {
+        self.request_json(route, Some(body)).await
+    }
+
+    async fn request_json(&self, route: &str, body: Option<&Value>) -> Result<Value, BeaconError> {
         // Add a delay to avoid hitting rate limits
-        sleep(Duration::from_millis(70)).await;
+        sleep(Duration::from_millis(self.config.beacon.request_delay_ms)).await;
 
         let url = format!("{}/{}", self.rpc_url, route);
-        let json = self.provider.get(url).send().await?.json().await?;
+        let label = normalize_route(route);
+        let timer = metrics()
+            .beacon_rpc_duration_seconds
+            .with_label_values(&[&label])
+            .start_timer();
+
+        let request = match body {
+            Some(body) => self.provider.post(url).json(body),
+            None => self.provider.get(url),
+        };
+        let result: Result<Value, reqwest::Error> =
+            async { request.send().await?.json().await }.await;
+        timer.observe_duration();
 
-        Ok(json)
+        result.map_err(|e| {
+            metrics()
+                .beacon_rpc_errors_total
+                .with_label_values(&[&label])
+                .inc();
+            BeaconError::Rpc(e)
+        })
     }
 
     /// Fetches the beacon chain header for a specific slot.
@@ -95,14 +157,14 @@ impl BeaconRpcClient {
                 Ok(header) => break header,
                 Err(BeaconError::EmptySlot(_)) => {
                     attempts += 1;
-                    if attempts >= constants::MAX_SKIPPED_SLOTS_RETRY_ATTEMPTS {
+                    if attempts >= self.config.beacon.max_skipped_slots {
                         return Err(BeaconError::EmptySlot(slot));
                     }
                     slot += 1;
                     warn!(
                         "Empty slot detected! Attempt {}/{}. Fetching slot: {}",
                         attempts,
-                        constants::MAX_SKIPPED_SLOTS_RETRY_ATTEMPTS,
+                        self.config.beacon.max_skipped_slots,
                         slot
                     );
                 }
@@ -110,24 +172,111 @@ impl BeaconRpcClient {
             }
         };
 
-        let json = self
-            .get_json(&format!("eth/v2/beacon/blocks/{}", slot))
-            .await?;
+        match self.fetch_block(slot).await? {
+            BlockPayload::Ssz(block) => {
+                let sync_aggregate = block.message().body().sync_aggregate().map_err(|e| {
+                    BeaconError::InvalidResponse(format!("Block {} has no sync aggregate: {:?}", slot, e))
+                })?;
+                Ok(SyncAggregate {
+                    sync_committee_bits: Bytes::copy_from_slice(
+                        sync_aggregate.sync_committee_bits.as_slice(),
+                    ),
+                    sync_committee_signature: FixedBytes::from(
+                        sync_aggregate.sync_committee_signature.serialize(),
+                    ),
+                })
+            }
+            BlockPayload::Json(json) => {
+                let sync_aggr = serde_json::from_value(
+                    json["data"]["message"]["body"]["sync_aggregate"].clone(),
+                )?;
+                Ok(sync_aggr)
+            }
+        }
+    }
+
+    /// Fetches a signed block, as SSZ if enabled and supported by the beacon node.
+    ///
+    /// SSZ blocks are decoded for the fork named in the `Eth-Consensus-Version` header. If the
+    /// node answers with JSON or the SSZ cannot be decoded, the JSON block is returned instead.
+    async fn fetch_block(&self, slot: u64) -> Result<BlockPayload, BeaconError> {
+        let route = format!("eth/v2/beacon/blocks/{}", slot);
+        if !self.config.beacon.ssz {
+            return Ok(BlockPayload::Json(self.get_json(&route).await?));
+        }
 
-        let sync_aggr =
-            serde_json::from_value(json["data"]["message"]["body"]["sync_aggregate"].clone())?;
-        Ok(sync_aggr)
+        sleep(Duration::from_millis(self.config.beacon.request_delay_ms)).await;
+        let label = normalize_route(&route);
+        let timer = metrics()
+            .beacon_rpc_duration_seconds
+            .with_label_values(&[&label])
+            .start_timer();
+        let result = async {
+            let response = self
+                .provider
+                .get(format!("{}/{}", self.rpc_url, route))
+                .header(ACCEPT, SSZ_ACCEPT)
+                .send()
+                .await?;
+            let is_ssz = response
+                .headers()
+                .get(CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .is_some_and(|value| value.starts_with(SSZ_CONTENT_TYPE));
+            let fork = response
+                .headers()
+                .get(CONSENSUS_VERSION_HEADER)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string);
+            let body = response.bytes().await?;
+            Ok::<_, reqwest::Error>((is_ssz, fork, body))
+        }
+        .await;
+        timer.observe_duration();
+
+        let (is_ssz, fork, body) = result.map_err(|e| {
+            metrics()
+                .beacon_rpc_errors_total
+                .with_label_values(&[&label])
+                .inc();
+            BeaconError::Rpc(e)
+        })?;
+
+        if !is_ssz {
+            return Ok(BlockPayload::Json(serde_json::from_slice(&body)?));
+        }
+        let decoded = fork
+            .ok_or_else(|| format!("missing {} header", CONSENSUS_VERSION_HEADER))
+            .and_then(|fork| fork.parse::<ForkName>())
+            .and_then(|fork| {
+                SignedBeaconBlock::<MainnetEthSpec>::from_ssz_bytes_for_fork(&body, fork)
+                    .map_err(|e| format!("{:?}", e))
+            });
+        match decoded {
+            Ok(block) => Ok(BlockPayload::Ssz(Box::new(block))),
+            Err(e) => {
+                warn!("Failed to decode SSZ block {}, falling back to JSON: {}", slot, e);
+                Ok(BlockPayload::Json(self.get_json(&route).await?))
+            }
+        }
     }
 
     /// Retrieves the list of validator indices that are part of the sync committee
     /// for the specified slot.
     ///
     /// The returned indices can be used to fetch the corresponding public keys
-    /// using fetch_validator_pubkeys().
-    async fn fetch_sync_committee_indexes(&self, slot: u64) -> Result<Vec<u64>, BeaconError> {
-        let json = self
-            .get_json(&format!("eth/v1/beacon/states/{}/sync_committees", slot))
-            .await?;
+    /// using fetch_validator_pubkeys(). If `epoch` is given, the committee of that epoch is
+    /// returned instead, which may be the committee of the following period.
+    async fn fetch_sync_committee_indexes(
+        &self,
+        slot: u64,
+        epoch: Option<u64>,
+    ) -> Result<Vec<u64>, BeaconError> {
+        let route = match epoch {
+            Some(epoch) => format!("eth/v1/beacon/states/{}/sync_committees?epoch={}", slot, epoch),
+            None => format!("eth/v1/beacon/states/{}/sync_committees", slot),
+        };
+        let json = self.get_json(&route).await?;
 
         // Parse the array of validator indices from the JSON response
         json["data"]["validators"]
@@ -142,36 +291,63 @@ impl BeaconRpcClient {
             .collect()
     }
 
-    /// Fetches the public keys for a list of validator indices.
+    /// Fetches the public keys for a list of validator indices at the state of `slot`.
+    ///
+    /// Validators are requested in chunks through the POST `validators` endpoint, as 512
+    /// indices do not fit a query string on every provider.
     ///
     /// # Arguments
-    /// * `indexes` - Array of validator indices to look up
+    /// * `slot` - Slot of the state to read the validators from
+    /// * `indexes` - Array of validator indices to look up, may contain duplicates
     ///
     /// # Returns
     /// A vector of public keys in the same order as the input indices.
-    /// If a validator index is not found, returns an error.
-    async fn fetch_validator_pubkeys(&self, indexes: &[u64]) -> Result<Vec<String>, BeaconError> {
-        // Construct query string with all validator indices
-        let query = indexes.iter().map(|i| format!("id={}", i)).join("&");
-        let json = self
-            .get_json(&format!("eth/v1/beacon/states/head/validators?{}", query))
-            .await?;
+    /// If a validator index is not found or not activated yet, returns an error. Committee
+    /// members stay in the committee for the whole period, so validators that exited since
+    /// the committee was selected are accepted.
+    async fn fetch_validator_pubkeys(
+        &self,
+        slot: u64,
+        indexes: &[u64],
+    ) -> Result<Vec<String>, BeaconError> {
+        let unique: Vec<u64> = indexes.iter().copied().unique().collect();
+        let route = format!("eth/v1/beacon/states/{}/validators", slot);
+
+        let mut validators: HashMap<u64, (String, String)> = HashMap::with_capacity(unique.len());
+        for chunk in unique.chunks(VALIDATORS_PER_REQUEST) {
+            let body = json!({ "ids": chunk.iter().map(u64::to_string).collect::<Vec<_>>() });
+            let json = self.post_json(&route, &body).await?;
+            let data = json["data"].as_array().ok_or_else(|| {
+                BeaconError::InvalidResponse(format!("Missing validators in response: {}", json))
+            })?;
+
+            for validator in data {
+                let index: u64 = validator["index"]
+                    .as_str()
+                    .ok_or_else(|| BeaconError::InvalidResponse("Missing validator index".to_string()))?
+                    .parse()?;
+                let pubkey = validator["validator"]["pubkey"]
+                    .as_str()
+                    .ok_or_else(|| BeaconError::InvalidResponse(format!("Missing pubkey of validator {}", index)))?;
+                let status = validator["status"].as_str().unwrap_or_default();
+                validators.insert(index, (pubkey.to_string(), status.to_string()));
+            }
+        }
 
-        let validators = json["data"]
-            .as_array()
-            .ok_or(BeaconError::FetchSyncCommittee)?;
-        // Match returned validators with requested indices and extract public keys
         indexes
             .iter()
             .map(|index| {
-                validators
-                    .iter()
-                    .find(|v| {
-                        v["index"].as_str().and_then(|i| i.parse::<u64>().ok()) == Some(*index)
-                    })
-                    .and_then(|v| v["validator"]["pubkey"].as_str())
-                    .map(String::from)
-                    .ok_or(BeaconError::FetchSyncCommittee)
+                let (pubkey, status) = validators
+                    .get(index)
+                    .ok_or(BeaconError::ValidatorNotFound { index: *index, slot })?;
+                if status.starts_with("pending") {
+                    return Err(BeaconError::InactiveValidator {
+                        index: *index,
+                        slot,
+                        status: status.clone(),
+                    });
+                }
+                Ok(pubkey.clone())
             })
             .collect()
     }
@@ -180,14 +356,25 @@ impl BeaconRpcClient {
         &self,
         slot: u64,
     ) -> Result<BeaconBlockBody<MainnetEthSpec, FullPayload<MainnetEthSpec>>, BeaconError> {
-        let json = self
-            .get_json(&format!("eth/v2/beacon/blocks/{}", slot))
-            .await?;
-
-        let block: BeaconBlockBody<MainnetEthSpec, FullPayload<MainnetEthSpec>> =
-            serde_json::from_value(json["data"]["message"]["body"].clone())?;
-
-        Ok(block)
+        match self.fetch_block(slot).await? {
+            BlockPayload::Ssz(block) => {
+                let (block, _signature) = block.deconstruct();
+                Ok(match block {
+                    BeaconBlock::Base(block) => BeaconBlockBody::Base(block.body),
+                    BeaconBlock::Altair(block) => BeaconBlockBody::Altair(block.body),
+                    BeaconBlock::Bellatrix(block) => BeaconBlockBody::Bellatrix(block.body),
+                    BeaconBlock::Capella(block) => BeaconBlockBody::Capella(block.body),
+                    BeaconBlock::Deneb(block) => BeaconBlockBody::Deneb(block.body),
+                    BeaconBlock::Electra(block) => BeaconBlockBody::Electra(block.body),
+                    BeaconBlock::Fulu(block) => BeaconBlockBody::Fulu(block.body),
+                })
+            }
+            BlockPayload::Json(json) => {
+                let block: BeaconBlockBody<MainnetEthSpec, FullPayload<MainnetEthSpec>> =
+                    serde_json::from_value(json["data"]["message"]["body"].clone())?;
+                Ok(block)
+            }
+        }
     }
 
     /// Fetches the public keys of validators in the sync committee for a given slot.
@@ -204,9 +391,51 @@ impl BeaconRpcClient {
         slot: u64,
     ) -> Result<SyncCommitteeValidatorPubs, BeaconError> {
         let slot = slot + 1;
-        let indexes = self.fetch_sync_committee_indexes(slot).await?;
-        let pubkeys = self.fetch_validator_pubkeys(&indexes).await?;
-        Ok(pubkeys.into())
+        let indexes = self.fetch_sync_committee_indexes(slot, None).await?;
+        let pubkeys = self.fetch_validator_pubkeys(slot, &indexes).await?;
+        Ok(pubkeys.try_into()?)
+    }
+
+    /// Fetches the public keys of the next sync committee, as known in the state at `slot`.
+    ///
+    /// # Arguments
+    /// * `slot` - A slot of the period before the committee becomes active
+    ///
+    /// # Returns
+    /// Returns a `SyncCommitteeValidatorPubs` for the sync committee of the following period.
+    pub async fn get_next_sync_committee_validator_pubs(
+        &self,
+        slot: u64,
+    ) -> Result<SyncCommitteeValidatorPubs, BeaconError> {
+        let next_period = slot / constants::SLOTS_PER_SYNC_COMMITTEE + 1;
+        let epoch = next_period * constants::EPOCHS_PER_SYNC_COMMITTEE;
+        let indexes = self.fetch_sync_committee_indexes(slot, Some(epoch)).await?;
+        let pubkeys = self.fetch_validator_pubkeys(slot, &indexes).await?;
+        Ok(pubkeys.try_into()?)
+    }
+
+    /// Fetches the best light client update of a sync committee period.
+    ///
+    /// # Arguments
+    /// * `period` - The sync committee period
+    ///
+    /// # Returns
+    /// The `data` object of the update, holding the attested header, the next sync committee
+    /// and its branch.
+    pub async fn get_light_client_update(&self, period: u64) -> Result<Value, BeaconError> {
+        let json = self
+            .get_json(&format!(
+                "eth/v1/beacon/light_client/updates?start_period={}&count=1",
+                period
+            ))
+            .await?;
+
+        json.as_array()
+            .and_then(|updates| updates.first())
+            .map(|update| update["data"].clone())
+            .ok_or_else(|| {
+                BeaconError::InvalidResponse(format!("No light client update for period {}: {}", period, json))
+            })
     }
 
     /// Fetches the current head slot of the beacon chain.
@@ -0,0 +1,361 @@
+//! Beacon Node Event Stream
+//!
+//! Subscribes to the server-sent events of `eth/v1/events` and turns them into typed
+//! `BeaconEvent`s. Providers differ in how they frame events: some omit the `event:` line,
+//! some send numbers unquoted and some wrap the payload in a `data` object. The topic is
+//! therefore inferred from the payload when the event name is missing. Dropped connections
+//! are re-established with exponential backoff.
+
+use std::time::Duration;
+
+use futures::StreamExt;
+use reqwest::{header::ACCEPT, Client};
+use serde_json::Value;
+use thiserror::Error;
+use tokio::{sync::mpsc, time::sleep};
+use tracing::{debug, info, warn};
+
+use crate::utils::helpers::extract_json_from_event;
+
+/// Topics needed to follow the chain and its finality
+pub const DEFAULT_TOPICS: &[&str] = &["head", "finalized_checkpoint", "light_client_finality_update"];
+
+/// Delay before the first reconnection attempt, doubled on every failure
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+
+/// An event emitted by the beacon node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BeaconEvent {
+    /// A new head block
+    Head { slot: u64, block: String },
+    /// A new finalized checkpoint
+    FinalizedCheckpoint { epoch: u64, block: String },
+    /// A new light client finality update
+    LightClientFinalityUpdate { attested_slot: u64, finalized_slot: u64 },
+}
+
+/// Subscribes to beacon node events and forwards them to a channel
+#[derive(Debug)]
+pub struct EventSubscriber {
+    client: Client,
+    url: String,
+}
+
+impl EventSubscriber {
+    /// Creates a subscriber for the given topics
+    ///
+    /// # Arguments
+    /// * `rpc_url` - Base URL of the beacon node RPC
+    /// * `topics` - Event topics to subscribe to, e.g. `DEFAULT_TOPICS`
+    pub fn new(rpc_url: &str, topics: &[&str]) -> Self {
+        Self {
+            client: Client::new(),
+            url: format!("{}/eth/v1/events?topics={}", rpc_url, topics.join(",")),
+        }
+    }
+
+    /// Streams events into `sender` until the receiving side is dropped.
+    ///
+    /// Connection errors and closed streams are logged and followed by a reconnect, so this
+    /// only returns once nobody listens anymore.
+    pub async fn run(&self, sender: mpsc::Sender<BeaconEvent>) {
+        let mut delay = INITIAL_RECONNECT_DELAY;
+        loop {
+            match self.stream(&sender, &mut delay).await {
+                Ok(StreamEnd::ReceiverDropped) => return,
+                Ok(StreamEnd::Closed) => warn!("⚠️  Event stream closed by the beacon node"),
+                Err(e) => warn!("⚠️  Event stream failed: {}", e),
+            }
+
+            info!("🔌 Reconnecting to the event stream in {:?}...", delay);
+            sleep(delay).await;
+            delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+        }
+    }
+
+    /// Reads one connection until it ends. `delay` is reset once events arrive.
+    async fn stream(
+        &self,
+        sender: &mpsc::Sender<BeaconEvent>,
+        delay: &mut Duration,
+    ) -> Result<StreamEnd, EventError> {
+        let response = self
+            .client
+            .get(&self.url)
+            .header(ACCEPT, "text/event-stream")
+            .send()
+            .await?
+            .error_for_status()?;
+        info!("📡 Subscribed to beacon events");
+
+        let mut chunks = response.bytes_stream();
+        // Raw bytes, as chunks may split a line ending or a multi-byte character
+        let mut buffer = Vec::new();
+        while let Some(chunk) = chunks.next().await {
+            buffer.extend_from_slice(&chunk?);
+
+            while let Some(frame) = next_frame(&mut buffer) {
+                match parse_event(&frame) {
+                    Ok(Some(event)) => {
+                        *delay = INITIAL_RECONNECT_DELAY;
+                        if sender.send(event).await.is_err() {
+                            return Ok(StreamEnd::ReceiverDropped);
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(e) => warn!("⚠️  Skipping malformed event: {}", e),
+                }
+            }
+        }
+
+        Ok(StreamEnd::Closed)
+    }
+}
+
+enum StreamEnd {
+    Closed,
+    ReceiverDropped,
+}
+
+/// Removes the first complete frame from `buffer`, with its line endings normalized to `\n`.
+///
+/// Frames are separated by an empty line. Lines may end in `\n` or `\r\n`.
+fn next_frame(buffer: &mut Vec<u8>) -> Option<String> {
+    let end = buffer.iter().enumerate().find_map(|(i, byte)| {
+        if *byte != b'\n' {
+            return None;
+        }
+        match &buffer[i + 1..] {
+            [b'\n', ..] => Some(i + 2),
+            [b'\r', b'\n', ..] => Some(i + 3),
+            _ => None,
+        }
+    })?;
+    let frame: Vec<u8> = buffer.drain(..end).collect();
+    Some(String::from_utf8_lossy(&frame).replace("\r\n", "\n"))
+}
+
+/// Parses a single server-sent event frame.
+///
+/// # Returns
+/// * `Result<Option<BeaconEvent>, EventError>` - `None` for comments, keep-alives and topics
+///   that are not handled
+pub fn parse_event(frame: &str) -> Result<Option<BeaconEvent>, EventError> {
+    let Some(data) = extract_json_from_event(frame) else {
+        return Ok(None);
+    };
+    let json: Value = serde_json::from_str(&data)?;
+    // Light client updates and some providers wrap the payload in `data`
+    let payload = match json.get("data") {
+        Some(inner) if inner.is_object() => inner,
+        _ => &json,
+    };
+
+    let topic = frame
+        .lines()
+        .find_map(|line| line.strip_prefix("event:"))
+        .map(|name| name.trim().to_string())
+        .or_else(|| infer_topic(payload).map(str::to_string));
+    let Some(topic) = topic else {
+        debug!("Ignoring event without recognizable topic: {}", data);
+        return Ok(None);
+    };
+
+    let event = match topic.as_str() {
+        "head" => BeaconEvent::Head {
+            slot: parse_u64(payload, &["slot"])?,
+            block: parse_string(payload, "block")?,
+        },
+        "finalized_checkpoint" => BeaconEvent::FinalizedCheckpoint {
+            epoch: parse_u64(payload, &["epoch"])?,
+            block: parse_string(payload, "block")?,
+        },
+        "light_client_finality_update" => BeaconEvent::LightClientFinalityUpdate {
+            attested_slot: header_slot(payload, "attested_header")?,
+            finalized_slot: header_slot(payload, "finalized_header")?,
+        },
+        _ => return Ok(None),
+    };
+    Ok(Some(event))
+}
+
+/// Guesses the topic of an event sent without an `event:` line
+fn infer_topic(payload: &Value) -> Option<&'static str> {
+    if payload.get("attested_header").is_some() {
+        Some("light_client_finality_update")
+    } else if payload.get("epoch").is_some() && payload.get("block").is_some() {
+        Some("finalized_checkpoint")
+    } else if payload.get("slot").is_some() && payload.get("block").is_some() {
+        Some("head")
+    } else {
+        None
+    }
+}
+
+/// Reads the slot of a light client header, which is nested in `beacon` since Capella
+fn header_slot(payload: &Value, header: &str) -> Result<u64, EventError> {
+    let header = &payload[header];
+    match header.get("beacon") {
+        Some(beacon) => parse_u64(beacon, &["slot"]),
+        None => parse_u64(header, &["slot"]),
+    }
+}
+
+/// Reads an integer sent either as a JSON number or as a decimal string
+fn parse_u64(value: &Value, path: &[&str]) -> Result<u64, EventError> {
+    let field = path.iter().fold(value, |value, key| &value[*key]);
+    field
+        .as_u64()
+        .or_else(|| field.as_str().and_then(|s| s.parse().ok()))
+        .ok_or_else(|| EventError::MissingField(path.join(".")))
+}
+
+fn parse_string(value: &Value, field: &str) -> Result<String, EventError> {
+    value[field]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| EventError::MissingField(field.to_string()))
+}
+
+/// Possible errors that can occur while reading the event stream
+#[derive(Debug, Error)]
+pub enum EventError {
+    /// Connection or HTTP error
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+    /// The event data is not valid JSON
+    #[error("Invalid event JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    /// A field required for the event type is missing
+    #[error("Missing field {0} in event")]
+    MissingField(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLOCK: &str = "0x9a2fefd2fdb57f74993c7780ea5b9030d2897b615b89f808011ca5aebed54eaf";
+
+    fn head(slot: u64) -> BeaconEvent {
+        BeaconEvent::Head {
+            slot,
+            block: BLOCK.to_string(),
+        }
+    }
+
+    #[test]
+    fn parses_named_events() {
+        let frame = format!("event: head\ndata: {{\"slot\":\"10\",\"block\":\"{}\"}}\n\n", BLOCK);
+        assert_eq!(parse_event(&frame).unwrap(), Some(head(10)));
+
+        let frame = format!(
+            "event: finalized_checkpoint\ndata: {{\"epoch\":\"3\",\"block\":\"{}\",\"state\":\"0x00\"}}\n\n",
+            BLOCK
+        );
+        assert_eq!(
+            parse_event(&frame).unwrap(),
+            Some(BeaconEvent::FinalizedCheckpoint {
+                epoch: 3,
+                block: BLOCK.to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn infers_topic_without_event_line() {
+        let frame = format!("data:{{\"slot\":\"11\",\"block\":\"{}\"}}\n\n", BLOCK);
+        assert_eq!(parse_event(&frame).unwrap(), Some(head(11)));
+
+        let frame = format!("data: {{\"epoch\":\"4\",\"block\":\"{}\"}}\n\n", BLOCK);
+        assert!(matches!(
+            parse_event(&frame).unwrap(),
+            Some(BeaconEvent::FinalizedCheckpoint { epoch: 4, .. })
+        ));
+    }
+
+    #[test]
+    fn accepts_unquoted_numbers_and_wrapped_payloads() {
+        let frame = format!("event: head\ndata: {{\"data\":{{\"slot\":12,\"block\":\"{}\"}}}}\n\n", BLOCK);
+        assert_eq!(parse_event(&frame).unwrap(), Some(head(12)));
+    }
+
+    #[test]
+    fn joins_multi_line_data() {
+        let frame = format!("event: head\ndata: {{\"slot\":\"13\",\ndata: \"block\":\"{}\"}}\n\n", BLOCK);
+        assert_eq!(parse_event(&frame).unwrap(), Some(head(13)));
+    }
+
+    #[test]
+    fn reads_light_client_header_slots() {
+        // Capella and later nest the header in `beacon`
+        let frame = "event: light_client_finality_update\ndata: {\"version\":\"deneb\",\"data\":{\
+            \"attested_header\":{\"beacon\":{\"slot\":\"100\"}},\
+            \"finalized_header\":{\"beacon\":{\"slot\":\"64\"}}}}\n\n";
+        assert_eq!(
+            parse_event(frame).unwrap(),
+            Some(BeaconEvent::LightClientFinalityUpdate {
+                attested_slot: 100,
+                finalized_slot: 64
+            })
+        );
+
+        let frame = "data: {\"attested_header\":{\"slot\":\"101\"},\"finalized_header\":{\"slot\":\"96\"}}\n\n";
+        assert_eq!(
+            parse_event(frame).unwrap(),
+            Some(BeaconEvent::LightClientFinalityUpdate {
+                attested_slot: 101,
+                finalized_slot: 96
+            })
+        );
+    }
+
+    #[test]
+    fn ignores_comments_and_unknown_topics() {
+        assert_eq!(parse_event(": keep-alive\n\n").unwrap(), None);
+        assert_eq!(parse_event("event: block\ndata: {\"slot\":\"1\"}\n\n").unwrap(), None);
+        assert_eq!(parse_event("data: {\"foo\":1}\n\n").unwrap(), None);
+    }
+
+    #[test]
+    fn reports_malformed_events() {
+        assert!(matches!(parse_event("event: head\ndata: {\"slot\"\n\n"), Err(EventError::Json(_))));
+        assert!(matches!(
+            parse_event("event: head\ndata: {\"slot\":\"1\"}\n\n"),
+            Err(EventError::MissingField(field)) if field == "block"
+        ));
+    }
+
+    #[test]
+    fn splits_frames_across_chunks() {
+        let event = format!("event: head\r\ndata: {{\"slot\":\"14\",\"block\":\"{}\",\"note\":\"é\"}}\r\n\r\n", BLOCK);
+        let bytes = event.as_bytes();
+        let accent = event.find('é').unwrap();
+        // Split inside the two-byte character and between `\r` and `\n` of the separator
+        let chunks = [&bytes[..accent + 1], &bytes[accent + 1..bytes.len() - 1], &bytes[bytes.len() - 1..]];
+
+        let mut buffer = Vec::new();
+        let mut frames = Vec::new();
+        for chunk in chunks {
+            buffer.extend_from_slice(chunk);
+            while let Some(frame) = next_frame(&mut buffer) {
+                frames.push(frame);
+            }
+        }
+
+        assert_eq!(frames.len(), 1);
+        assert!(!frames[0].contains('\r'));
+        assert!(frames[0].contains('é'));
+        assert_eq!(parse_event(&frames[0]).unwrap(), Some(head(14)));
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn keeps_incomplete_frames_buffered() {
+        let mut buffer = b"event: head\ndata: {}\n\nevent: head\n".to_vec();
+        assert_eq!(next_frame(&mut buffer).as_deref(), Some("event: head\ndata: {}\n\n"));
+        assert_eq!(next_frame(&mut buffer), None);
+        assert_eq!(buffer, b"event: head\n");
+    }
+}
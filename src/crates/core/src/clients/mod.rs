@@ -2,6 +2,8 @@ use thiserror::Error;
 
 pub mod atlantic;
 pub mod beacon_chain;
+pub mod events;
+pub mod starknet;
 
 #[derive(Debug, Error)]
 pub enum ClientError {
@@ -9,4 +11,6 @@ pub enum ClientError {
     Beacon(#[from] beacon_chain::BeaconError),
     #[error("Atlantic error: {0}")]
     Atlantic(#[from] atlantic::AtlanticError),
+    #[error("Starknet error: {0}")]
+    Starknet(#[from] starknet::StarknetError),
 }
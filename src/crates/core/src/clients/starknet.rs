@@ -0,0 +1,301 @@
+//! Starknet Contract Reader
+//!
+//! Reads the state of the Bankai verifier contract over Starknet JSON-RPC: the latest verified
+//! slot, the committee hashes and the epoch proofs stored per slot. This is what consumers of
+//! Bankai see on-chain, so it is compared against the local `epoch_updates` table to find epoch
+//! updates that were proven but never landed.
+
+use alloy_primitives::FixedBytes;
+use starknet::{
+    core::{
+        types::{BlockId, BlockTag, Felt, FunctionCall},
+        utils::get_selector_from_name,
+    },
+    providers::{jsonrpc::HttpTransport, JsonRpcClient, Provider, ProviderError, Url},
+};
+use thiserror::Error;
+use tracing::debug;
+
+use crate::{fetcher::recursive_epoch_input::RecursiveEpochOutput, utils::config::StarknetConfig};
+
+/// Epoch state stored by the contract for a verified slot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OnChainEpoch {
+    pub header_root: FixedBytes<32>,
+    pub beacon_state_root: FixedBytes<32>,
+    pub n_signers: u64,
+    pub execution_header_root: FixedBytes<32>,
+    pub execution_header_height: u64,
+}
+
+impl OnChainEpoch {
+    /// Number of felts the contract returns for an epoch proof
+    const N_FELTS: usize = 8;
+
+    /// Decodes the result of `get_epoch_proof`: the header root, beacon state root, number of
+    /// signers, execution header root and execution header height, each u256 as low and high limb
+    pub fn from_felts(result: &[Felt]) -> Result<Self, StarknetError> {
+        let result = expect_len(result, Self::N_FELTS)?;
+        Ok(Self {
+            header_root: u256_to_bytes(result[0], result[1]),
+            beacon_state_root: u256_to_bytes(result[2], result[3]),
+            n_signers: felt_to_u64(result[4])?,
+            execution_header_root: u256_to_bytes(result[5], result[6]),
+            execution_header_height: felt_to_u64(result[7])?,
+        })
+    }
+
+    /// Returns true if the contract has no epoch stored for the slot
+    pub fn is_empty(&self) -> bool {
+        self.header_root.is_zero() && self.beacon_state_root.is_zero() && self.n_signers == 0
+    }
+
+    /// Lists the fields that differ from the outputs of a local epoch update
+    pub fn diff(&self, outputs: &RecursiveEpochOutput) -> Vec<String> {
+        let mut differences = Vec::new();
+        let mut check = |name: &str, on_chain: String, local: String| {
+            if on_chain != local {
+                differences.push(format!("{}: on-chain {}, local {}", name, on_chain, local));
+            }
+        };
+        check("header_root", self.header_root.to_string(), outputs.beacon_header_root.to_string());
+        check(
+            "beacon_state_root",
+            self.beacon_state_root.to_string(),
+            outputs.beacon_state_root.to_string(),
+        );
+        check("n_signers", self.n_signers.to_string(), outputs.n_signers.to_string());
+        check(
+            "execution_header_root",
+            self.execution_header_root.to_string(),
+            outputs.execution_header_root.to_string(),
+        );
+        check(
+            "execution_header_height",
+            self.execution_header_height.to_string(),
+            outputs.execution_header_height.to_string(),
+        );
+        differences
+    }
+}
+
+/// Read-only client for the Bankai verifier contract
+#[derive(Debug)]
+pub struct StarknetClient {
+    provider: JsonRpcClient<HttpTransport>,
+    contract_address: Felt,
+}
+
+impl StarknetClient {
+    /// Creates a client for the contract configured in `config`
+    ///
+    /// # Arguments
+    /// * `config` - Starknet RPC URL and contract address
+    pub fn new(config: &StarknetConfig) -> Result<Self, StarknetError> {
+        let rpc_url = config
+            .rpc_url
+            .as_deref()
+            .ok_or(StarknetError::Config("starknet.rpc_url (STARKNET_RPC_URL) is not set"))?;
+        let contract_address = config.contract_address.as_deref().ok_or(StarknetError::Config(
+            "starknet.contract_address (BANKAI_CONTRACT_ADDRESS) is not set",
+        ))?;
+
+        let url = Url::parse(rpc_url).map_err(|e| StarknetError::InvalidValue(e.to_string()))?;
+        Ok(Self {
+            provider: JsonRpcClient::new(HttpTransport::new(url)),
+            contract_address: Felt::from_hex(contract_address)
+                .map_err(|e| StarknetError::InvalidValue(format!("{}: {}", contract_address, e)))?,
+        })
+    }
+
+    /// Returns the slot of the latest epoch verified on-chain
+    pub async fn get_latest_epoch_slot(&self) -> Result<u64, StarknetError> {
+        let result = self.call("get_latest_epoch_slot", vec![]).await?;
+        felt_to_u64(expect_len(&result, 1)?[0])
+    }
+
+    /// Returns the ID of the latest committee verified on-chain
+    pub async fn get_latest_committee_id(&self) -> Result<u64, StarknetError> {
+        let result = self.call("get_latest_committee_id", vec![]).await?;
+        felt_to_u64(expect_len(&result, 1)?[0])
+    }
+
+    /// Returns the hash of a verified committee, zero if the committee is unknown
+    ///
+    /// # Arguments
+    /// * `committee_id` - Sync committee period of the committee
+    pub async fn get_committee_hash(&self, committee_id: u64) -> Result<FixedBytes<32>, StarknetError> {
+        let result = self
+            .call("get_committee_hash", vec![Felt::from(committee_id)])
+            .await?;
+        committee_hash_from_felts(&result)
+    }
+
+    /// Returns the epoch stored for a verified slot
+    ///
+    /// # Arguments
+    /// * `slot` - Slot of the epoch update
+    pub async fn get_epoch_proof(&self, slot: u64) -> Result<OnChainEpoch, StarknetError> {
+        let result = self.call("get_epoch_proof", vec![Felt::from(slot)]).await?;
+        OnChainEpoch::from_felts(&result)
+    }
+
+    async fn call(&self, function: &str, calldata: Vec<Felt>) -> Result<Vec<Felt>, StarknetError> {
+        debug!("Calling {} on the Bankai contract", function);
+        let entry_point_selector = get_selector_from_name(function)
+            .map_err(|e| StarknetError::InvalidValue(e.to_string()))?;
+        Ok(self
+            .provider
+            .call(
+                FunctionCall {
+                    contract_address: self.contract_address,
+                    entry_point_selector,
+                    calldata,
+                },
+                BlockId::Tag(BlockTag::Latest),
+            )
+            .await?)
+    }
+}
+
+/// Decodes the u256 committee hash returned by `get_committee_hash`
+fn committee_hash_from_felts(result: &[Felt]) -> Result<FixedBytes<32>, StarknetError> {
+    let result = expect_len(result, 2)?;
+    Ok(u256_to_bytes(result[0], result[1]))
+}
+
+fn expect_len(result: &[Felt], expected: usize) -> Result<&[Felt], StarknetError> {
+    if result.len() != expected {
+        return Err(StarknetError::InvalidResponse(format!(
+            "expected {} felts, got {}",
+            expected,
+            result.len()
+        )));
+    }
+    Ok(result)
+}
+
+fn felt_to_u64(felt: Felt) -> Result<u64, StarknetError> {
+    u64::try_from(felt).map_err(|_| StarknetError::InvalidResponse(format!("{:#x} does not fit a u64", felt)))
+}
+
+/// Joins the low and high 128 bit limbs of a u256 into big-endian bytes
+fn u256_to_bytes(low: Felt, high: Felt) -> FixedBytes<32> {
+    let mut bytes = [0u8; 32];
+    bytes[..16].copy_from_slice(&high.to_bytes_be()[16..]);
+    bytes[16..].copy_from_slice(&low.to_bytes_be()[16..]);
+    FixedBytes::from(bytes)
+}
+
+/// Possible errors that can occur while reading the Bankai contract
+#[derive(Debug, Error)]
+pub enum StarknetError {
+    /// A required configuration value is missing
+    #[error("Configuration error: {0}")]
+    Config(&'static str),
+    /// A configured value or argument is malformed
+    #[error("Invalid value: {0}")]
+    InvalidValue(String),
+    /// JSON-RPC error
+    #[error("Starknet RPC error: {0}")]
+    Provider(#[from] ProviderError),
+    /// The contract returned an unexpected value
+    #[error("Invalid contract response: {0}")]
+    InvalidResponse(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses the `result` of a `starknet_call` response. The values below are constructed to
+    /// match the contract ABI, they are not recorded from a deployed contract.
+    fn call_result(response: serde_json::Value) -> Vec<Felt> {
+        serde_json::from_value(response["result"].clone()).unwrap()
+    }
+
+    fn epoch_response() -> serde_json::Value {
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": [
+                "0x2122232425262728292a2b2c2d2e2f30",
+                "0x1112131415161718191a1b1c1d1e1f20",
+                "0x4142434445464748494a4b4c4d4e4f50",
+                "0x3132333435363738393a3b3c3d3e3f40",
+                "0x1fe",
+                "0x6162636465666768696a6b6c6d6e6f70",
+                "0x5152535455565758595a5b5c5d5e5f60",
+                "0x1561f0b"
+            ]
+        })
+    }
+
+    fn bytes(first: u8) -> FixedBytes<32> {
+        let bytes: [u8; 32] = std::array::from_fn(|i| first + i as u8);
+        FixedBytes::from(bytes)
+    }
+
+    #[test]
+    fn decodes_epoch_with_high_limb_first_in_bytes() {
+        let epoch = OnChainEpoch::from_felts(&call_result(epoch_response())).unwrap();
+
+        assert_eq!(
+            epoch,
+            OnChainEpoch {
+                header_root: bytes(0x11),
+                beacon_state_root: bytes(0x31),
+                n_signers: 510,
+                execution_header_root: bytes(0x51),
+                execution_header_height: 22_421_259,
+            }
+        );
+        assert!(!epoch.is_empty());
+    }
+
+    #[test]
+    fn decodes_missing_epoch_as_empty() {
+        let epoch = OnChainEpoch::from_felts(&[Felt::ZERO; OnChainEpoch::N_FELTS]).unwrap();
+        assert!(epoch.is_empty());
+    }
+
+    #[test]
+    fn rejects_epoch_of_wrong_length() {
+        let mut result = call_result(epoch_response());
+        result.pop();
+
+        let decoded = OnChainEpoch::from_felts(&result);
+        assert!(
+            matches!(&decoded, Err(StarknetError::InvalidResponse(message)) if message == "expected 8 felts, got 7"),
+            "{:?}",
+            decoded
+        );
+    }
+
+    #[test]
+    fn rejects_counter_not_fitting_u64() {
+        let mut result = call_result(epoch_response());
+        result[4] = Felt::from(u128::from(u64::MAX) + 1);
+
+        let decoded = OnChainEpoch::from_felts(&result);
+        assert!(
+            matches!(&decoded, Err(StarknetError::InvalidResponse(message)) if message.contains("does not fit a u64")),
+            "{:?}",
+            decoded
+        );
+    }
+
+    #[test]
+    fn decodes_committee_hash() {
+        let result = call_result(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": ["0x0102030405060708090a0b0c0d0e0f10", "0x0"]
+        }));
+
+        let mut expected = [0u8; 32];
+        expected[16..].copy_from_slice(&bytes(1)[..16]);
+        assert_eq!(committee_hash_from_felts(&result).unwrap(), FixedBytes::from(expected));
+        assert!(committee_hash_from_felts(&result[..1]).is_err());
+    }
+}
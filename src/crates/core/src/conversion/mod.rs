@@ -163,16 +163,23 @@ impl From<EpochUpdate> for EpochUpdateCairo {
 
 impl From<G1Point> for G1PointCairo {
     fn from(val: G1Point) -> Self {
-        let json = serde_json::to_string(&val).unwrap();
-        let parsed: G1PointCairo = serde_json::from_str(&json).unwrap();
-        parsed
+        let uncompressed = val.0.to_uncompressed();
+        G1PointCairo::new(
+            UInt384(BigUint::from_bytes_be(&uncompressed[0..48])),
+            UInt384(BigUint::from_bytes_be(&uncompressed[48..96])),
+        )
     }
 }
 
 impl From<G2Point> for G2PointCairo {
     fn from(val: G2Point) -> Self {
-        let json = serde_json::to_string(&val).unwrap();
-        let parsed: G2PointCairo = serde_json::from_str(&json).unwrap();
-        parsed
+        // The uncompressed encoding orders each Fp2 coordinate as (c1, c0)
+        let uncompressed = val.0.to_uncompressed();
+        G2PointCairo::new(
+            UInt384(BigUint::from_bytes_be(&uncompressed[48..96])),
+            UInt384(BigUint::from_bytes_be(&uncompressed[0..48])),
+            UInt384(BigUint::from_bytes_be(&uncompressed[144..192])),
+            UInt384(BigUint::from_bytes_be(&uncompressed[96..144])),
+        )
     }
-}
\ No newline at end of file
+}
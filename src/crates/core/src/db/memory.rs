@@ -0,0 +1,292 @@
+//! In-memory `Store` for embedding Bankai without a database.
+//!
+//! State lives for the lifetime of the `MemoryStore` only. Epoch updates keep the uniqueness
+//! of slot numbers and proofs are deduplicated by content hash, like in the SQL `Database`.
+
+use std::sync::{Mutex, MutexGuard};
+
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::{fetcher::recursive_epoch_input::RecursiveEpochOutput, utils::config::Network};
+
+use super::{proof_store::content_hash, store::Store, EpochUpdate, Proof, Status, SyncCommittee};
+
+/// `Store` keeping epoch updates and proofs in memory
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    state: Mutex<MemoryState>,
+}
+
+#[derive(Debug, Default)]
+struct MemoryState {
+    epoch_updates: Vec<EpochUpdate>,
+    proofs: Vec<Proof>,
+    sync_committees: Vec<SyncCommittee>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> MutexGuard<'_, MemoryState> {
+        // The state is never left half-updated, so a poisoned lock is still usable
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl MemoryState {
+    fn epoch_update_mut(&mut self, uuid: &str) -> Result<&mut EpochUpdate, MemoryStoreError> {
+        self.epoch_updates
+            .iter_mut()
+            .find(|update| update.uuid == uuid)
+            .ok_or_else(|| MemoryStoreError::NotFound(format!("epoch update {}", uuid)))
+    }
+}
+
+impl Store for MemoryStore {
+    type Error = MemoryStoreError;
+
+    async fn get_latest_epoch_update(&self) -> Result<Option<EpochUpdate>, MemoryStoreError> {
+        Ok(self
+            .state()
+            .epoch_updates
+            .iter()
+            .filter(|update| update.status != Status::Error.as_str())
+            .max_by_key(|update| update.slot_number)
+            .cloned())
+    }
+
+    async fn get_epoch_update_by_uuid(&self, uuid: &str) -> Result<Option<EpochUpdate>, MemoryStoreError> {
+        Ok(self
+            .state()
+            .epoch_updates
+            .iter()
+            .find(|update| update.uuid == uuid)
+            .cloned())
+    }
+
+    async fn create_epoch_update(
+        &self,
+        epoch_number: u64,
+        slot_number: u64,
+        outputs: RecursiveEpochOutput,
+    ) -> Result<String, MemoryStoreError> {
+        let mut state = self.state();
+        if state
+            .epoch_updates
+            .iter()
+            .any(|update| update.slot_number == slot_number as i64)
+        {
+            return Err(MemoryStoreError::DuplicateSlot(slot_number));
+        }
+
+        let uuid = Uuid::new_v4().to_string();
+        state.epoch_updates.push(EpochUpdate {
+            uuid: uuid.clone(),
+            epoch_number: epoch_number as i64,
+            slot_number: slot_number as i64,
+            outputs: Some(outputs),
+            atlantic_id: None,
+            proof_id: None,
+            status: Status::Fetching.as_str().to_string(),
+            error_reason: None,
+            wrap_atlantic_id: None,
+            wrap_status: None,
+            wrap_error: None,
+            fact_hash: None,
+            atlantic_status: None,
+            atlantic_step: None,
+            atlantic_error: None,
+            atlantic_phases: Vec::new(),
+            job_params: None,
+        });
+
+        Ok(uuid)
+    }
+
+    async fn update_status(&self, uuid: &str, status: Status) -> Result<(), MemoryStoreError> {
+        self.state().epoch_update_mut(uuid)?.status = status.as_str().to_string();
+        Ok(())
+    }
+
+    async fn update_error(&self, uuid: &str, error_reason: &str) -> Result<(), MemoryStoreError> {
+        let mut state = self.state();
+        let update = state.epoch_update_mut(uuid)?;
+        update.status = Status::Error.as_str().to_string();
+        update.error_reason = Some(error_reason.to_string());
+        Ok(())
+    }
+
+    async fn add_proof(&self, proof_json: &str) -> Result<i64, MemoryStoreError> {
+        let hash = content_hash(proof_json.as_bytes());
+        let mut state = self.state();
+        if let Some(proof) = state.proofs.iter().find(|proof| proof.hash == hash) {
+            return Ok(proof.id);
+        }
+
+        let id = state.proofs.len() as i64 + 1;
+        state.proofs.push(Proof {
+            id,
+            hash,
+            pinned: false,
+            proof: proof_json.to_string(),
+        });
+
+        Ok(id)
+    }
+
+    async fn update_proof_id(&self, uuid: &str, proof_id: i64) -> Result<(), MemoryStoreError> {
+        self.state().epoch_update_mut(uuid)?.proof_id = Some(proof_id);
+        Ok(())
+    }
+
+    async fn get_proof(&self, proof_id: i64) -> Result<Option<Proof>, MemoryStoreError> {
+        Ok(self
+            .state()
+            .proofs
+            .iter()
+            .find(|proof| proof.id == proof_id)
+            .cloned())
+    }
+
+    async fn get_sync_committee(
+        &self,
+        network: Network,
+        period: u64,
+    ) -> Result<Option<SyncCommittee>, MemoryStoreError> {
+        Ok(self
+            .state()
+            .sync_committees
+            .iter()
+            .find(|committee| committee.network == network && committee.period == period)
+            .cloned())
+    }
+
+    async fn store_sync_committee(&self, committee: &SyncCommittee) -> Result<(), MemoryStoreError> {
+        let mut state = self.state();
+        match state
+            .sync_committees
+            .iter_mut()
+            .find(|cached| cached.network == committee.network && cached.period == committee.period)
+        {
+            Some(cached) if !cached.validated => *cached = committee.clone(),
+            Some(_) => {}
+            None => state.sync_committees.push(committee.clone()),
+        }
+        Ok(())
+    }
+
+    async fn delete_sync_committee(&self, network: Network, period: u64) -> Result<(), MemoryStoreError> {
+        self.state()
+            .sync_committees
+            .retain(|committee| committee.network != network || committee.period != period);
+        Ok(())
+    }
+}
+
+/// Possible errors that can occur in the in-memory store
+#[derive(Debug, Error)]
+pub enum MemoryStoreError {
+    /// The requested record does not exist
+    #[error("Not found: {0}")]
+    NotFound(String),
+    /// An epoch update for the slot already exists
+    #[error("Epoch update for slot {0} already exists")]
+    DuplicateSlot(u64),
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::FixedBytes;
+
+    use super::*;
+
+    fn outputs(slot: u64) -> RecursiveEpochOutput {
+        RecursiveEpochOutput {
+            beacon_header_root: FixedBytes::repeat_byte(0x11),
+            beacon_state_root: FixedBytes::repeat_byte(0x22),
+            beacon_height: slot,
+            n_signers: 510,
+            execution_header_root: FixedBytes::repeat_byte(0x33),
+            execution_header_height: 22_000_000,
+            current_committee_hash: FixedBytes::repeat_byte(0x44),
+            next_committee_hash: FixedBytes::repeat_byte(0x55),
+        }
+    }
+
+    fn committee(period: u64, hash: u8, validated: bool) -> SyncCommittee {
+        SyncCommittee {
+            network: Network::Sepolia,
+            period,
+            pubkeys: vec![FixedBytes::repeat_byte(0xaa)],
+            aggregate_pubkey: FixedBytes::repeat_byte(0xaa),
+            committee_keys_root: FixedBytes::repeat_byte(0xbb),
+            committee_hash: FixedBytes::repeat_byte(hash),
+            validated,
+        }
+    }
+
+    #[tokio::test]
+    async fn latest_epoch_update_skips_errored_updates() {
+        let store = MemoryStore::new();
+        assert!(store.get_latest_epoch_update().await.unwrap().is_none());
+
+        let done = store.create_epoch_update(256, 8223, outputs(8223)).await.unwrap();
+        let errored = store.create_epoch_update(257, 8255, outputs(8255)).await.unwrap();
+        store.update_status(&done, Status::Done).await.unwrap();
+        store.update_error(&errored, "trace generation failed").await.unwrap();
+
+        let latest = store.get_latest_epoch_update().await.unwrap().unwrap();
+        assert_eq!(latest.uuid, done);
+        assert_eq!(latest.status, Status::Done.as_str());
+        let errored = store.get_epoch_update_by_uuid(&errored).await.unwrap().unwrap();
+        assert_eq!(errored.status, Status::Error.as_str());
+        assert_eq!(errored.error_reason.as_deref(), Some("trace generation failed"));
+    }
+
+    #[tokio::test]
+    async fn rejects_duplicate_slot_and_unknown_uuid() {
+        let store = MemoryStore::new();
+        store.create_epoch_update(256, 8223, outputs(8223)).await.unwrap();
+
+        let duplicate = store.create_epoch_update(256, 8223, outputs(8223)).await;
+        assert!(matches!(duplicate, Err(MemoryStoreError::DuplicateSlot(8223))), "{:?}", duplicate);
+        let unknown = store.update_status("unknown", Status::Done).await;
+        assert!(matches!(unknown, Err(MemoryStoreError::NotFound(_))), "{:?}", unknown);
+    }
+
+    #[tokio::test]
+    async fn deduplicates_proofs_by_content() {
+        let store = MemoryStore::new();
+        let uuid = store.create_epoch_update(256, 8223, outputs(8223)).await.unwrap();
+
+        let id = store.add_proof(r#"{"proof":"0x1234"}"#).await.unwrap();
+        assert_eq!(store.add_proof(r#"{"proof":"0x1234"}"#).await.unwrap(), id);
+        let other = store.add_proof(r#"{"proof":"0x5678"}"#).await.unwrap();
+        assert_ne!(other, id);
+
+        store.update_proof_id(&uuid, id).await.unwrap();
+        let update = store.get_epoch_update_by_uuid(&uuid).await.unwrap().unwrap();
+        assert_eq!(update.proof_id, Some(id));
+        let proof = store.get_proof(id).await.unwrap().unwrap();
+        assert_eq!(proof.proof, r#"{"proof":"0x1234"}"#);
+        assert!(store.get_proof(other + 1).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn replaces_only_unvalidated_committees() {
+        let store = MemoryStore::new();
+        store.store_sync_committee(&committee(2, 0x01, false)).await.unwrap();
+        store.store_sync_committee(&committee(2, 0x02, true)).await.unwrap();
+        store.store_sync_committee(&committee(2, 0x03, false)).await.unwrap();
+
+        let cached = store.get_sync_committee(Network::Sepolia, 2).await.unwrap();
+        assert_eq!(cached, Some(committee(2, 0x02, true)));
+        assert!(store.get_sync_committee(Network::Mainnet, 2).await.unwrap().is_none());
+
+        store.delete_sync_committee(Network::Sepolia, 2).await.unwrap();
+        assert!(store.get_sync_committee(Network::Sepolia, 2).await.unwrap().is_none());
+    }
+}
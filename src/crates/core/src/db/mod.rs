@@ -1,10 +1,23 @@
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, Pool, Sqlite, sqlite::SqliteConnectOptions};
+use sqlx::{any::{install_default_drivers, AnyPoolOptions}, AnyPool, FromRow};
 use uuid::Uuid;
-use std::str::FromStr;
-use crate::fetcher::recursive_epoch_input::RecursiveEpochOutput;
+use std::{path::PathBuf, str::FromStr};
+use thiserror::Error;
+use tracing::info;
+use alloy_primitives::FixedBytes;
+use crate::clients::atlantic::{AtlanticPhase, AtlanticQuery, JobParams};
+use crate::fetcher::recursive_epoch_input::{RecursiveEpochInputs, RecursiveEpochOutput};
+use crate::utils::config::Network;
+use crate::utils::compression::{compress_json, decompress_json};
+use crate::metrics::metrics;
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
+pub mod memory;
+pub mod proof_store;
+pub mod store;
+
+use proof_store::{ProofStore, ProofStoreError};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "TEXT", rename_all = "snake_case")]
 pub enum Status {
     Fetching,
@@ -14,7 +27,92 @@ pub enum Status {
     Error,
 }
 
-#[derive(Debug)]
+impl Status {
+    pub const ALL: [Status; 5] = [
+        Status::Fetching,
+        Status::TraceGen,
+        Status::Proving,
+        Status::Done,
+        Status::Error,
+    ];
+
+    /// Returns the value stored in the `status` column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Status::Fetching => "fetching",
+            Status::TraceGen => "trace_gen",
+            Status::Proving => "proving",
+            Status::Done => "done",
+            Status::Error => "error",
+        }
+    }
+
+    /// Returns true while the epoch update is still being worked on
+    pub fn is_in_flight(&self) -> bool {
+        matches!(self, Status::Fetching | Status::TraceGen | Status::Proving)
+    }
+}
+
+/// Progress of the Starknet verification of a proven epoch update
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+pub enum WrapStatus {
+    /// The wrapped proof was submitted to Atlantic
+    Submitted,
+    /// The proof was verified on Starknet and its fact registered
+    Verified,
+    /// Wrapping or verification failed
+    Error,
+}
+
+impl WrapStatus {
+    pub const ALL: [WrapStatus; 3] = [WrapStatus::Submitted, WrapStatus::Verified, WrapStatus::Error];
+
+    /// Returns the value stored in the `wrap_status` column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WrapStatus::Submitted => "submitted",
+            WrapStatus::Verified => "verified",
+            WrapStatus::Error => "error",
+        }
+    }
+}
+
+impl std::fmt::Display for WrapStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for WrapStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        WrapStatus::ALL
+            .into_iter()
+            .find(|status| status.as_str() == s)
+            .ok_or_else(|| format!("Unknown wrap status: {}", s))
+    }
+}
+
+impl std::fmt::Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Status {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Status::ALL
+            .into_iter()
+            .find(|status| status.as_str() == s)
+            .ok_or_else(|| format!("Unknown status: {}", s))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct EpochUpdate {
     pub uuid: String,
     pub epoch_number: i64,
@@ -24,6 +122,20 @@ pub struct EpochUpdate {
     pub proof_id: Option<i64>,
     pub status: String,
     pub error_reason: Option<String>,
+    /// Atlantic query ID of the wrapped proof submitted for Starknet verification
+    pub wrap_atlantic_id: Option<String>,
+    pub wrap_status: Option<String>,
+    pub wrap_error: Option<String>,
+    /// Fact hash registered on Starknet once the wrapped proof is verified
+    pub fact_hash: Option<String>,
+    /// Last observed status, step and error of the Atlantic query
+    pub atlantic_status: Option<String>,
+    pub atlantic_step: Option<String>,
+    pub atlantic_error: Option<String>,
+    /// Phases of the Atlantic query with their timing
+    pub atlantic_phases: Vec<AtlanticPhase>,
+    /// Job size and layout the PIE was last submitted with
+    pub job_params: Option<JobParams>,
 }
 
 #[derive(Debug, FromRow)]
@@ -36,6 +148,15 @@ struct EpochUpdateRow {
     pub proof_id: Option<i64>,
     pub status: String,
     pub error_reason: Option<String>,
+    pub wrap_atlantic_id: Option<String>,
+    pub wrap_status: Option<String>,
+    pub wrap_error: Option<String>,
+    pub fact_hash: Option<String>,
+    pub atlantic_status: Option<String>,
+    pub atlantic_step: Option<String>,
+    pub atlantic_error: Option<String>,
+    pub atlantic_phases: Option<String>,
+    pub job_params: Option<String>,
 }
 
 impl From<EpochUpdateRow> for EpochUpdate {
@@ -43,6 +164,13 @@ impl From<EpochUpdateRow> for EpochUpdate {
         let outputs = row.outputs
             .as_ref()
             .and_then(|json| serde_json::from_str(json).ok());
+        let atlantic_phases = row.atlantic_phases
+            .as_ref()
+            .and_then(|json| serde_json::from_str(json).ok())
+            .unwrap_or_default();
+        let job_params = row.job_params
+            .as_ref()
+            .and_then(|json| serde_json::from_str(json).ok());
         
         EpochUpdate {
             uuid: row.uuid,
@@ -53,29 +181,253 @@ impl From<EpochUpdateRow> for EpochUpdate {
             proof_id: row.proof_id,
             status: row.status,
             error_reason: row.error_reason,
+            wrap_atlantic_id: row.wrap_atlantic_id,
+            wrap_status: row.wrap_status,
+            wrap_error: row.wrap_error,
+            fact_hash: row.fact_hash,
+            atlantic_status: row.atlantic_status,
+            atlantic_step: row.atlantic_step,
+            atlantic_error: row.atlantic_error,
+            atlantic_phases,
+            job_params,
         }
     }
 }
 
-#[derive(Debug, FromRow)]
+/// Filters applied when listing epoch updates
+#[derive(Debug, Clone, Default)]
+pub struct EpochUpdateFilter {
+    /// Only return rows with this status
+    pub status: Option<Status>,
+    /// Only return rows with an epoch number greater or equal to this one
+    pub from_epoch: Option<u64>,
+    /// Only return rows with an epoch number less or equal to this one
+    pub to_epoch: Option<u64>,
+    /// Only return rows whose error reason contains this string
+    pub error_reason: Option<String>,
+}
+
+impl EpochUpdateFilter {
+    /// Returns the WHERE clause for this filter and the values bound to its placeholders
+    fn where_clause(&self) -> (String, Vec<FilterValue>) {
+        let mut clause = String::from(" WHERE 1 = 1");
+        let mut values = Vec::new();
+        if let Some(status) = &self.status {
+            values.push(FilterValue::Text(status.as_str().to_string()));
+            clause.push_str(&format!(" AND status = ${}", values.len()));
+        }
+        if let Some(from_epoch) = self.from_epoch {
+            values.push(FilterValue::Int(from_epoch as i64));
+            clause.push_str(&format!(" AND epoch_number >= ${}", values.len()));
+        }
+        if let Some(to_epoch) = self.to_epoch {
+            values.push(FilterValue::Int(to_epoch as i64));
+            clause.push_str(&format!(" AND epoch_number <= ${}", values.len()));
+        }
+        if let Some(error_reason) = &self.error_reason {
+            values.push(FilterValue::Text(format!("%{}%", error_reason)));
+            clause.push_str(&format!(" AND error_reason LIKE ${}", values.len()));
+        }
+        (clause, values)
+    }
+}
+
+enum FilterValue {
+    Text(String),
+    Int(i64),
+}
+
+/// A single proving attempt of an epoch update
+#[derive(Debug, Serialize, FromRow)]
+pub struct Attempt {
+    pub attempt_number: i64,
+    pub error: Option<String>,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+}
+
+#[derive(Debug, Clone)]
 pub struct Proof {
     pub id: i64,
+    pub hash: String,
+    pub pinned: bool,
     pub proof: String,
 }
 
+#[derive(Debug, FromRow)]
+struct ProofRow {
+    id: i64,
+    hash: Option<String>,
+    pinned: i64,
+}
+
+/// Sync committee of one period, cached to avoid looking up its validators for every epoch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncCommittee {
+    pub network: Network,
+    pub period: u64,
+    /// Compressed public keys in committee order
+    pub pubkeys: Vec<FixedBytes<48>>,
+    pub aggregate_pubkey: FixedBytes<48>,
+    pub committee_keys_root: FixedBytes<32>,
+    pub committee_hash: FixedBytes<32>,
+    /// Whether the committee was checked against a proven sync committee update
+    pub validated: bool,
+}
+
+#[derive(Debug, FromRow)]
+struct SyncCommitteeRow {
+    network: String,
+    period: i64,
+    pubkeys: Vec<u8>,
+    aggregate_pubkey: String,
+    committee_keys_root: String,
+    committee_hash: String,
+    validated: i64,
+}
+
+impl TryFrom<SyncCommitteeRow> for SyncCommittee {
+    type Error = sqlx::Error;
+
+    fn try_from(row: SyncCommitteeRow) -> Result<Self, Self::Error> {
+        if row.pubkeys.len() % 48 != 0 {
+            return Err(sqlx::Error::Decode(
+                format!("sync committee pubkeys have invalid length {}", row.pubkeys.len()).into(),
+            ));
+        }
+
+        Ok(SyncCommittee {
+            network: row.network.parse().map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+            period: row.period as u64,
+            pubkeys: row.pubkeys.chunks_exact(48).map(FixedBytes::from_slice).collect(),
+            aggregate_pubkey: parse_fixed_bytes(&row.aggregate_pubkey)?,
+            committee_keys_root: parse_fixed_bytes(&row.committee_keys_root)?,
+            committee_hash: parse_fixed_bytes(&row.committee_hash)?,
+            validated: row.validated != 0,
+        })
+    }
+}
+
+fn parse_fixed_bytes<const N: usize>(value: &str) -> Result<FixedBytes<N>, sqlx::Error> {
+    value.parse().map_err(|e| sqlx::Error::Decode(Box::new(e)))
+}
+
+/// SQL backend of the database, selected by the scheme of the database URL
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Sqlite,
+    Postgres,
+}
+
+impl Backend {
+    /// Returns the backend for a database URL, e.g. `sqlite:./bankai.db` or `postgres://...`
+    pub fn from_url(url: &str) -> Result<Self, DatabaseError> {
+        match url.split(':').next() {
+            Some("sqlite") => Ok(Backend::Sqlite),
+            Some("postgres") | Some("postgresql") => Ok(Backend::Postgres),
+            _ => Err(DatabaseError::UnsupportedUrl(url.to_string())),
+        }
+    }
+}
+
+/// Columns of `epoch_updates` read into an `EpochUpdateRow`
+const EPOCH_UPDATE_COLUMNS: &str = "uuid, epoch_number, slot_number, outputs, atlantic_id, proof_id, status, error_reason, \
+     wrap_atlantic_id, wrap_status, wrap_error, fact_hash, \
+     atlantic_status, atlantic_step, atlantic_error, atlantic_phases, job_params";
+
 #[derive(Debug)]
 pub struct Database {
-    pool: Pool<Sqlite>,
+    pool: AnyPool,
+    proof_store: ProofStore,
 }
 
 impl Database {
-    pub async fn new(url: &str) -> Result<Self, sqlx::Error> {
-        let options = SqliteConnectOptions::from_str(url)?
-            .create_if_missing(true);
-        
-        let pool = Pool::connect_with(options).await?;
-        sqlx::migrate!("../../../migrations").run(&pool).await?;
-        Ok(Self { pool })
+    /// Connects to the database and runs all migrations.
+    ///
+    /// The backend is selected by the URL scheme: `sqlite:` or `postgres://`. Proofs are stored
+    /// as files below `proof_store_dir` if it is set, otherwise as BLOBs in the database itself.
+    pub async fn new(url: &str, proof_store_dir: Option<&str>) -> Result<Self, DatabaseError> {
+        install_default_drivers();
+
+        let backend = Backend::from_url(url)?;
+        let pool = match backend {
+            Backend::Sqlite => {
+                // Create the database file if it doesn't exist yet
+                let url = if url.contains("mode=") {
+                    url.to_string()
+                } else if url.contains('?') {
+                    format!("{}&mode=rwc", url)
+                } else {
+                    format!("{}?mode=rwc", url)
+                };
+                AnyPoolOptions::new().connect(&url).await?
+            }
+            Backend::Postgres => AnyPoolOptions::new().connect(url).await?,
+        };
+
+        match backend {
+            Backend::Sqlite => sqlx::migrate!("../../../migrations/sqlite").run(&pool).await,
+            Backend::Postgres => sqlx::migrate!("../../../migrations/postgres").run(&pool).await,
+        }
+        .map_err(sqlx::Error::from)?;
+        info!("🗄️  Connected to {:?} database", backend);
+
+        let proof_store = match proof_store_dir {
+            Some(dir) => ProofStore::Filesystem(PathBuf::from(dir)),
+            None => ProofStore::Database(pool.clone()),
+        };
+
+        let db = Self { pool, proof_store };
+        db.migrate_legacy_proofs().await?;
+        Ok(db)
+    }
+
+    /// Moves proofs stored inline in `proofs.proof` into the proof store
+    async fn migrate_legacy_proofs(&self) -> Result<(), DatabaseError> {
+        let legacy: Vec<(i64, String)> =
+            sqlx::query_as("SELECT id, proof FROM proofs WHERE hash IS NULL")
+                .fetch_all(&self.pool)
+                .await?;
+
+        if !legacy.is_empty() {
+            info!("📦 Moving {} legacy proofs into the proof store...", legacy.len());
+        }
+
+        for (id, proof) in legacy {
+            let hash = self.proof_store.put(proof.as_bytes()).await?;
+
+            let existing: Option<i64> =
+                sqlx::query_scalar("SELECT id FROM proofs WHERE hash = $1")
+                    .bind(&hash)
+                    .fetch_optional(&self.pool)
+                    .await?;
+
+            let mut tx = self.pool.begin().await?;
+            match existing {
+                // Duplicate proof, point its epoch updates at the existing row
+                Some(existing_id) => {
+                    sqlx::query("UPDATE epoch_updates SET proof_id = $1 WHERE proof_id = $2")
+                        .bind(existing_id)
+                        .bind(id)
+                        .execute(&mut *tx)
+                        .await?;
+                    sqlx::query("DELETE FROM proofs WHERE id = $1")
+                        .bind(id)
+                        .execute(&mut *tx)
+                        .await?;
+                }
+                None => {
+                    sqlx::query("UPDATE proofs SET hash = $1, proof = '' WHERE id = $2")
+                        .bind(&hash)
+                        .bind(id)
+                        .execute(&mut *tx)
+                        .await?;
+                }
+            }
+            tx.commit().await?;
+        }
+
+        Ok(())
     }
 
     pub async fn create_epoch_update(
@@ -89,28 +441,29 @@ impl Database {
         let epoch_number_i64 = epoch_number as i64;
         let slot_number_i64 = slot_number as i64;
         
-        sqlx::query!(
-            "INSERT INTO epoch_updates (uuid, epoch_number, slot_number, outputs, status) VALUES (?, ?, ?, ?, ?)",
-            uuid,
-            epoch_number_i64,
-            slot_number_i64,
-            outputs_json,
-            "fetching"
+        sqlx::query(
+            "INSERT INTO epoch_updates (uuid, epoch_number, slot_number, outputs, status) VALUES ($1, $2, $3, $4, $5)"
         )
+        .bind(uuid)
+        .bind(epoch_number_i64)
+        .bind(slot_number_i64)
+        .bind(outputs_json)
+        .bind("fetching")
         .execute(&self.pool)
         .await?;
 
+        metrics().set_status(&Status::Fetching);
         Ok(uuid)
     }
 
     pub async fn update_outputs(&self, uuid: &str, outputs: &RecursiveEpochOutput) -> Result<(), sqlx::Error> {
         let outputs_json = serde_json::to_string(outputs).unwrap();
         
-        sqlx::query!(
-            "UPDATE epoch_updates SET outputs = ? WHERE uuid = ?",
-            outputs_json,
-            uuid
+        sqlx::query(
+            "UPDATE epoch_updates SET outputs = $1 WHERE uuid = $2"
         )
+        .bind(outputs_json)
+        .bind(uuid)
         .execute(&self.pool)
         .await?;
 
@@ -118,34 +471,39 @@ impl Database {
     }
 
     pub async fn add_atlantic_id(&self, uuid: &str, atlantic_id: &str) -> Result<(), sqlx::Error> {
-        sqlx::query!(
-            "UPDATE epoch_updates SET atlantic_id = ? WHERE uuid = ?",
-            atlantic_id,
-            uuid
+        sqlx::query(
+            "UPDATE epoch_updates SET atlantic_id = $1 WHERE uuid = $2"
         )
+        .bind(atlantic_id)
+        .bind(uuid)
         .execute(&self.pool)
         .await?;
 
         Ok(())
     }
 
-    pub async fn add_proof(&self, proof_json: &str) -> Result<i64, sqlx::Error> {
-        let result = sqlx::query!(
-            "INSERT INTO proofs (proof) VALUES (?) RETURNING id",
-            proof_json
+    /// Stores a proof in the proof store and returns its ID. Identical proofs share one ID.
+    pub async fn add_proof(&self, proof_json: &str) -> Result<i64, DatabaseError> {
+        let hash = self.proof_store.put(proof_json.as_bytes()).await?;
+
+        let id: i64 = sqlx::query_scalar(
+            "INSERT INTO proofs (proof, hash) VALUES ('', $1)
+             ON CONFLICT (hash) DO UPDATE SET hash = excluded.hash
+             RETURNING id"
         )
+        .bind(hash)
         .fetch_one(&self.pool)
         .await?;
 
-        Ok(result.id)
+        Ok(id)
     }
 
     pub async fn update_proof_id(&self, uuid: &str, proof_id: i64) -> Result<(), sqlx::Error> {
-        sqlx::query!(
-            "UPDATE epoch_updates SET proof_id = ? WHERE uuid = ?",
-            proof_id,
-            uuid
+        sqlx::query(
+            "UPDATE epoch_updates SET proof_id = $1 WHERE uuid = $2"
         )
+        .bind(proof_id)
+        .bind(uuid)
         .execute(&self.pool)
         .await?;
 
@@ -153,73 +511,505 @@ impl Database {
     }
 
     pub async fn update_status(&self, uuid: &str, status: Status) -> Result<(), sqlx::Error> {
-        let status_str = match status {
-            Status::Fetching => "fetching",
-            Status::TraceGen => "trace_gen",
-            Status::Proving => "proving",
-            Status::Done => "done",
-            Status::Error => "error",
-        };
+        let status_str = status.as_str();
 
-        sqlx::query!(
-            "UPDATE epoch_updates SET status = ? WHERE uuid = ?",
-            status_str,
-            uuid
+        sqlx::query(
+            "UPDATE epoch_updates SET status = $1 WHERE uuid = $2"
         )
+        .bind(status_str)
+        .bind(uuid)
         .execute(&self.pool)
         .await?;
 
+        metrics().set_status(&status);
         Ok(())
     }
 
     pub async fn update_error(&self, uuid: &str, error_reason: &str) -> Result<(), sqlx::Error> {
-        sqlx::query!(
-            "UPDATE epoch_updates SET status = 'error', error_reason = ? WHERE uuid = ?",
-            error_reason,
-            uuid
+        sqlx::query(
+            "UPDATE epoch_updates SET status = 'error', error_reason = $1 WHERE uuid = $2"
+        )
+        .bind(error_reason)
+        .bind(uuid)
+        .execute(&self.pool)
+        .await?;
+
+        self.finish_attempt(uuid, Some(error_reason)).await?;
+
+        metrics().set_status(&Status::Error);
+        Ok(())
+    }
+
+    /// Records the start of a new proving attempt and returns its attempt number
+    pub async fn start_attempt(&self, uuid: &str) -> Result<i64, sqlx::Error> {
+        let attempt_number: i64 = sqlx::query_scalar(
+            "INSERT INTO attempts (epoch_update_uuid, attempt_number)
+             VALUES ($1, (SELECT COALESCE(MAX(attempt_number), 0) + 1 FROM attempts WHERE epoch_update_uuid = $2))
+             RETURNING attempt_number"
+        )
+        .bind(uuid)
+        .bind(uuid)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(attempt_number)
+    }
+
+    /// Closes the latest open attempt of an epoch update, recording `error` if it failed
+    pub async fn finish_attempt(&self, uuid: &str, error: Option<&str>) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE attempts SET error = $1, finished_at = CURRENT_TIMESTAMP
+             WHERE epoch_update_uuid = $2 AND finished_at IS NULL"
         )
+        .bind(error)
+        .bind(uuid)
         .execute(&self.pool)
         .await?;
 
         Ok(())
     }
 
+    /// Returns all proving attempts of an epoch update, oldest first
+    pub async fn get_attempts(&self, uuid: &str) -> Result<Vec<Attempt>, sqlx::Error> {
+        sqlx::query_as::<_, Attempt>(
+            "SELECT attempt_number, error, started_at, finished_at
+             FROM attempts
+             WHERE epoch_update_uuid = $1
+             ORDER BY attempt_number ASC"
+        )
+        .bind(uuid)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Resets an errored epoch update so it can be proven again under the same UUID
+    pub async fn reset_epoch_update(
+        &self,
+        uuid: &str,
+        outputs: &RecursiveEpochOutput,
+    ) -> Result<(), sqlx::Error> {
+        let outputs_json = serde_json::to_string(outputs).unwrap();
+
+        sqlx::query(
+            "UPDATE epoch_updates
+             SET status = 'fetching', error_reason = NULL, atlantic_id = NULL, proof_id = NULL, outputs = $1,
+                 wrap_atlantic_id = NULL, wrap_status = NULL, wrap_error = NULL, fact_hash = NULL,
+                 atlantic_status = NULL, atlantic_step = NULL, atlantic_error = NULL, atlantic_phases = NULL,
+                 job_params = NULL
+             WHERE uuid = $2"
+        )
+        .bind(outputs_json)
+        .bind(uuid)
+        .execute(&self.pool)
+        .await?;
+
+        metrics().set_status(&Status::Fetching);
+        Ok(())
+    }
+
+    /// Returns the latest errored epoch update for an epoch number
+    pub async fn get_errored_epoch_update_by_epoch(&self, epoch_number: u64) -> Result<Option<EpochUpdate>, sqlx::Error> {
+        let sql = format!(
+            "SELECT {} FROM epoch_updates
+             WHERE epoch_number = $1 AND status = 'error'
+             ORDER BY slot_number DESC
+             LIMIT 1",
+            EPOCH_UPDATE_COLUMNS
+        );
+        let row = sqlx::query_as::<_, EpochUpdateRow>(&sql)
+            .bind(epoch_number as i64)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.map(Into::into))
+    }
+
+    /// Returns the latest non-errored epoch update strictly before `slot_number`
+    pub async fn get_previous_epoch_update(&self, slot_number: u64) -> Result<Option<EpochUpdate>, sqlx::Error> {
+        let sql = format!(
+            "SELECT {} FROM epoch_updates
+             WHERE status != 'error' AND slot_number < $1
+             ORDER BY slot_number DESC
+             LIMIT 1",
+            EPOCH_UPDATE_COLUMNS
+        );
+        let row = sqlx::query_as::<_, EpochUpdateRow>(&sql)
+            .bind(slot_number as i64)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.map(Into::into))
+    }
+
     pub async fn get_latest_epoch_update(&self) -> Result<Option<EpochUpdate>, sqlx::Error> {
-        let row = sqlx::query_as::<_, EpochUpdateRow>(
-            "SELECT uuid, epoch_number, slot_number, outputs, atlantic_id, proof_id, status, error_reason 
-             FROM epoch_updates 
+        let sql = format!(
+            "SELECT {} FROM epoch_updates
              WHERE status != 'error'
-             ORDER BY slot_number DESC 
-             LIMIT 1"
+             ORDER BY slot_number DESC
+             LIMIT 1",
+            EPOCH_UPDATE_COLUMNS
+        );
+        let row = sqlx::query_as::<_, EpochUpdateRow>(&sql)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.map(Into::into))
+    }
+
+    pub async fn get_epoch_update_by_uuid(&self, uuid: &str) -> Result<Option<EpochUpdate>, sqlx::Error> {
+        let sql = format!(
+            "SELECT {} FROM epoch_updates
+             WHERE uuid = $1",
+            EPOCH_UPDATE_COLUMNS
+        );
+        let row = sqlx::query_as::<_, EpochUpdateRow>(&sql)
+            .bind(uuid)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.map(Into::into))
+    }
+
+    /// Stores the job size and layout an epoch update is submitted with
+    pub async fn update_job_params(&self, uuid: &str, params: &JobParams) -> Result<(), sqlx::Error> {
+        let params_json = serde_json::to_string(params).unwrap();
+
+        sqlx::query("UPDATE epoch_updates SET job_params = $1 WHERE uuid = $2")
+            .bind(params_json)
+            .bind(uuid)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Stores the last observed state of the Atlantic query of an epoch update
+    pub async fn update_atlantic_query(
+        &self,
+        uuid: &str,
+        query: &AtlanticQuery,
+        phases: &[AtlanticPhase],
+    ) -> Result<(), sqlx::Error> {
+        let phases_json = serde_json::to_string(phases).unwrap();
+
+        sqlx::query(
+            "UPDATE epoch_updates
+             SET atlantic_status = $1, atlantic_step = $2, atlantic_error = $3, atlantic_phases = $4
+             WHERE uuid = $5"
+        )
+        .bind(query.status.as_str())
+        .bind(&query.step)
+        .bind(&query.error_reason)
+        .bind(phases_json)
+        .bind(uuid)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Records the submission of a wrapped proof, clearing the result of earlier submissions
+    pub async fn start_wrap(&self, uuid: &str, wrap_atlantic_id: &str) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE epoch_updates
+             SET wrap_atlantic_id = $1, wrap_status = 'submitted', wrap_error = NULL, fact_hash = NULL
+             WHERE uuid = $2"
+        )
+        .bind(wrap_atlantic_id)
+        .bind(uuid)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Marks the wrapped proof of an epoch update as verified on Starknet
+    pub async fn finish_wrap(&self, uuid: &str, fact_hash: Option<&str>) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE epoch_updates SET wrap_status = 'verified', fact_hash = $1 WHERE uuid = $2"
+        )
+        .bind(fact_hash)
+        .bind(uuid)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Marks the wrapped proof of an epoch update as failed. The epoch update stays done.
+    pub async fn update_wrap_error(&self, uuid: &str, wrap_error: &str) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE epoch_updates SET wrap_status = 'error', wrap_error = $1 WHERE uuid = $2"
+        )
+        .bind(wrap_error)
+        .bind(uuid)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Loads a proof, verifying its content hash
+    pub async fn get_proof(&self, proof_id: i64) -> Result<Option<Proof>, DatabaseError> {
+        let row = sqlx::query_as::<_, ProofRow>(
+            "SELECT id, hash, pinned FROM proofs WHERE id = $1"
         )
+        .bind(proof_id)
         .fetch_optional(&self.pool)
         .await?;
 
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let hash = row.hash.ok_or_else(|| {
+            ProofStoreError::NotFound(format!("proof {} has no content hash", row.id))
+        })?;
+        let proof = self.proof_store.get(&hash).await?;
+
+        Ok(Some(Proof {
+            id: row.id,
+            hash,
+            pinned: row.pinned != 0,
+            proof: String::from_utf8(proof).map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+        }))
+    }
+
+    /// Pins or unpins a proof. Pinned proofs are never removed by `prune_proofs`.
+    pub async fn set_proof_pinned(&self, proof_id: i64, pinned: bool) -> Result<(), DatabaseError> {
+        let result = sqlx::query("UPDATE proofs SET pinned = $1 WHERE id = $2")
+            .bind(pinned as i64)
+            .bind(proof_id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(ProofStoreError::NotFound(format!("proof {}", proof_id)).into());
+        }
+        Ok(())
+    }
+
+    /// Returns the proofs that `prune_proofs` would remove.
+    ///
+    /// A proof is kept if it is pinned or belongs to the latest done epoch update, since the
+    /// next recursive update verifies it. Proofs whose wrap is submitted or failed are kept too,
+    /// since `wrap` submits them until they are verified on Starknet.
+    pub async fn get_prunable_proofs(&self) -> Result<Vec<i64>, DatabaseError> {
+        let ids: Vec<i64> = sqlx::query_scalar(
+            "SELECT id FROM proofs
+             WHERE pinned = 0
+             AND id NOT IN (
+                 SELECT proof_id FROM epoch_updates
+                 WHERE status = 'done' AND proof_id IS NOT NULL
+                 ORDER BY slot_number DESC
+                 LIMIT 1
+             )
+             AND id NOT IN (
+                 SELECT proof_id FROM epoch_updates
+                 WHERE proof_id IS NOT NULL AND wrap_status IS NOT NULL AND wrap_status != 'verified'
+             )
+             ORDER BY id ASC"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(ids)
+    }
+
+    /// Removes all proofs not needed for recursion and not pinned. Returns the removed proof IDs.
+    pub async fn prune_proofs(&self) -> Result<Vec<i64>, DatabaseError> {
+        let ids = self.get_prunable_proofs().await?;
+
+        for id in &ids {
+            let hash: Option<String> = sqlx::query_scalar("SELECT hash FROM proofs WHERE id = $1")
+                .bind(id)
+                .fetch_one(&self.pool)
+                .await?;
+
+            let mut tx = self.pool.begin().await?;
+            sqlx::query("UPDATE epoch_updates SET proof_id = NULL WHERE proof_id = $1")
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query("DELETE FROM proofs WHERE id = $1")
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+
+            if let Some(hash) = hash {
+                self.proof_store.delete(&hash).await?;
+            }
+        }
+
+        Ok(ids)
+    }
+
+    /// Returns the most recent epoch update that is still being fetched, traced or proven
+    pub async fn get_in_flight_epoch_update(&self) -> Result<Option<EpochUpdate>, sqlx::Error> {
+        let sql = format!(
+            "SELECT {} FROM epoch_updates
+             WHERE status IN ('fetching', 'trace_gen', 'proving')
+             ORDER BY slot_number DESC
+             LIMIT 1",
+            EPOCH_UPDATE_COLUMNS
+        );
+        let row = sqlx::query_as::<_, EpochUpdateRow>(&sql)
+            .fetch_optional(&self.pool)
+            .await?;
+
         Ok(row.map(Into::into))
     }
 
-    pub async fn get_epoch_update_by_uuid(&self, uuid: &str) -> Result<Option<EpochUpdate>, sqlx::Error> {
-        let row = sqlx::query_as::<_, EpochUpdateRow>(
-            "SELECT uuid, epoch_number, slot_number, outputs, atlantic_id, proof_id, status, error_reason 
-             FROM epoch_updates 
-             WHERE uuid = ?"
+    /// Lists epoch updates matching `filter`, newest first
+    pub async fn list_epoch_updates(
+        &self,
+        filter: &EpochUpdateFilter,
+        limit: u64,
+        offset: u64,
+    ) -> Result<Vec<EpochUpdate>, sqlx::Error> {
+        let (where_clause, values) = filter.where_clause();
+        let sql = format!(
+            "SELECT {} FROM epoch_updates{} ORDER BY slot_number DESC LIMIT ${} OFFSET ${}",
+            EPOCH_UPDATE_COLUMNS,
+            where_clause,
+            values.len() + 1,
+            values.len() + 2,
+        );
+
+        let mut query = sqlx::query_as::<_, EpochUpdateRow>(&sql);
+        for value in values {
+            query = match value {
+                FilterValue::Text(text) => query.bind(text),
+                FilterValue::Int(int) => query.bind(int),
+            };
+        }
+        let rows = query
+            .bind(limit as i64)
+            .bind(offset as i64)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Counts epoch updates matching `filter`
+    pub async fn count_epoch_updates(&self, filter: &EpochUpdateFilter) -> Result<u64, sqlx::Error> {
+        let (where_clause, values) = filter.where_clause();
+        let sql = format!("SELECT COUNT(*) FROM epoch_updates{}", where_clause);
+
+        let mut query = sqlx::query_scalar::<_, i64>(&sql);
+        for value in values {
+            query = match value {
+                FilterValue::Text(text) => query.bind(text),
+                FilterValue::Int(int) => query.bind(int),
+            };
+        }
+        let count = query.fetch_one(&self.pool).await?;
+        Ok(count as u64)
+    }
+
+    /// Stores the compressed proving inputs of an epoch update, replacing earlier ones
+    pub async fn store_inputs(&self, uuid: &str, inputs: &RecursiveEpochInputs) -> Result<(), sqlx::Error> {
+        let data = compress_json(inputs).map_err(sqlx::Error::Io)?;
+
+        sqlx::query(
+            "INSERT INTO inputs (epoch_update_uuid, data) VALUES ($1, $2)
+             ON CONFLICT (epoch_update_uuid) DO UPDATE SET data = excluded.data, created_at = CURRENT_TIMESTAMP"
         )
         .bind(uuid)
+        .bind(data)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Loads the cached sync committee of a period
+    pub async fn get_sync_committee(
+        &self,
+        network: Network,
+        period: u64,
+    ) -> Result<Option<SyncCommittee>, sqlx::Error> {
+        let row = sqlx::query_as::<_, SyncCommitteeRow>(
+            "SELECT network, period, pubkeys, aggregate_pubkey, committee_keys_root, committee_hash, validated
+             FROM sync_committees WHERE network = $1 AND period = $2"
+        )
+        .bind(network.to_string())
+        .bind(period as i64)
         .fetch_optional(&self.pool)
         .await?;
 
-        Ok(row.map(Into::into))
+        row.map(TryInto::try_into).transpose()
     }
 
-    pub async fn get_proof(&self, proof_id: i64) -> Result<Option<Proof>, sqlx::Error> {
-        let proof = sqlx::query_as!(
-            Proof,
-            "SELECT id, proof FROM proofs WHERE id = ?",
-            proof_id
+    /// Caches a sync committee, replacing an unvalidated entry for the same period
+    pub async fn store_sync_committee(&self, committee: &SyncCommittee) -> Result<(), sqlx::Error> {
+        let pubkeys: Vec<u8> = committee
+            .pubkeys
+            .iter()
+            .flat_map(|pubkey| pubkey.as_slice().to_vec())
+            .collect();
+
+        sqlx::query(
+            "INSERT INTO sync_committees
+                (network, period, pubkeys, aggregate_pubkey, committee_keys_root, committee_hash, validated)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)
+             ON CONFLICT (network, period) DO UPDATE SET
+                pubkeys = excluded.pubkeys,
+                aggregate_pubkey = excluded.aggregate_pubkey,
+                committee_keys_root = excluded.committee_keys_root,
+                committee_hash = excluded.committee_hash,
+                validated = excluded.validated
+             WHERE sync_committees.validated = 0"
+        )
+        .bind(committee.network.to_string())
+        .bind(committee.period as i64)
+        .bind(pubkeys)
+        .bind(committee.aggregate_pubkey.to_string())
+        .bind(committee.committee_keys_root.to_string())
+        .bind(committee.committee_hash.to_string())
+        .bind(committee.validated as i64)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Removes a cached sync committee, e.g. after it failed validation
+    pub async fn delete_sync_committee(&self, network: Network, period: u64) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM sync_committees WHERE network = $1 AND period = $2")
+            .bind(network.to_string())
+            .bind(period as i64)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Loads the proving inputs of an epoch update, if they were stored
+    pub async fn get_inputs(&self, uuid: &str) -> Result<Option<RecursiveEpochInputs>, sqlx::Error> {
+        let data: Option<Vec<u8>> = sqlx::query_scalar(
+            "SELECT data FROM inputs WHERE epoch_update_uuid = $1"
         )
+        .bind(uuid)
         .fetch_optional(&self.pool)
         .await?;
 
-        Ok(proof)
+        data.map(|data| decompress_json(&data).map_err(|e| sqlx::Error::Decode(Box::new(e))))
+            .transpose()
     }
-}
\ No newline at end of file
+}
+
+/// Possible errors that can occur during database operations
+#[derive(Debug, Error)]
+pub enum DatabaseError {
+    /// SQL or connection error
+    #[error("SQL error: {0}")]
+    Sqlx(#[from] sqlx::Error),
+    /// Error storing or loading a proof
+    #[error("Proof store error: {0}")]
+    ProofStore(#[from] ProofStoreError),
+    /// The database URL has an unsupported scheme
+    #[error("Unsupported database URL: {0}, expected sqlite: or postgres://")]
+    UnsupportedUrl(String),
+}
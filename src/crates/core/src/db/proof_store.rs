@@ -0,0 +1,134 @@
+//! Content-addressed storage for Stone proofs.
+//!
+//! Proofs are stored zstd-compressed under the hex SHA-256 of their uncompressed JSON, either as
+//! BLOBs in the `proof_blobs` table or as files on disk. Every read recomputes the hash, so a
+//! corrupted blob is reported instead of being fed into the next recursive run.
+
+use std::path::PathBuf;
+
+use sha2::{Digest, Sha256};
+use sqlx::AnyPool;
+use thiserror::Error;
+use tokio::fs;
+
+use crate::utils::compression::{compress, decompress};
+
+/// Backend holding the compressed proof blobs
+#[derive(Debug, Clone)]
+pub enum ProofStore {
+    /// Blobs are stored in the `proof_blobs` table of the Bankai database
+    Database(AnyPool),
+    /// Blobs are stored as `{dir}/{hash[..2]}/{hash}.json.zst`
+    Filesystem(PathBuf),
+}
+
+impl ProofStore {
+    /// Stores a proof and returns its content hash. Storing the same proof twice is a no-op.
+    pub async fn put(&self, proof: &[u8]) -> Result<String, ProofStoreError> {
+        let hash = content_hash(proof);
+        let data = compress(proof)?;
+
+        match self {
+            ProofStore::Database(pool) => {
+                sqlx::query("INSERT INTO proof_blobs (hash, data) VALUES ($1, $2) ON CONFLICT (hash) DO NOTHING")
+                    .bind(&hash)
+                    .bind(data)
+                    .execute(pool)
+                    .await?;
+            }
+            ProofStore::Filesystem(dir) => {
+                let path = blob_path(dir, &hash);
+                if fs::try_exists(&path).await? {
+                    return Ok(hash);
+                }
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent).await?;
+                }
+                // Write to a temporary file first so readers never observe a partial blob
+                let tmp_path = path.with_extension(format!("zst.{}.tmp", uuid::Uuid::new_v4()));
+                fs::write(&tmp_path, data).await?;
+                fs::rename(&tmp_path, &path).await?;
+            }
+        }
+
+        Ok(hash)
+    }
+
+    /// Loads a proof by its content hash and verifies that it still matches the hash
+    pub async fn get(&self, hash: &str) -> Result<Vec<u8>, ProofStoreError> {
+        let data = match self {
+            ProofStore::Database(pool) => {
+                let data: Option<Vec<u8>> =
+                    sqlx::query_scalar("SELECT data FROM proof_blobs WHERE hash = $1")
+                        .bind(hash)
+                        .fetch_optional(pool)
+                        .await?;
+                data.ok_or_else(|| ProofStoreError::NotFound(hash.to_string()))?
+            }
+            ProofStore::Filesystem(dir) => match fs::read(blob_path(dir, hash)).await {
+                Ok(data) => data,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    return Err(ProofStoreError::NotFound(hash.to_string()))
+                }
+                Err(e) => return Err(e.into()),
+            },
+        };
+
+        let proof = decompress(&data)?;
+        let actual = content_hash(&proof);
+        if actual != hash {
+            return Err(ProofStoreError::Integrity {
+                expected: hash.to_string(),
+                actual,
+            });
+        }
+
+        Ok(proof)
+    }
+
+    /// Removes a proof blob. Removing a missing blob is not an error.
+    pub async fn delete(&self, hash: &str) -> Result<(), ProofStoreError> {
+        match self {
+            ProofStore::Database(pool) => {
+                sqlx::query("DELETE FROM proof_blobs WHERE hash = $1")
+                    .bind(hash)
+                    .execute(pool)
+                    .await?;
+            }
+            ProofStore::Filesystem(dir) => match fs::remove_file(blob_path(dir, hash)).await {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            },
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the hex encoded SHA-256 of `data`
+pub fn content_hash(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+fn blob_path(dir: &std::path::Path, hash: &str) -> PathBuf {
+    let prefix = hash.get(..2).unwrap_or("00");
+    dir.join(prefix).join(format!("{}.json.zst", hash))
+}
+
+/// Possible errors that can occur while storing or loading proofs
+#[derive(Debug, Error)]
+pub enum ProofStoreError {
+    /// Database error
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+    /// File system or compression error
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    /// No blob is stored under the hash
+    #[error("Proof {0} not found in proof store")]
+    NotFound(String),
+    /// The stored blob does not match its content hash
+    #[error("Proof integrity check failed: expected hash {expected}, got {actual}")]
+    Integrity { expected: String, actual: String },
+}
@@ -0,0 +1,143 @@
+//! Storage abstraction for epoch updates and proofs.
+//!
+//! The input building logic only needs a handful of operations on the stored state. `Store`
+//! captures them so the recursion can run on top of the SQL `Database` as well as on the
+//! `MemoryStore` when Bankai is embedded without a database.
+
+use std::future::Future;
+
+use crate::{fetcher::recursive_epoch_input::RecursiveEpochOutput, utils::config::Network};
+
+use super::{Database, DatabaseError, EpochUpdate, Proof, Status, SyncCommittee};
+
+/// Operations on epoch updates and proofs required to build and track recursive updates
+pub trait Store: Send + Sync {
+    /// Error returned by the store
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// Returns the latest non-errored epoch update
+    fn get_latest_epoch_update(
+        &self,
+    ) -> impl Future<Output = Result<Option<EpochUpdate>, Self::Error>> + Send;
+
+    /// Returns the epoch update with the given UUID
+    fn get_epoch_update_by_uuid(
+        &self,
+        uuid: &str,
+    ) -> impl Future<Output = Result<Option<EpochUpdate>, Self::Error>> + Send;
+
+    /// Creates a new epoch update in the `fetching` state and returns its UUID
+    fn create_epoch_update(
+        &self,
+        epoch_number: u64,
+        slot_number: u64,
+        outputs: RecursiveEpochOutput,
+    ) -> impl Future<Output = Result<String, Self::Error>> + Send;
+
+    /// Sets the status of an epoch update
+    fn update_status(
+        &self,
+        uuid: &str,
+        status: Status,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Marks an epoch update as errored
+    fn update_error(
+        &self,
+        uuid: &str,
+        error_reason: &str,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Stores a proof and returns its ID. Identical proofs share one ID.
+    fn add_proof(&self, proof_json: &str) -> impl Future<Output = Result<i64, Self::Error>> + Send;
+
+    /// Links a proof to an epoch update
+    fn update_proof_id(
+        &self,
+        uuid: &str,
+        proof_id: i64,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Loads a proof by its ID
+    fn get_proof(
+        &self,
+        proof_id: i64,
+    ) -> impl Future<Output = Result<Option<Proof>, Self::Error>> + Send;
+
+    /// Loads the cached sync committee of a period
+    fn get_sync_committee(
+        &self,
+        network: Network,
+        period: u64,
+    ) -> impl Future<Output = Result<Option<SyncCommittee>, Self::Error>> + Send;
+
+    /// Caches a sync committee. A validated entry is never replaced.
+    fn store_sync_committee(
+        &self,
+        committee: &SyncCommittee,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Removes a cached sync committee
+    fn delete_sync_committee(
+        &self,
+        network: Network,
+        period: u64,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send;
+}
+
+impl Store for Database {
+    type Error = DatabaseError;
+
+    async fn get_latest_epoch_update(&self) -> Result<Option<EpochUpdate>, DatabaseError> {
+        Ok(Database::get_latest_epoch_update(self).await?)
+    }
+
+    async fn get_epoch_update_by_uuid(&self, uuid: &str) -> Result<Option<EpochUpdate>, DatabaseError> {
+        Ok(Database::get_epoch_update_by_uuid(self, uuid).await?)
+    }
+
+    async fn create_epoch_update(
+        &self,
+        epoch_number: u64,
+        slot_number: u64,
+        outputs: RecursiveEpochOutput,
+    ) -> Result<String, DatabaseError> {
+        Ok(Database::create_epoch_update(self, epoch_number, slot_number, outputs).await?)
+    }
+
+    async fn update_status(&self, uuid: &str, status: Status) -> Result<(), DatabaseError> {
+        Ok(Database::update_status(self, uuid, status).await?)
+    }
+
+    async fn update_error(&self, uuid: &str, error_reason: &str) -> Result<(), DatabaseError> {
+        Ok(Database::update_error(self, uuid, error_reason).await?)
+    }
+
+    async fn add_proof(&self, proof_json: &str) -> Result<i64, DatabaseError> {
+        Database::add_proof(self, proof_json).await
+    }
+
+    async fn update_proof_id(&self, uuid: &str, proof_id: i64) -> Result<(), DatabaseError> {
+        Ok(Database::update_proof_id(self, uuid, proof_id).await?)
+    }
+
+    async fn get_proof(&self, proof_id: i64) -> Result<Option<Proof>, DatabaseError> {
+        Database::get_proof(self, proof_id).await
+    }
+
+    async fn get_sync_committee(
+        &self,
+        network: Network,
+        period: u64,
+    ) -> Result<Option<SyncCommittee>, DatabaseError> {
+        Ok(Database::get_sync_committee(self, network, period).await?)
+    }
+
+    async fn store_sync_committee(&self, committee: &SyncCommittee) -> Result<(), DatabaseError> {
+        Ok(Database::store_sync_committee(self, committee).await?)
+    }
+
+    async fn delete_sync_committee(&self, network: Network, period: u64) -> Result<(), DatabaseError> {
+        Ok(Database::delete_sync_committee(self, network, period).await?)
+    }
+}
@@ -8,22 +8,28 @@ use std::fs;
 
 use crate::clients::ClientError;
 use crate::fetcher::execution_header_input::ExecutionHeaderError;
-use crate::utils::constants;
+use crate::utils::{config::SyncCommitteeSource, constants};
 use crate::utils::hashing::get_committee_hash;
 use crate::{
     clients::beacon_chain::BeaconError, fetcher::execution_header_input::ExecutionHeaderProof,
 };
-use crate::fetcher::sync_committee_input::{SyncCommitteeData, SyncCommitteeValidatorPubs};
+use crate::fetcher::sync_committee_input::{SyncCommitteeData, SyncCommitteeError, SyncCommitteeValidatorPubs};
 use crate::clients::beacon_chain::BeaconRpcClient;
+use crate::metrics::metrics;
+use crate::db::{store::Store, SyncCommittee};
+use crate::utils::bls::{
+    decode_coordinate, g1_from_compressed, g1_from_uncompressed, g2_from_uncompressed, PointError,
+};
 // use crate::utils::{constants, hashing::get_committee_hash};
 use alloy_primitives::FixedBytes;
 use alloy_rpc_types_beacon::{
     events::light_client_finality::SyncAggregate, header::HeaderResponse,
 };
 use bls12_381::{G1Affine, G2Affine};
+use cairo_vm::Felt252;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use tracing::{info, debug, error};
+use tracing::{info, debug, error, warn};
 use tree_hash::TreeHash;
 use tree_hash_derive::TreeHash;
 
@@ -49,6 +55,31 @@ pub struct RecursiveEpochOutput {
     pub next_committee_hash: FixedBytes<32>,
 }
 
+impl RecursiveEpochOutput {
+    /// Returns the outputs as the 13 felts written by the recursive update program.
+    ///
+    /// 256 bit values are split into a low and a high 128 bit limb, in that order.
+    pub fn to_felts(&self) -> Vec<Felt252> {
+        let limbs = |value: &FixedBytes<32>| {
+            [
+                Felt252::from_bytes_be_slice(&value[16..32]),
+                Felt252::from_bytes_be_slice(&value[0..16]),
+            ]
+        };
+
+        let mut felts = Vec::with_capacity(13);
+        felts.extend(limbs(&self.beacon_header_root));
+        felts.extend(limbs(&self.beacon_state_root));
+        felts.push(Felt252::from(self.beacon_height));
+        felts.push(Felt252::from(self.n_signers));
+        felts.extend(limbs(&self.execution_header_root));
+        felts.push(Felt252::from(self.execution_header_height));
+        felts.extend(limbs(&self.current_committee_hash));
+        felts.extend(limbs(&self.next_committee_hash));
+        felts
+    }
+}
+
 /// Represents the inputs for recursive epoch update processing using native types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RecursiveEpochInputs {
@@ -93,17 +124,21 @@ pub struct BeaconHeader {
     pub body_root: FixedBytes<32>,
 }
 
-impl From<RecursiveEpochInputs> for RecursiveEpochUpdate {
-    fn from(val: RecursiveEpochInputs) -> Self {
-        RecursiveEpochUpdate {
-            outputs: val.clone().into(),
+impl TryFrom<RecursiveEpochInputs> for RecursiveEpochUpdate {
+    type Error = PointError;
+
+    fn try_from(val: RecursiveEpochInputs) -> Result<Self, Self::Error> {
+        Ok(RecursiveEpochUpdate {
+            outputs: val.clone().try_into()?,
             inputs: val,
-        }
+        })
     }
 }
 
-impl From<RecursiveEpochInputs> for RecursiveEpochOutput {
-    fn from(val: RecursiveEpochInputs) -> Self {
+impl TryFrom<RecursiveEpochInputs> for RecursiveEpochOutput {
+    type Error = PointError;
+
+    fn try_from(val: RecursiveEpochInputs) -> Result<Self, Self::Error> {
         let execution_header_hash = val.epoch_update.execution_header_proof.execution_payload_header.block_hash();
         println!("committee update: {:?}", val.sync_committee_update);
 
@@ -125,12 +160,9 @@ impl From<RecursiveEpochInputs> for RecursiveEpochOutput {
                     ),
                     Some(sync_committee_update) => (
                         stark_proof_output.current_committee_hash,
-                        get_committee_hash(
-                            G1Affine::from_compressed(
-                                &sync_committee_update.next_aggregate_sync_committee,
-                            )
-                            .unwrap(),
-                        ),
+                        get_committee_hash(g1_from_compressed(
+                            &sync_committee_update.next_aggregate_sync_committee,
+                        )?),
                     ),
                 }
             } else {
@@ -156,7 +188,7 @@ impl From<RecursiveEpochInputs> for RecursiveEpochOutput {
             next_committee_hash,
         };
         println!("RecursiveEpochOutput: {:?}", out);
-        out
+        Ok(out)
     }
 }
 
@@ -165,13 +197,13 @@ impl RecursiveEpochInputs {
     ///
     /// # Arguments
     /// * `client` - Reference to the beacon chain client
-    /// * `db` - Reference to the database
+    /// * `db` - Store holding the proven epoch updates
     ///
     /// # Returns
     /// * `Result<Self, EpochUpdateError>` - New epoch update or error
     pub async fn new(
         client: &BeaconRpcClient,
-        db: &crate::db::Database,
+        db: &impl Store,
         fast_forward: Option<u64>,
     ) -> Result<Self, EpochUpdateError> {
         info!("🔍 Initializing recursive epoch inputs...");
@@ -181,48 +213,93 @@ impl RecursiveEpochInputs {
         }
 
         info!("📊 Querying database for latest epoch update...");
-        let latest_epoch_update = db.get_latest_epoch_update().await
-            .map_err(|e| EpochUpdateError::Io(std::io::Error::new(std::io::ErrorKind::Other, e)))?;
+        let latest_epoch_update = db.get_latest_epoch_update().await.map_err(store_error)?;
 
-        match latest_epoch_update {
+        let target_epoch = match &latest_epoch_update {
             Some(update) => {
                 info!("✅ Found existing epoch update - Epoch: {}, Slot: {}, UUID: {}", update.epoch_number, update.slot_number, update.uuid);
-                
-                let target_epoch = update.epoch_number as u64 + 1 + fast_forward.unwrap_or(0);
-                let slot = target_epoch * constants::SLOTS_PER_EPOCH + constants::SLOTS_PER_EPOCH - 1;
+                update.epoch_number as u64 + 1 + fast_forward.unwrap_or(0)
+            }
+            None => constants::GENESIS_EPOCH,
+        };
+
+        Self::for_epoch(client, db, latest_epoch_update, target_epoch).await
+    }
+
+    /// Creates the epoch update inputs for `target_epoch`, recursing on `previous`
+    ///
+    /// # Arguments
+    /// * `client` - Reference to the beacon chain client
+    /// * `db` - Store holding the proven epoch updates
+    /// * `previous` - The proven epoch update to recurse on, or `None` for genesis
+    /// * `target_epoch` - The epoch to generate the inputs for
+    ///
+    /// # Returns
+    /// * `Result<Self, EpochUpdateError>` - New epoch update or error
+    pub async fn for_epoch(
+        client: &BeaconRpcClient,
+        db: &impl Store,
+        previous: Option<crate::db::EpochUpdate>,
+        target_epoch: u64,
+    ) -> Result<Self, EpochUpdateError> {
+        match previous {
+            Some(update) => {
+                let mut slot = target_epoch * constants::SLOTS_PER_EPOCH + constants::SLOTS_PER_EPOCH - 1;
                 info!("🎯 Target epoch: {}, Target slot: {}", target_epoch, slot);
-                
-                info!("🏗️  Generating epoch update proof for slot {}...", slot);
-                let epoch_update = EpochUpdate::generate_epoch_proof(client, slot).await?;
-                info!("✅ Epoch update proof generated successfully");
-                
+
+                // Load the proof to recurse on first, there is no point in fetching beacon data without it
                 info!("🔍 Loading STARK proof from previous epoch...");
                 let stark_proof = match update.proof_id {
                     Some(proof_id) => {
                         debug!("📄 Found proof ID: {}", proof_id);
                         let proof = db.get_proof(proof_id).await
-                            .map_err(|e| EpochUpdateError::Io(std::io::Error::new(std::io::ErrorKind::Other, e)))?
-                            .ok_or_else(|| EpochUpdateError::Io(std::io::Error::new(std::io::ErrorKind::NotFound, "Proof not found in database")))?;
+                            .map_err(store_error)?
+                            .ok_or_else(|| EpochUpdateError::MissingProof(format!("Proof {} not found in database", proof_id)))?;
                         
                         serde_json::from_str(&proof.proof)
                             .map_err(|e| EpochUpdateError::Deserialize(e))?
                     }
                     None => {
-                        return Err(EpochUpdateError::Io(std::io::Error::new(
-                            std::io::ErrorKind::InvalidData, 
-                            "No proof ID found for previous epoch update"
-                        )));
+                        return Err(EpochUpdateError::MissingProof(
+                            "No proof ID found for previous epoch update".to_string(),
+                        ));
                     }
                 };
                 info!("✅ STARK proof loaded successfully");
 
                 info!("🔍 Checking if sync committee update is needed...");
+                let mut attested_state_root = None;
                 let sync_committee_update = match update.outputs {
                     Some(ref output) => {
                         if output.next_committee_hash == FixedBytes::from([0u8; 32]) {
                             info!("🔄 Next committee hash is zero, generating sync committee update...");
-                            let sync_committee_update = SyncCommitteeData::new(client, slot).await?;
+                            let sync_committee_update = match client.config().beacon.sync_committee_source {
+                                SyncCommitteeSource::StateProof => SyncCommitteeData::new(client, slot).await?,
+                                SyncCommitteeSource::LightClient => {
+                                    let (light_client_update, state_root) =
+                                        SyncCommitteeData::from_light_client(client, slot).await?;
+                                    // The branch only proves the committee in the attested state, so
+                                    // this update proves the attested header instead of the target slot.
+                                    // An update attesting a slot that is already proven can not advance
+                                    // the chain, and the node keeps serving it until the period's best
+                                    // update changes, so the committee is proven from the state instead.
+                                    if light_client_update.beacon_slot > update.slot_number as u64 {
+                                        slot = light_client_update.beacon_slot;
+                                        attested_state_root = Some(state_root);
+                                        info!("🎯 Proving attested slot {} of the light client update", slot);
+                                        light_client_update
+                                    } else {
+                                        warn!(
+                                            "⚠️  Light client update attests slot {}, which is not after the last proven slot {}, falling back to the state proof",
+                                            light_client_update.beacon_slot, update.slot_number
+                                        );
+                                        SyncCommitteeData::new(client, slot).await?
+                                    }
+                                }
+                            };
+                            metrics().committee_updates_total.inc();
                             info!("✅ Sync committee update generated");
+                            EpochUpdate::cache_next_sync_committee(client, db, &sync_committee_update).await?;
                             Some(sync_committee_update)
                         } else {
                             info!("✅ Next committee hash already set, no sync committee update needed");
@@ -235,6 +312,20 @@ impl RecursiveEpochInputs {
                     }
                 };
 
+                info!("🏗️  Generating epoch update proof for slot {}...", slot);
+                let epoch_update = EpochUpdate::generate_epoch_proof(client, db, slot).await?;
+                if let Some(state_root) = attested_state_root {
+                    if epoch_update.header.state_root != state_root {
+                        return Err(SyncCommitteeError::StateRootMismatch {
+                            slot: epoch_update.header.slot,
+                            expected: epoch_update.header.state_root,
+                            computed: state_root,
+                        }
+                        .into());
+                    }
+                }
+                info!("✅ Epoch update proof generated successfully");
+
                 info!("🎉 Recursive epoch inputs created successfully");
                 Ok(Self {
                     epoch_update,
@@ -245,11 +336,11 @@ impl RecursiveEpochInputs {
             }
             None => {
                 info!("🏁 No previous epoch update found, creating genesis inputs...");
-                let slot = constants::GENESIS_EPOCH * constants::SLOTS_PER_EPOCH + constants::SLOTS_PER_EPOCH - 1;
+                let slot = target_epoch * constants::SLOTS_PER_EPOCH + constants::SLOTS_PER_EPOCH - 1;
                 info!("🎯 Genesis slot: {}", slot);
                 
                 info!("🏗️  Generating genesis epoch proof...");
-                let epoch_update = EpochUpdate::generate_epoch_proof(client, slot).await?;
+                let epoch_update = EpochUpdate::generate_epoch_proof(client, db, slot).await?;
                 info!("✅ Genesis epoch update proof generated successfully");
                 
                 info!("🎉 Genesis inputs created successfully");
@@ -283,12 +374,14 @@ impl EpochUpdate {
     ///
     /// # Arguments
     /// * `client` - Reference to the beacon chain client
+    /// * `db` - Store caching the sync committees
     /// * `slot` - Slot number to generate proof for
     ///
     /// # Returns
     /// * `Result<EpochUpdate, EpochUpdateError>` - Generated inputs or error
     pub(crate) async fn generate_epoch_proof(
         client: &BeaconRpcClient,
+        db: &impl Store,
         mut slot: u64,
     ) -> Result<EpochUpdate, EpochUpdateError> {
         info!("🏗️  Starting epoch proof generation for slot {}", slot);
@@ -308,16 +401,16 @@ impl EpochUpdate {
                 },
                 Err(BeaconError::EmptySlot(_)) => {
                     attempts += 1;
-                    if attempts >= constants::MAX_SKIPPED_SLOTS_RETRY_ATTEMPTS {
+                    if attempts >= client.config().beacon.max_skipped_slots {
                         let error_msg = format!("Exceeded maximum empty slot retry attempts ({}) starting from slot {}", 
-                                               constants::MAX_SKIPPED_SLOTS_RETRY_ATTEMPTS, original_slot);
+                                               client.config().beacon.max_skipped_slots, original_slot);
                         return Err(EpochUpdateError::Client(
                             BeaconError::EmptySlot(slot).into(),
                         ));
                     }
                     slot += 1;
                     debug!("⚠️  Empty slot detected at {}! Attempt {}/{}. Trying next slot: {}", 
-                           slot - 1, attempts, constants::MAX_SKIPPED_SLOTS_RETRY_ATTEMPTS, slot);
+                           slot - 1, attempts, client.config().beacon.max_skipped_slots, slot);
                 }
                 Err(e) => {
                     error!("❌ Failed to fetch header for slot {}: {}", slot, e);
@@ -333,12 +426,9 @@ impl EpochUpdate {
             .map_err(ClientError::Beacon)?;
         info!("✅ Sync aggregate fetched successfully");
 
-        info!("📥 Fetching sync committee validator public keys...");
-        let validator_pubs = client
-            .get_sync_committee_validator_pubs(slot)
-            .await
-            .map_err(ClientError::Beacon)?;
-        info!("✅ Validator public keys fetched successfully ({} validators)", validator_pubs.validator_pubs.len());
+        info!("📥 Loading sync committee validator public keys...");
+        let validator_pubs = Self::sync_committee_validator_pubs(client, db, slot).await?;
+        info!("✅ Validator public keys loaded successfully ({} validators)", validator_pubs.validator_pubs.len());
 
         info!("🔐 Processing BLS signature...");
         let signature_point = Self::extract_signature_point(&sync_agg)?;
@@ -363,6 +453,125 @@ impl EpochUpdate {
         })
     }
 
+    /// Returns the sync committee signing `slot`, from the cache if possible.
+    ///
+    /// The signature for `slot` is included in, and signed by the committee of, `slot + 1`.
+    /// Committees fetched from the beacon node are cached for their period.
+    ///
+    /// # Arguments
+    /// * `client` - Reference to the beacon chain client
+    /// * `db` - Store caching the sync committees
+    /// * `slot` - Slot number of the signed header
+    ///
+    /// # Returns
+    /// * `Result<SyncCommitteeValidatorPubs, EpochUpdateError>` - The committee or error
+    async fn sync_committee_validator_pubs(
+        client: &BeaconRpcClient,
+        db: &impl Store,
+        slot: u64,
+    ) -> Result<SyncCommitteeValidatorPubs, EpochUpdateError> {
+        let network = client.config().network;
+        let period = (slot + 1) / constants::SLOTS_PER_SYNC_COMMITTEE;
+
+        if let Some(cached) = db.get_sync_committee(network, period).await.map_err(store_error)? {
+            match SyncCommitteeValidatorPubs::try_from(&cached) {
+                Ok(validator_pubs) => {
+                    info!("♻️  Using cached sync committee for period {}", period);
+                    return Ok(validator_pubs);
+                }
+                Err(e) => {
+                    warn!("⚠️  Cached sync committee for period {} is invalid, refetching: {}", period, e);
+                    db.delete_sync_committee(network, period).await.map_err(store_error)?;
+                }
+            }
+        }
+
+        let validator_pubs = client
+            .get_sync_committee_validator_pubs(slot)
+            .await
+            .map_err(ClientError::Beacon)?;
+        db.store_sync_committee(&validator_pubs.to_cached(network, period, false))
+            .await
+            .map_err(store_error)?;
+        debug!("💾 Cached sync committee for period {}", period);
+
+        Ok(validator_pubs)
+    }
+
+    /// Warms the sync committee cache for the committee signing `slot`.
+    ///
+    /// # Arguments
+    /// * `client` - Reference to the beacon chain client
+    /// * `db` - Store caching the sync committees
+    /// * `slot` - Slot number of a header signed by the committee
+    ///
+    /// # Returns
+    /// * `Result<(), EpochUpdateError>` - An error if the committee could not be fetched
+    pub async fn prefetch_sync_committee(
+        client: &BeaconRpcClient,
+        db: &impl Store,
+        slot: u64,
+    ) -> Result<(), EpochUpdateError> {
+        Self::sync_committee_validator_pubs(client, db, slot).await.map(|_| ())
+    }
+
+    /// Caches the committee of the next period, validated against a proven committee update.
+    ///
+    /// An unvalidated cache entry that does not match the proven update is replaced by the
+    /// committee fetched from the beacon node.
+    ///
+    /// # Arguments
+    /// * `client` - Reference to the beacon chain client
+    /// * `db` - Store caching the sync committees
+    /// * `proven` - The sync committee update proving the next committee
+    ///
+    /// # Returns
+    /// * `Result<(), EpochUpdateError>` - An error if the beacon node disagrees with the proof
+    pub(crate) async fn cache_next_sync_committee(
+        client: &BeaconRpcClient,
+        db: &impl Store,
+        proven: &SyncCommitteeData,
+    ) -> Result<(), EpochUpdateError> {
+        let network = client.config().network;
+        let period = proven.beacon_slot / constants::SLOTS_PER_SYNC_COMMITTEE + 1;
+
+        if let Some(cached) = db.get_sync_committee(network, period).await.map_err(store_error)? {
+            let checked = SyncCommitteeValidatorPubs::try_from(&cached)
+                .map_err(|e| e.to_string())
+                .and_then(|validator_pubs| validator_pubs.verify_against(proven));
+            match checked {
+                Ok(()) => {
+                    if !cached.validated {
+                        db.store_sync_committee(&SyncCommittee { validated: true, ..cached })
+                            .await
+                            .map_err(store_error)?;
+                    }
+                    info!("✅ Cached sync committee for period {} matches the proven update", period);
+                    return Ok(());
+                }
+                Err(reason) => {
+                    warn!("⚠️  Cached sync committee for period {} does not match the proven update: {}", period, reason);
+                    db.delete_sync_committee(network, period).await.map_err(store_error)?;
+                }
+            }
+        }
+
+        info!("📥 Fetching sync committee for period {}...", period);
+        let validator_pubs = client
+            .get_next_sync_committee_validator_pubs(proven.beacon_slot)
+            .await
+            .map_err(ClientError::Beacon)?;
+        validator_pubs
+            .verify_against(proven)
+            .map_err(|reason| EpochUpdateError::SyncCommitteeMismatch { period, reason })?;
+        db.store_sync_committee(&validator_pubs.to_cached(network, period, true))
+            .await
+            .map_err(store_error)?;
+        info!("💾 Cached validated sync committee for period {}", period);
+
+        Ok(())
+    }
+
     /// Extracts and validates the BLS signature point from the sync aggregate
     ///
     /// # Arguments
@@ -434,6 +643,24 @@ pub struct G1Point(pub G1Affine);
 #[derive(Debug, Clone)]
 pub struct G2Point(pub G2Affine);
 
+impl TryFrom<[u8; 96]> for G1Point {
+    type Error = PointError;
+
+    /// Decodes a G1 point from its uncompressed form, rejecting points off the curve
+    fn try_from(uncompressed: [u8; 96]) -> Result<Self, Self::Error> {
+        g1_from_uncompressed(&uncompressed).map(G1Point)
+    }
+}
+
+impl TryFrom<[u8; 192]> for G2Point {
+    type Error = PointError;
+
+    /// Decodes a G2 point from its uncompressed form, rejecting points off the curve
+    fn try_from(uncompressed: [u8; 192]) -> Result<Self, Self::Error> {
+        g2_from_uncompressed(&uncompressed).map(G2Point)
+    }
+}
+
 impl Serialize for G1Point {
     /// Serializes a G1 point to its uncompressed form
     ///
@@ -503,24 +730,15 @@ impl<'de> Deserialize<'de> for G1Point {
             .as_str()
             .ok_or_else(|| serde::de::Error::custom("missing y coordinate"))?;
 
-        // Safely remove "0x" prefix if it exists
-        let x_hex = x_str.strip_prefix("0x").unwrap_or(x_str);
-        let y_hex = y_str.strip_prefix("0x").unwrap_or(y_str);
-
-        let x_bytes = hex::decode(x_hex)
-            .map_err(|e| serde::de::Error::custom(format!("invalid x hex: {}", e)))?;
-        let y_bytes = hex::decode(y_hex)
-            .map_err(|e| serde::de::Error::custom(format!("invalid y hex: {}", e)))?;
+        let x_bytes = decode_coordinate("x", x_str).map_err(serde::de::Error::custom)?;
+        let y_bytes = decode_coordinate("y", y_str).map_err(serde::de::Error::custom)?;
 
         // Combine into uncompressed format
         let mut uncompressed = [0u8; 96];
         uncompressed[0..48].copy_from_slice(&x_bytes);
         uncompressed[48..96].copy_from_slice(&y_bytes);
 
-        // Convert to G1Affine point
-        let point = G1Affine::from_uncompressed(&uncompressed).unwrap();
-
-        Ok(G1Point(point))
+        G1Point::try_from(uncompressed).map_err(serde::de::Error::custom)
     }
 }
 
@@ -549,21 +767,10 @@ impl<'de> Deserialize<'de> for G2Point {
             .as_str()
             .ok_or_else(|| serde::de::Error::custom("missing y1 coordinate"))?;
 
-        // Safely remove "0x" prefix if it exists
-        let x0_hex = x0_str.strip_prefix("0x").unwrap_or(x0_str);
-        let x1_hex = x1_str.strip_prefix("0x").unwrap_or(x1_str);
-        let y0_hex = y0_str.strip_prefix("0x").unwrap_or(y0_str);
-        let y1_hex = y1_str.strip_prefix("0x").unwrap_or(y1_str);
-
-        // Decode hex strings to bytes
-        let x0_bytes = hex::decode(x0_hex)
-            .map_err(|e| serde::de::Error::custom(format!("invalid x0 hex: {}", e)))?;
-        let x1_bytes = hex::decode(x1_hex)
-            .map_err(|e| serde::de::Error::custom(format!("invalid x1 hex: {}", e)))?;
-        let y0_bytes = hex::decode(y0_hex)
-            .map_err(|e| serde::de::Error::custom(format!("invalid y0 hex: {}", e)))?;
-        let y1_bytes = hex::decode(y1_hex)
-            .map_err(|e| serde::de::Error::custom(format!("invalid y1 hex: {}", e)))?;
+        let x0_bytes = decode_coordinate("x0", x0_str).map_err(serde::de::Error::custom)?;
+        let x1_bytes = decode_coordinate("x1", x1_str).map_err(serde::de::Error::custom)?;
+        let y0_bytes = decode_coordinate("y0", y0_str).map_err(serde::de::Error::custom)?;
+        let y1_bytes = decode_coordinate("y1", y1_str).map_err(serde::de::Error::custom)?;
 
         // Combine into uncompressed format
         let mut uncompressed = [0u8; 192];
@@ -572,10 +779,7 @@ impl<'de> Deserialize<'de> for G2Point {
         uncompressed[96..144].copy_from_slice(&y1_bytes);
         uncompressed[144..192].copy_from_slice(&y0_bytes);
 
-        // Convert to G2Affine point
-        let point = G2Affine::from_uncompressed(&uncompressed).unwrap();
-
-        Ok(G2Point(point))
+        G2Point::try_from(uncompressed).map_err(serde::de::Error::custom)
     }
 }
 
@@ -600,5 +804,218 @@ pub enum EpochUpdateError {
     /// Invalid BLS cryptographic point
     #[error("Invalid BLS point")]
     InvalidBLSPoint,
+    /// Malformed validator key or curve point
+    #[error("Point decoding error: {0}")]
+    Point(#[from] PointError),
+    /// The sync committee reported by the beacon node does not match the proven one
+    #[error("Sync committee for period {period} does not match the proven update: {reason}")]
+    SyncCommitteeMismatch { period: u64, reason: String },
+    /// Reading or writing the store failed
+    #[error("Store error: {0}")]
+    Store(Box<dyn std::error::Error + Send + Sync>),
+    /// The proof of the previous epoch update is not stored
+    #[error("Missing proof: {0}")]
+    MissingProof(String),
 }
 
+/// Wraps a store error, which is opaque to the input building logic
+fn store_error(e: impl std::error::Error + Send + Sync + 'static) -> EpochUpdateError {
+    EpochUpdateError::Store(Box::new(e))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Range;
+
+    use bankai_test_support::beacon::{BeaconFixtures, MockBeacon};
+    use bls12_381::Scalar;
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::{
+        db::{memory::MemoryStore, Status},
+        utils::config::BankaiConfig,
+    };
+
+    /// Slot in period 1, so the proven committee is the one of period 2
+    const PROVEN_SLOT: u64 = 8200;
+    const NEXT_PERIOD: u64 = 2;
+
+    fn pubkey(index: u64) -> String {
+        let point = G1Affine::from(G1Affine::generator() * Scalar::from(index + 1));
+        format!("0x{}", hex::encode(point.to_compressed()))
+    }
+
+    fn validator_pubs(indexes: Range<u64>) -> SyncCommitteeValidatorPubs {
+        indexes.map(pubkey).collect::<Vec<_>>().try_into().unwrap()
+    }
+
+    fn proven(validator_pubs: &SyncCommitteeValidatorPubs) -> SyncCommitteeData {
+        SyncCommitteeData {
+            beacon_slot: PROVEN_SLOT,
+            next_sync_committee_branch: Vec::new(),
+            next_aggregate_sync_committee: FixedBytes::from(validator_pubs.aggregate_pub.to_compressed()),
+            committee_keys_root: validator_pubs.committee_keys_root(),
+        }
+    }
+
+    /// Beacon node reporting `indexes` as the next sync committee at `PROVEN_SLOT`
+    async fn beacon(indexes: Range<u64>) -> (MockBeacon, BeaconRpcClient) {
+        let validators: Vec<Value> = indexes
+            .clone()
+            .map(|index| {
+                json!({
+                    "index": index.to_string(),
+                    "status": "active_ongoing",
+                    "validator": { "pubkey": pubkey(index) },
+                })
+            })
+            .collect();
+        let ids: Vec<String> = indexes.map(|index| index.to_string()).collect();
+
+        let mut fixtures = BeaconFixtures::default();
+        fixtures.insert(
+            format!("eth/v1/beacon/states/{}/sync_committees?epoch=512", PROVEN_SLOT),
+            json!({ "data": { "validators": ids } }),
+        );
+        fixtures.insert(
+            format!("eth/v1/beacon/states/{}/validators", PROVEN_SLOT),
+            json!({ "data": validators }),
+        );
+        let beacon = MockBeacon::start(fixtures).await.unwrap();
+        let mut config = BankaiConfig::default();
+        config.beacon.rpc_url = beacon.url();
+        config.beacon.request_delay_ms = 0;
+        let client = BeaconRpcClient::new(beacon.url(), config);
+        (beacon, client)
+    }
+
+    async fn cached(db: &MemoryStore, client: &BeaconRpcClient) -> Option<SyncCommittee> {
+        db.get_sync_committee(client.config().network, NEXT_PERIOD).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn validates_matching_cached_committee() {
+        let (beacon, client) = beacon(0..0).await;
+        let committee = validator_pubs(0..512);
+        let db = MemoryStore::new();
+        db.store_sync_committee(&committee.to_cached(client.config().network, NEXT_PERIOD, false))
+            .await
+            .unwrap();
+
+        EpochUpdate::cache_next_sync_committee(&client, &db, &proven(&committee))
+            .await
+            .unwrap();
+
+        let cached = cached(&db, &client).await.unwrap();
+        assert!(cached.validated);
+        assert_eq!(cached.committee_keys_root, committee.committee_keys_root());
+        assert!(beacon.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn replaces_mismatching_unvalidated_committee() {
+        let (_beacon, client) = beacon(0..512).await;
+        let stale = validator_pubs(512..1024);
+        let committee = validator_pubs(0..512);
+        let db = MemoryStore::new();
+        db.store_sync_committee(&stale.to_cached(client.config().network, NEXT_PERIOD, false))
+            .await
+            .unwrap();
+
+        EpochUpdate::cache_next_sync_committee(&client, &db, &proven(&committee))
+            .await
+            .unwrap();
+
+        let cached = cached(&db, &client).await.unwrap();
+        assert!(cached.validated);
+        assert_eq!(cached.committee_keys_root, committee.committee_keys_root());
+    }
+
+    #[tokio::test]
+    async fn validated_committee_is_not_replaced_by_store() {
+        let network = BankaiConfig::default().network;
+        let committee = validator_pubs(0..512);
+        let db = MemoryStore::new();
+        db.store_sync_committee(&committee.to_cached(network, NEXT_PERIOD, true))
+            .await
+            .unwrap();
+
+        db.store_sync_committee(&validator_pubs(512..1024).to_cached(network, NEXT_PERIOD, false))
+            .await
+            .unwrap();
+
+        let cached = db.get_sync_committee(network, NEXT_PERIOD).await.unwrap().unwrap();
+        assert!(cached.validated);
+        assert_eq!(cached.committee_keys_root, committee.committee_keys_root());
+    }
+
+    #[tokio::test]
+    async fn rejects_beacon_committee_not_matching_proof() {
+        let (_beacon, client) = beacon(512..1024).await;
+        let db = MemoryStore::new();
+
+        let result =
+            EpochUpdate::cache_next_sync_committee(&client, &db, &proven(&validator_pubs(0..512))).await;
+
+        assert!(
+            matches!(result, Err(EpochUpdateError::SyncCommitteeMismatch { period: NEXT_PERIOD, .. })),
+            "{:?}",
+            result
+        );
+        assert!(cached(&db, &client).await.is_none());
+    }
+
+    fn outputs(slot: u64) -> RecursiveEpochOutput {
+        RecursiveEpochOutput {
+            beacon_header_root: FixedBytes::repeat_byte(0x11),
+            beacon_state_root: FixedBytes::repeat_byte(0x22),
+            beacon_height: slot,
+            n_signers: 510,
+            execution_header_root: FixedBytes::repeat_byte(0x33),
+            execution_header_height: 22_000_000,
+            current_committee_hash: FixedBytes::repeat_byte(0x44),
+            next_committee_hash: FixedBytes::repeat_byte(0x55),
+        }
+    }
+
+    #[tokio::test]
+    async fn recurses_on_latest_update_of_the_store() {
+        let (beacon, client) = beacon(0..0).await;
+        let db = MemoryStore::new();
+        let proven = db.create_epoch_update(256, PROVEN_SLOT - 8, outputs(PROVEN_SLOT - 8)).await.unwrap();
+        let proof_id = db.add_proof("{}").await.unwrap();
+        db.update_proof_id(&proven, proof_id).await.unwrap();
+        db.update_status(&proven, Status::Done).await.unwrap();
+        // The latest update has no proof yet, so there is nothing to recurse on
+        db.create_epoch_update(257, PROVEN_SLOT + 24, outputs(PROVEN_SLOT + 24)).await.unwrap();
+
+        let result = RecursiveEpochInputs::new(&client, &db, None).await;
+
+        assert!(
+            matches!(&result, Err(EpochUpdateError::MissingProof(message)) if message.contains("No proof ID")),
+            "{:?}",
+            result.map(|_| ())
+        );
+        assert!(beacon.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn rejects_previous_proof_missing_from_store() {
+        let (beacon, client) = beacon(0..0).await;
+        let db = MemoryStore::new();
+        let uuid = db.create_epoch_update(256, PROVEN_SLOT, outputs(PROVEN_SLOT)).await.unwrap();
+        db.update_proof_id(&uuid, 7).await.unwrap();
+        let previous = db.get_epoch_update_by_uuid(&uuid).await.unwrap();
+
+        let result = RecursiveEpochInputs::for_epoch(&client, &db, previous, 257).await;
+
+        assert!(
+            matches!(&result, Err(EpochUpdateError::MissingProof(message)) if message.contains("Proof 7")),
+            "{:?}",
+            result.map(|_| ())
+        );
+        assert!(beacon.requests().is_empty());
+    }
+}
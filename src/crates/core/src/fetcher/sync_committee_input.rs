@@ -5,7 +5,16 @@ use beacon_state_proof::state_proof_fetcher::{StateProofFetcher, SyncCommitteePr
 use sha2::{Digest, Sha256};
 use thiserror::Error;
 
-use crate::utils::hashing::get_committee_hash;
+use crate::{
+    db::SyncCommittee,
+    utils::{
+        bls::{decode_compressed_pubkey, decode_validator_pubkey, PointError},
+        config::Network,
+        constants,
+        hashing::get_committee_hash,
+        merkle,
+    },
+};
 
 /// Represents the public keys of sync committee validators and their aggregate
 #[derive(Debug, Clone)]
@@ -31,6 +40,11 @@ pub struct SyncCommitteeData {
 
 impl SyncCommitteeData {
     /// Creates a new sync committee update for a given slot
+    ///
+    /// The proof is built by `StateProofFetcher` from the full beacon state, which it downloads
+    /// as JSON with its own client, so `beacon.ssz` does not apply. Fetching the state as SSZ
+    /// through `BeaconRpcClient` is left to a separate request. `from_light_client` avoids the
+    /// state download.
     pub async fn new(
         client: &crate::clients::beacon_chain::BeaconRpcClient,
         slot: u64,
@@ -43,7 +57,82 @@ impl SyncCommitteeData {
         Ok(SyncCommitteeData::from(proof))
     }
 
-    /// Computes the state root by hashing the committee keys root and the aggregate pubkey
+    /// Builds the sync committee update from the light client update of the slot's period.
+    ///
+    /// The branch of a light client update proves the next sync committee against the state
+    /// of its attested header, so `beacon_slot` is the attested slot rather than `slot`. The
+    /// branch is verified against the attested state root before the update is returned.
+    ///
+    /// # Arguments
+    /// * `client` - The beacon chain client
+    /// * `slot` - A slot of the period whose next committee is needed
+    ///
+    /// # Returns
+    /// * `Result<(SyncCommitteeData, FixedBytes<32>), SyncCommitteeError>` - The update and the
+    ///   attested state root it was verified against
+    pub async fn from_light_client(
+        client: &crate::clients::beacon_chain::BeaconRpcClient,
+        slot: u64,
+    ) -> Result<(SyncCommitteeData, FixedBytes<32>), SyncCommitteeError> {
+        let period = slot / constants::SLOTS_PER_SYNC_COMMITTEE;
+        let update = client.get_light_client_update(period).await?;
+
+        let attested = &update["attested_header"]["beacon"];
+        let beacon_slot: u64 = json_str(attested, "slot")?
+            .parse()
+            .map_err(|e| SyncCommitteeError::LightClient(format!("invalid attested slot: {}", e)))?;
+        let state_root: FixedBytes<32> = parse_json_hex(attested, "state_root")?;
+        if beacon_slot / constants::SLOTS_PER_SYNC_COMMITTEE != period {
+            return Err(SyncCommitteeError::LightClient(format!(
+                "attested slot {} is not in period {}",
+                beacon_slot, period
+            )));
+        }
+
+        let committee = &update["next_sync_committee"];
+        let pubkeys = committee["pubkeys"]
+            .as_array()
+            .ok_or_else(|| SyncCommitteeError::LightClient("missing next_sync_committee.pubkeys".to_string()))?
+            .iter()
+            .map(|pubkey| parse_hex::<48>("next_sync_committee.pubkeys", pubkey.as_str().unwrap_or_default()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let branch = update["next_sync_committee_branch"]
+            .as_array()
+            .ok_or_else(|| SyncCommitteeError::LightClient("missing next_sync_committee_branch".to_string()))?
+            .iter()
+            .map(|node| parse_hex::<32>("next_sync_committee_branch", node.as_str().unwrap_or_default()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let depth = next_sync_committee_depth(beacon_slot);
+        if branch.len() != depth {
+            return Err(SyncCommitteeError::LightClient(format!(
+                "next_sync_committee_branch has {} nodes, expected {} at slot {}",
+                branch.len(),
+                depth,
+                beacon_slot
+            )));
+        }
+
+        let data = SyncCommitteeData {
+            beacon_slot,
+            next_sync_committee_branch: branch,
+            next_aggregate_sync_committee: parse_json_hex(committee, "aggregate_pubkey")?,
+            committee_keys_root: compute_committee_keys_root(pubkeys.iter().map(|pubkey| &pubkey.0)),
+        };
+
+        let computed = data.compute_state_root();
+        if computed != state_root {
+            return Err(SyncCommitteeError::StateRootMismatch {
+                slot: beacon_slot,
+                expected: state_root,
+                computed,
+            });
+        }
+
+        Ok((data, state_root))
+    }
+
+    /// Computes the state root by hashing the committee keys root and the aggregate pubkey up
+    /// the branch, at the index of `next_sync_committee` in the fork of `beacon_slot`
     pub fn compute_state_root(&self) -> FixedBytes<32> {
         let mut padded_aggregate = vec![0u8; 64];
         padded_aggregate[..48].copy_from_slice(&self.next_aggregate_sync_committee[..]);
@@ -55,7 +144,11 @@ impl SyncCommitteeData {
         leaf_data[32..64].copy_from_slice(aggregate_root.as_slice());
         let leaf = FixedBytes::from_slice(&Sha256::digest(leaf_data));
 
-        crate::utils::merkle::sha256::hash_path(self.next_sync_committee_branch.clone(), leaf, 55)
+        crate::utils::merkle::sha256::hash_path(
+            self.next_sync_committee_branch.clone(),
+            leaf,
+            next_sync_committee_gindex(self.beacon_slot),
+        )
     }
 }
 
@@ -89,9 +182,89 @@ impl SyncCommitteeValidatorPubs {
     pub fn get_committee_hash(&self) -> FixedBytes<32> {
         get_committee_hash(self.aggregate_pub)
     }
+
+    /// Computes the SSZ root of the committee public keys, as proven in `SyncCommitteeData`
+    ///
+    /// # Returns
+    /// * `FixedBytes<32>` - Hash tree root of the compressed public keys
+    pub fn committee_keys_root(&self) -> FixedBytes<32> {
+        let pubkeys: Vec<[u8; 48]> = self
+            .validator_pubs
+            .iter()
+            .map(|pubkey| pubkey.to_compressed())
+            .collect();
+        compute_committee_keys_root(pubkeys.iter())
+    }
+
+    /// Checks the committee against a proven sync committee update
+    ///
+    /// # Returns
+    /// * `Result<(), String>` - A description of the first mismatch
+    pub fn verify_against(&self, proven: &SyncCommitteeData) -> Result<(), String> {
+        let aggregate = FixedBytes::<48>::from(self.aggregate_pub.to_compressed());
+        if aggregate != proven.next_aggregate_sync_committee {
+            return Err(format!(
+                "aggregate pubkey {} does not match proven {}",
+                aggregate, proven.next_aggregate_sync_committee
+            ));
+        }
+        let keys_root = self.committee_keys_root();
+        if keys_root != proven.committee_keys_root {
+            return Err(format!(
+                "committee keys root {} does not match proven {}",
+                keys_root, proven.committee_keys_root
+            ));
+        }
+        Ok(())
+    }
+
+    /// Builds the cache entry for this committee
+    ///
+    /// # Arguments
+    /// * `network` - Network the committee belongs to
+    /// * `period` - Sync committee period
+    /// * `validated` - Whether the committee was checked against a proven update
+    pub fn to_cached(&self, network: Network, period: u64, validated: bool) -> SyncCommittee {
+        SyncCommittee {
+            network,
+            period,
+            pubkeys: self
+                .validator_pubs
+                .iter()
+                .map(|pubkey| FixedBytes::from(pubkey.to_compressed()))
+                .collect(),
+            aggregate_pubkey: FixedBytes::from(self.aggregate_pub.to_compressed()),
+            committee_keys_root: self.committee_keys_root(),
+            committee_hash: self.get_committee_hash(),
+            validated,
+        }
+    }
 }
 
-impl From<Vec<String>> for SyncCommitteeValidatorPubs {
+impl TryFrom<&SyncCommittee> for SyncCommitteeValidatorPubs {
+    type Error = PointError;
+
+    /// Restores the validator public keys of a cached committee
+    fn try_from(committee: &SyncCommittee) -> Result<Self, Self::Error> {
+        let validator_pubs = committee
+            .pubkeys
+            .iter()
+            .enumerate()
+            .map(|(index, pubkey)| decode_compressed_pubkey(index, &pubkey.0))
+            .collect::<Result<Vec<_>, _>>()?;
+        let aggregate_pub = Option::from(G1Affine::from_compressed(&committee.aggregate_pubkey.0))
+            .ok_or(PointError::NotOnCurve("G1"))?;
+
+        Ok(Self {
+            validator_pubs,
+            aggregate_pub,
+        })
+    }
+}
+
+impl TryFrom<Vec<String>> for SyncCommitteeValidatorPubs {
+    type Error = PointError;
+
     /// Converts a vector of hex-encoded public key strings into `SyncCommitteeValidatorPubs`.
     ///
     /// # Arguments
@@ -100,17 +273,14 @@ impl From<Vec<String>> for SyncCommitteeValidatorPubs {
     ///
     /// # Returns
     ///
-    /// A new `SyncCommitteeValidatorPubs` instance with parsed public keys.
-    fn from(validator_pubs: Vec<String>) -> Self {
+    /// A new `SyncCommitteeValidatorPubs` instance with parsed public keys, or the index of
+    /// the first key that could not be decoded.
+    fn try_from(validator_pubs: Vec<String>) -> Result<Self, Self::Error> {
         let validator_pubs = validator_pubs
             .iter()
-            .map(|s| {
-                let mut bytes = [0u8; 48];
-                let hex_str = s.trim_start_matches("0x");
-                hex::decode_to_slice(hex_str, &mut bytes).unwrap();
-                G1Affine::from_compressed(&bytes).unwrap()
-            })
-            .collect::<Vec<_>>();
+            .enumerate()
+            .map(|(index, pubkey)| decode_validator_pubkey(index, pubkey))
+            .collect::<Result<Vec<_>, _>>()?;
 
         // Aggregate all public keys into a single G1Projective point
         let aggregate_pub = validator_pubs
@@ -118,13 +288,62 @@ impl From<Vec<String>> for SyncCommitteeValidatorPubs {
             .fold(G1Projective::identity(), |acc, pubkey| {
                 acc.add_mixed(pubkey)
             });
-        Self {
+        Ok(Self {
             validator_pubs,
             aggregate_pub: aggregate_pub.into(),
-        }
+        })
+    }
+}
+
+/// First slot of Electra in the fork schedule of the committee update circuit, which uses the
+/// Sepolia schedule (`cairo/src/domain.cairo`)
+const ELECTRA_ACTIVATION_SLOT: u64 = 7118848;
+
+/// Generalized index of `next_sync_committee` in the beacon state at `slot`, as chosen in
+/// `cairo/src/committee_update.cairo`. Electra grew the state to more than 32 fields, adding a
+/// level to the tree.
+fn next_sync_committee_gindex(slot: u64) -> u64 {
+    if slot >= ELECTRA_ACTIVATION_SLOT {
+        87
+    } else {
+        55
     }
 }
 
+/// Depth of the `next_sync_committee` branch in the beacon state at `slot`
+fn next_sync_committee_depth(slot: u64) -> usize {
+    next_sync_committee_gindex(slot).ilog2() as usize
+}
+
+/// Computes the SSZ root of a list of compressed public keys
+fn compute_committee_keys_root<'a>(pubkeys: impl Iterator<Item = &'a [u8; 48]>) -> FixedBytes<32> {
+    let leaves = pubkeys
+        .map(|pubkey| {
+            // A 48 byte key is merkleized as two 32 byte chunks
+            let mut chunks = [0u8; 64];
+            chunks[..48].copy_from_slice(pubkey);
+            FixedBytes::from_slice(&Sha256::digest(chunks))
+        })
+        .collect();
+    merkle::sha256::compute_root(leaves)
+}
+
+fn json_str<'a>(value: &'a serde_json::Value, field: &str) -> Result<&'a str, SyncCommitteeError> {
+    value[field]
+        .as_str()
+        .ok_or_else(|| SyncCommitteeError::LightClient(format!("missing field {}", field)))
+}
+
+fn parse_json_hex<const N: usize>(value: &serde_json::Value, field: &str) -> Result<FixedBytes<N>, SyncCommitteeError> {
+    parse_hex(field, json_str(value, field)?)
+}
+
+fn parse_hex<const N: usize>(field: &str, value: &str) -> Result<FixedBytes<N>, SyncCommitteeError> {
+    value
+        .parse()
+        .map_err(|e| SyncCommitteeError::LightClient(format!("invalid {} {}: {}", field, value, e)))
+}
+
 /// Possible errors that can occur during sync committee operations
 #[derive(Debug, Error)]
 pub enum SyncCommitteeError {
@@ -134,6 +353,16 @@ pub enum SyncCommitteeError {
     /// Error processing beacon state proof
     #[error("Beacon state proof error")]
     BeaconStateProof(beacon_state_proof::error::Error),
+    /// Malformed light client update
+    #[error("Invalid light client update: {0}")]
+    LightClient(String),
+    /// The committee branch does not prove the attested state root
+    #[error("Sync committee branch at slot {slot} computes state root {computed}, expected {expected}")]
+    StateRootMismatch {
+        slot: u64,
+        expected: FixedBytes<32>,
+        computed: FixedBytes<32>,
+    },
 }
 
 impl From<beacon_state_proof::error::Error> for SyncCommitteeError {
@@ -141,3 +370,66 @@ impl From<beacon_state_proof::error::Error> for SyncCommitteeError {
         SyncCommitteeError::BeaconStateProof(error)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use beacon_types::{
+        FixedVector, MainnetEthSpec, PublicKeyBytes, SyncCommittee as BeaconSyncCommittee, TreeHash,
+    };
+    use bls12_381::Scalar;
+
+    use super::*;
+
+    fn validator_pubs() -> SyncCommitteeValidatorPubs {
+        let pubkeys: Vec<String> = (1..=512u64)
+            .map(|index| {
+                let point = G1Affine::from(G1Affine::generator() * Scalar::from(index));
+                format!("0x{}", hex::encode(point.to_compressed()))
+            })
+            .collect();
+        pubkeys.try_into().unwrap()
+    }
+
+    fn beacon_committee(validator_pubs: &SyncCommitteeValidatorPubs) -> BeaconSyncCommittee<MainnetEthSpec> {
+        let key = |point: &G1Affine| PublicKeyBytes::deserialize(&point.to_compressed()).unwrap();
+        BeaconSyncCommittee {
+            pubkeys: FixedVector::new(validator_pubs.validator_pubs.iter().map(key).collect()).unwrap(),
+            aggregate_pubkey: key(&validator_pubs.aggregate_pub),
+        }
+    }
+
+    #[test]
+    fn committee_keys_root_matches_ssz() {
+        let validator_pubs = validator_pubs();
+        let committee = beacon_committee(&validator_pubs);
+
+        assert_eq!(validator_pubs.committee_keys_root(), committee.pubkeys.tree_hash_root());
+        assert_eq!(
+            validator_pubs.to_cached(Network::default(), 1, false).committee_keys_root,
+            committee.pubkeys.tree_hash_root()
+        );
+    }
+
+    #[test]
+    fn committee_leaf_matches_ssz() {
+        let validator_pubs = validator_pubs();
+        let committee = beacon_committee(&validator_pubs);
+        // Without a branch the computed root is the sync committee leaf itself
+        let data = SyncCommitteeData {
+            beacon_slot: 0,
+            next_sync_committee_branch: Vec::new(),
+            next_aggregate_sync_committee: FixedBytes::from(validator_pubs.aggregate_pub.to_compressed()),
+            committee_keys_root: validator_pubs.committee_keys_root(),
+        };
+
+        assert_eq!(data.compute_state_root(), committee.tree_hash_root());
+    }
+
+    #[test]
+    fn gindex_follows_the_fork() {
+        assert_eq!(next_sync_committee_gindex(ELECTRA_ACTIVATION_SLOT - 1), 55);
+        assert_eq!(next_sync_committee_depth(ELECTRA_ACTIVATION_SLOT - 1), 5);
+        assert_eq!(next_sync_committee_gindex(ELECTRA_ACTIVATION_SLOT), 87);
+        assert_eq!(next_sync_committee_depth(ELECTRA_ACTIVATION_SLOT), 6);
+    }
+}
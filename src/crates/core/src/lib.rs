@@ -1,43 +1,70 @@
-use std::env;
+use thiserror::Error;
 
-use crate::{clients::{atlantic::AtlanticClient, beacon_chain::BeaconRpcClient}, utils::config::BankaiConfig, db::Database};
+use crate::{clients::{atlantic::AtlanticClient, beacon_chain::BeaconRpcClient}, utils::config::{BankaiConfig, ConfigError}, db::{Database, DatabaseError}};
 
 pub mod fetcher;
 pub mod conversion;
 pub mod clients;
 pub mod utils;
 pub mod db;
-use dotenv::from_filename;
+pub mod metrics;
+pub mod prover;
+pub mod api;
 
 
 #[derive(Debug)]
 pub struct BankaiClient {
     pub client: BeaconRpcClient,
-    // pub config: BankaiConfig,
+    pub config: BankaiConfig,
     pub db: Database,
     pub atlantic_client: AtlanticClient,
 }
 
 impl BankaiClient {
-    pub async fn new(is_docker: bool) -> Self {
-        let config = if is_docker {
-            BankaiConfig::docker_config()
-        } else {
-            from_filename(".env.sepolia").ok();
-            BankaiConfig::default()
-        };
-
-        let db = Database::new(&config.database_url).await
-            .expect("Failed to initialize database");
-
-        Self {
-            client: BeaconRpcClient::new(env::var("BEACON_RPC_URL").unwrap(), config.clone()),
-            atlantic_client: AtlanticClient::new(
-                config.atlantic_endpoint.clone(),
-                env::var("ATLANTIC_API_KEY").unwrap(),
-            ),
+    /// Creates the clients and connects to the database described by `config`
+    ///
+    /// # Arguments
+    /// * `config` - The Bankai configuration, validated before use
+    ///
+    /// # Returns
+    /// * `Result<Self, BankaiError>` - The initialized client or error
+    pub async fn new(config: BankaiConfig) -> Result<Self, BankaiError> {
+        config.validate()?;
+        Self::connect(config).await
+    }
+
+    /// Creates the client for commands that work on the database only, without requiring the
+    /// beacon node URL or the Atlantic API key, see `BankaiConfig::validate_local`
+    ///
+    /// # Arguments
+    /// * `config` - The Bankai configuration, validated before use
+    ///
+    /// # Returns
+    /// * `Result<Self, BankaiError>` - The initialized client or error
+    pub async fn new_local(config: BankaiConfig) -> Result<Self, BankaiError> {
+        config.validate_local()?;
+        Self::connect(config).await
+    }
+
+    async fn connect(config: BankaiConfig) -> Result<Self, BankaiError> {
+        let db = Database::new(&config.database.url, config.database.proof_store_dir.as_deref()).await?;
+
+        Ok(Self {
+            client: BeaconRpcClient::new(config.beacon.rpc_url.clone(), config.clone()),
+            atlantic_client: AtlanticClient::new(config.atlantic.clone()),
             db,
-            // config,
-        }
+            config,
+        })
     }
-}
\ No newline at end of file
+}
+
+/// Possible errors that can occur while initializing the Bankai client
+#[derive(Debug, Error)]
+pub enum BankaiError {
+    /// Invalid configuration
+    #[error("Configuration error: {0}")]
+    Config(#[from] ConfigError),
+    /// The database could not be initialized
+    #[error("Database error: {0}")]
+    Database(#[from] DatabaseError),
+}
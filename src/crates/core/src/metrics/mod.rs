@@ -0,0 +1,209 @@
+//! Prometheus Metrics Module
+//!
+//! This module owns the process-wide metrics registry for Bankai. It tracks beacon RPC latency
+//! and errors, proving throughput, Cairo execution size, Atlantic timings and the state of the
+//! in-flight epoch update, and can serve everything in the Prometheus text format on `/metrics`.
+
+use std::{net::SocketAddr, sync::LazyLock};
+
+use axum::{
+    http::{header, StatusCode},
+    routing::get,
+    Router,
+};
+use prometheus::{
+    exponential_buckets, Encoder, Histogram, HistogramOpts, HistogramVec, IntCounter,
+    IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use thiserror::Error;
+use tracing::info;
+
+use crate::db::Status;
+
+static METRICS: LazyLock<Metrics> =
+    LazyLock::new(|| Metrics::new().expect("Failed to register Bankai metrics"));
+
+/// Returns the global metrics instance, registering all collectors on first use.
+pub fn metrics() -> &'static Metrics {
+    &METRICS
+}
+
+/// Collection of all metrics exported by Bankai.
+#[derive(Debug)]
+pub struct Metrics {
+    registry: Registry,
+    /// Latency of beacon RPC requests, labeled by normalized route
+    pub beacon_rpc_duration_seconds: HistogramVec,
+    /// Number of failed beacon RPC requests, labeled by normalized route
+    pub beacon_rpc_errors_total: IntCounterVec,
+    /// Number of epoch updates whose proof was fetched and stored
+    pub epochs_proven_total: IntCounter,
+    /// Number of epoch updates that included a sync committee update
+    pub committee_updates_total: IntCounter,
+    /// Number of Cairo steps per generated PIE
+    pub cairo_steps: Histogram,
+    /// Time an Atlantic query spent waiting before proving started
+    pub atlantic_queue_seconds: Histogram,
+    /// Time from Atlantic submission until the query reached `DONE`
+    pub atlantic_proving_seconds: Histogram,
+    /// Slots between the beacon chain head and the latest proven slot
+    pub proving_lag_slots: IntGauge,
+    /// Status of the in-flight epoch update, one-hot encoded by status label
+    pub epoch_update_status: IntGaugeVec,
+}
+
+impl Metrics {
+    fn new() -> Result<Self, MetricsError> {
+        let registry = Registry::new_custom(Some("bankai".to_string()), None)?;
+
+        let beacon_rpc_duration_seconds = HistogramVec::new(
+            HistogramOpts::new(
+                "beacon_rpc_duration_seconds",
+                "Latency of beacon RPC requests",
+            ),
+            &["route"],
+        )?;
+        let beacon_rpc_errors_total = IntCounterVec::new(
+            Opts::new("beacon_rpc_errors_total", "Failed beacon RPC requests"),
+            &["route"],
+        )?;
+        let epochs_proven_total =
+            IntCounter::new("epochs_proven_total", "Epoch updates with a stored proof")?;
+        let committee_updates_total = IntCounter::new(
+            "committee_updates_total",
+            "Epoch updates including a sync committee update",
+        )?;
+        let cairo_steps = Histogram::with_opts(
+            HistogramOpts::new("cairo_steps", "Cairo steps per generated PIE")
+                .buckets(exponential_buckets(100_000.0, 2.0, 10)?),
+        )?;
+        let atlantic_queue_seconds = Histogram::with_opts(
+            HistogramOpts::new(
+                "atlantic_queue_seconds",
+                "Time an Atlantic query waited before proving started",
+            )
+            .buckets(exponential_buckets(5.0, 2.0, 10)?),
+        )?;
+        let atlantic_proving_seconds = Histogram::with_opts(
+            HistogramOpts::new(
+                "atlantic_proving_seconds",
+                "Time from Atlantic submission until the query is done",
+            )
+            .buckets(exponential_buckets(30.0, 2.0, 10)?),
+        )?;
+        let proving_lag_slots = IntGauge::new(
+            "proving_lag_slots",
+            "Slots between the beacon head and the latest proven slot",
+        )?;
+        let epoch_update_status = IntGaugeVec::new(
+            Opts::new(
+                "epoch_update_status",
+                "Status of the in-flight epoch update",
+            ),
+            &["status"],
+        )?;
+
+        registry.register(Box::new(beacon_rpc_duration_seconds.clone()))?;
+        registry.register(Box::new(beacon_rpc_errors_total.clone()))?;
+        registry.register(Box::new(epochs_proven_total.clone()))?;
+        registry.register(Box::new(committee_updates_total.clone()))?;
+        registry.register(Box::new(cairo_steps.clone()))?;
+        registry.register(Box::new(atlantic_queue_seconds.clone()))?;
+        registry.register(Box::new(atlantic_proving_seconds.clone()))?;
+        registry.register(Box::new(proving_lag_slots.clone()))?;
+        registry.register(Box::new(epoch_update_status.clone()))?;
+
+        Ok(Self {
+            registry,
+            beacon_rpc_duration_seconds,
+            beacon_rpc_errors_total,
+            epochs_proven_total,
+            committee_updates_total,
+            cairo_steps,
+            atlantic_queue_seconds,
+            atlantic_proving_seconds,
+            proving_lag_slots,
+            epoch_update_status,
+        })
+    }
+
+    /// Marks `status` as the current status of the in-flight epoch update
+    pub fn set_status(&self, status: &Status) {
+        for candidate in Status::ALL {
+            let value = if candidate == status { 1 } else { 0 };
+            self.epoch_update_status
+                .with_label_values(&[candidate.as_str()])
+                .set(value);
+        }
+    }
+
+    /// Encodes all registered metrics in the Prometheus text format
+    pub fn encode(&self) -> Result<String, MetricsError> {
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        Ok(String::from_utf8_lossy(&buffer).into_owned())
+    }
+}
+
+/// Collapses a beacon API route into a low-cardinality label.
+///
+/// Query strings are dropped and path segments holding slots, block roots or
+/// validator indices are replaced by `{id}`, e.g. `eth/v1/beacon/headers/123` becomes
+/// `eth/v1/beacon/headers/{id}`.
+pub fn normalize_route(route: &str) -> String {
+    let path = route.split('?').next().unwrap_or(route);
+    path.split('/')
+        .map(|segment| {
+            let is_id = segment.chars().all(|c| c.is_ascii_digit()) || segment.starts_with("0x");
+            if is_id && !segment.is_empty() {
+                "{id}"
+            } else {
+                segment
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Serves the metrics registry on `0.0.0.0:{port}/metrics` until the process exits
+///
+/// # Arguments
+/// * `port` - Port to listen on
+/// * `routes` - Additional routes served next to `/metrics`, e.g. `api::router`
+pub async fn serve(port: u16, routes: Router) -> Result<(), MetricsError> {
+    let app = Router::new().route(
+        "/metrics",
+        get(|| async {
+            match metrics().encode() {
+                Ok(body) => (
+                    StatusCode::OK,
+                    [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+                    body,
+                ),
+                Err(e) => (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    [(header::CONTENT_TYPE, "text/plain")],
+                    format!("Failed to encode metrics: {}", e),
+                ),
+            }
+        }),
+    )
+    .merge(routes);
+
+    let addr = SocketAddr::from(([0, 0, 0, 0], port));
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    info!("📈 Serving metrics on http://{}/metrics", addr);
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+/// Possible errors that can occur while registering or serving metrics
+#[derive(Debug, Error)]
+pub enum MetricsError {
+    /// Prometheus registry error
+    #[error("Prometheus error: {0}")]
+    Prometheus(#[from] prometheus::Error),
+    /// IO error while binding or serving the endpoint
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
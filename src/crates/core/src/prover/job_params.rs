@@ -0,0 +1,187 @@
+//! Atlantic Job Parameters
+//!
+//! Atlantic provisions the prover by the declared job size and proves the PIE in the given
+//! layout. Both are derived from the execution resources of the Cairo run: the size from the
+//! step count and the thresholds in `atlantic.job_sizing`, the layout as the first configured
+//! candidate that has every builtin the run used and that the next update can verify.
+
+use cairo_vm::{types::builtin_name::BuiltinName, vm::runners::cairo_runner::ExecutionResources};
+
+use crate::{
+    clients::atlantic::{JobParams, JobSize},
+    utils::config::JobSizingConfig,
+};
+
+use super::ProverError;
+
+/// Layouts the Stone verifier of the recursive update can verify, see `cairo/src/verify_stone.cairo`.
+/// Every proof is verified by the following update, so no other layout is submitted.
+pub const VERIFIABLE_LAYOUTS: &[&str] = &["dynamic"];
+
+/// Returns the builtins of a Stone layout, or `None` if the layout is unknown
+fn layout_builtins(layout: &str) -> Option<&'static [BuiltinName]> {
+    use BuiltinName::*;
+    Some(match layout {
+        "plain" => &[output],
+        "small" => &[output, pedersen, range_check, ecdsa],
+        "recursive" => &[output, pedersen, range_check, bitwise],
+        "recursive_with_poseidon" => &[output, pedersen, range_check, bitwise, poseidon],
+        "starknet" => &[output, pedersen, range_check, ecdsa, bitwise, ec_op, poseidon],
+        "starknet_with_keccak" => &[
+            output, pedersen, range_check, ecdsa, bitwise, ec_op, keccak, poseidon,
+        ],
+        "dynamic" | "all_cairo" => &[
+            output, pedersen, range_check, ecdsa, bitwise, ec_op, keccak, poseidon,
+            range_check96, add_mod, mul_mod,
+        ],
+        _ => return None,
+    })
+}
+
+/// Derives the declared job size and layout of a run.
+///
+/// # Arguments
+/// * `resources` - Execution resources of the Cairo run
+/// * `config` - Step thresholds and candidate layouts
+///
+/// # Returns
+/// * `Result<JobParams, ProverError>` - The parameters and the reasons they were chosen
+pub fn job_params(
+    resources: &ExecutionResources,
+    config: &JobSizingConfig,
+) -> Result<JobParams, ProverError> {
+    let n_steps = resources.n_steps;
+    let (declared_job_size, limit) = if n_steps <= config.xs_max_steps {
+        (JobSize::XS, format!("at most {} (xs_max_steps)", config.xs_max_steps))
+    } else if n_steps <= config.s_max_steps {
+        (JobSize::S, format!("at most {} (s_max_steps)", config.s_max_steps))
+    } else if n_steps <= config.m_max_steps {
+        (JobSize::M, format!("at most {} (m_max_steps)", config.m_max_steps))
+    } else {
+        (JobSize::L, format!("more than {} (m_max_steps)", config.m_max_steps))
+    };
+    let mut reasons = vec![format!("{} steps is {}", n_steps, limit)];
+
+    // The segment arena is not a layout builtin
+    let mut used: Vec<BuiltinName> = resources
+        .builtin_instance_counter
+        .iter()
+        .filter(|(name, count)| **count > 0 && **name != BuiltinName::segment_arena)
+        .map(|(name, _)| *name)
+        .collect();
+    used.sort_by_key(|name| name.to_str());
+
+    let mut layout = None;
+    for candidate in &config.layouts {
+        let builtins = layout_builtins(candidate).ok_or_else(|| {
+            ProverError::Config(format!("Unknown layout {} in atlantic.job_sizing.layouts", candidate))
+        })?;
+        if !VERIFIABLE_LAYOUTS.contains(&candidate.as_str()) {
+            reasons.push(format!("{} can not be verified by the recursive update", candidate));
+            continue;
+        }
+        let missing: Vec<&str> = used
+            .iter()
+            .filter(|name| !builtins.contains(name))
+            .map(|name| name.to_str())
+            .collect();
+        if missing.is_empty() {
+            reasons.push(format!("{} has every used builtin", candidate));
+            layout = Some(candidate.clone());
+            break;
+        }
+        reasons.push(format!("{} lacks {}", candidate, missing.join(", ")));
+    }
+    let layout = layout.ok_or_else(|| {
+        ProverError::Config(format!(
+            "No layout in atlantic.job_sizing.layouts supports the used builtins and can be verified: {}",
+            reasons[1..].join("; ")
+        ))
+    })?;
+
+    Ok(JobParams {
+        declared_job_size,
+        layout,
+        n_steps,
+        builtins: used.iter().map(|name| name.to_str().to_string()).collect(),
+        reasons,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn resources(n_steps: usize, builtins: &[(BuiltinName, usize)]) -> ExecutionResources {
+        ExecutionResources {
+            n_steps,
+            n_memory_holes: 0,
+            builtin_instance_counter: builtins.iter().copied().collect::<HashMap<_, _>>(),
+        }
+    }
+
+    fn size(n_steps: usize) -> JobSize {
+        job_params(&resources(n_steps, &[]), &JobSizingConfig::default())
+            .unwrap()
+            .declared_job_size
+    }
+
+    #[test]
+    fn job_size_follows_step_thresholds() {
+        let config = JobSizingConfig::default();
+        assert_eq!(size(1), JobSize::XS);
+        assert_eq!(size(config.xs_max_steps), JobSize::XS);
+        assert_eq!(size(config.xs_max_steps + 1), JobSize::S);
+        assert_eq!(size(config.s_max_steps), JobSize::S);
+        assert_eq!(size(config.s_max_steps + 1), JobSize::M);
+        assert_eq!(size(config.m_max_steps), JobSize::M);
+        assert_eq!(size(config.m_max_steps + 1), JobSize::L);
+    }
+
+    #[test]
+    fn default_layout_is_verifiable() {
+        let run = resources(
+            3_000_000,
+            &[
+                (BuiltinName::range_check, 10),
+                (BuiltinName::bitwise, 4),
+                (BuiltinName::poseidon, 2),
+                (BuiltinName::segment_arena, 1),
+            ],
+        );
+        let params = job_params(&run, &JobSizingConfig::default()).unwrap();
+        assert_eq!(params.layout, "dynamic");
+        assert_eq!(params.builtins, vec!["bitwise", "poseidon", "range_check"]);
+        assert!(VERIFIABLE_LAYOUTS.contains(&params.layout.as_str()));
+    }
+
+    #[test]
+    fn skips_layouts_the_recursive_update_can_not_verify() {
+        let config = JobSizingConfig {
+            layouts: vec!["recursive".to_string(), "dynamic".to_string()],
+            ..Default::default()
+        };
+        let params = job_params(&resources(100, &[(BuiltinName::range_check, 1)]), &config).unwrap();
+        assert_eq!(params.layout, "dynamic");
+        assert!(params.reasons.iter().any(|r| r == "recursive can not be verified by the recursive update"));
+
+        let config = JobSizingConfig {
+            layouts: vec!["recursive".to_string()],
+            ..Default::default()
+        };
+        let result = job_params(&resources(100, &[(BuiltinName::range_check, 1)]), &config);
+        assert!(matches!(result, Err(ProverError::Config(_))), "{:?}", result);
+    }
+
+    #[test]
+    fn rejects_unknown_layouts() {
+        let config = JobSizingConfig {
+            layouts: vec!["no_such_layout".to_string()],
+            ..Default::default()
+        };
+        let result = job_params(&resources(100, &[]), &config);
+        assert!(matches!(result, Err(ProverError::Config(_))), "{:?}", result);
+    }
+}
@@ -0,0 +1,474 @@
+//! Epoch Update Proving Pipeline
+//!
+//! This module drives an epoch update from its inputs to a submitted Atlantic query: trace
+//! generation with the Cairo runner, submission to the prover and the bookkeeping of status and
+//! proving attempts in the database. It also implements retrying of failed epoch updates and,
+//! in `wrap`, the Starknet verification of finished proofs.
+
+pub mod job_params;
+pub mod verify;
+pub mod wrap;
+
+use verify::EpochFact;
+
+use std::{
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use cairo_vm::{vm::runners::cairo_pie::CairoPie, Felt252};
+use serde_json::Value;
+use thiserror::Error;
+use tracing::{debug, error, info, warn};
+
+use crate::{
+    clients::atlantic::{
+        observe_phase, pie_zip, AtlanticError, AtlanticQuery, AtlanticStatus, JobParams,
+    },
+    db::{EpochUpdate, EpochUpdateFilter, Status},
+    fetcher::recursive_epoch_input::{
+        EpochUpdateError, RecursiveEpochInputs, RecursiveEpochOutput, RecursiveEpochUpdate,
+    },
+    metrics::metrics,
+    BankaiClient,
+};
+
+/// Identifies an epoch update by UUID or epoch number, e.g. the one to retry
+#[derive(Debug, Clone)]
+pub enum RetryTarget {
+    /// The UUID of the epoch update
+    Uuid(String),
+    /// The epoch number of the epoch update
+    Epoch(u64),
+}
+
+impl From<&str> for RetryTarget {
+    /// Interprets numeric input as an epoch number and anything else as a UUID
+    fn from(value: &str) -> Self {
+        match value.parse::<u64>() {
+            Ok(epoch) => RetryTarget::Epoch(epoch),
+            Err(_) => RetryTarget::Uuid(value.to_string()),
+        }
+    }
+}
+
+/// Generates the PIE for an epoch update and submits it to Atlantic.
+///
+/// A new proving attempt is recorded for `uuid`. On failure the epoch update is marked as
+/// errored and the error is stored with the attempt.
+///
+/// # Arguments
+/// * `bankai` - The Bankai client
+/// * `uuid` - UUID of the epoch update record
+/// * `update` - The epoch update to prove
+/// * `program_path` - Path to the compiled recursive update program
+///
+/// # Returns
+/// * `Result<String, ProverError>` - The Atlantic query ID on success
+pub async fn prove_epoch_update(
+    bankai: &BankaiClient,
+    uuid: &str,
+    update: RecursiveEpochUpdate,
+    program_path: &str,
+) -> Result<String, ProverError> {
+    let epoch = update.inputs.epoch_update.header.slot / crate::utils::constants::SLOTS_PER_EPOCH;
+    let attempt = bankai.db.start_attempt(uuid).await?;
+    info!("🔁 Starting proving attempt {} for epoch update {}", attempt, uuid);
+
+    let result = async {
+        info!("💾 Storing proving inputs...");
+        bankai.db.store_inputs(uuid, &update.inputs).await?;
+
+        info!("🔄 Updating status to TraceGen...");
+        bankai.db.update_status(uuid, Status::TraceGen).await?;
+
+        let (pie, params) = run_epoch_update(bankai, update, program_path)?;
+        bankai.db.update_job_params(uuid, &params).await?;
+
+        info!(
+            "🚀 Submitting proof to Atlantic (size: {}, layout: {})...",
+            params.declared_job_size, params.layout
+        );
+        let pie_zip = pie_zip(&pie)?;
+        if let Some(dir) = &bankai.config.atlantic.pie_archive_dir {
+            archive_pie(dir, uuid, &pie_zip).await;
+        }
+        let atlantic_id = bankai
+            .atlantic_client
+            .submit_stone(pie_zip, &params, format!("epoch_{}", epoch))
+            .await?;
+        info!("✅ Proof submitted to Atlantic with ID: {}", atlantic_id);
+
+        bankai.db.add_atlantic_id(uuid, &atlantic_id).await?;
+        bankai.db.update_status(uuid, Status::Proving).await?;
+        info!("🔄 Status updated to Proving");
+
+        Ok::<String, ProverError>(atlantic_id)
+    }
+    .await;
+
+    if let Err(e) = &result {
+        let error_msg = format!("Epoch update proof generation failed: {}", e);
+        error!("❌ {}", error_msg);
+        if let Err(db_err) = bankai.db.update_error(uuid, &error_msg).await {
+            error!("💥 Failed to update error status in database: {}", db_err);
+        }
+    }
+
+    result
+}
+
+/// Keeps a copy of a submitted PIE as `{dir}/{uuid}.zip`, replacing the one of an earlier attempt.
+/// A failed copy is logged and does not stop the submission.
+async fn archive_pie(dir: &str, uuid: &str, pie_zip: &[u8]) {
+    let path = Path::new(dir).join(format!("{}.zip", uuid));
+    let result = async {
+        tokio::fs::create_dir_all(dir).await?;
+        tokio::fs::write(&path, pie_zip).await
+    }
+    .await;
+    match result {
+        Ok(()) => info!("🗄️  Archived PIE to {}", path.display()),
+        Err(e) => warn!("⚠️  Failed to archive PIE to {}: {}", path.display(), e),
+    }
+}
+
+/// Runs the Cairo program for an epoch update and derives the parameters it would be submitted
+/// with, without submitting it or touching the database.
+///
+/// # Arguments
+/// * `bankai` - The Bankai client
+/// * `update` - The epoch update to prove
+/// * `program_path` - Path to the compiled recursive update program
+///
+/// # Returns
+/// * `Result<JobParams, ProverError>` - The job size and layout and why they were chosen
+pub fn plan_epoch_update(
+    bankai: &BankaiClient,
+    update: RecursiveEpochUpdate,
+    program_path: &str,
+) -> Result<JobParams, ProverError> {
+    let (_, params) = run_epoch_update(bankai, update, program_path)?;
+    Ok(params)
+}
+
+/// Generates the PIE of an epoch update and derives its Atlantic job parameters
+fn run_epoch_update(
+    bankai: &BankaiClient,
+    update: RecursiveEpochUpdate,
+    program_path: &str,
+) -> Result<(CairoPie, JobParams), ProverError> {
+    info!("🧮 Running Cairo program to generate PIE...");
+    let expected_program_hash = bankai
+        .config
+        .program
+        .expected_hash()
+        .map_err(|e| ProverError::Config(e.to_string()))?;
+    let runner_config = bankai
+        .config
+        .program
+        .runner_config(program_path)
+        .map_err(|e| ProverError::Config(e.to_string()))?;
+    let pie = cairo_runner::run(&runner_config, update.into(), expected_program_hash)?;
+    info!("✅ PIE generated successfully");
+    metrics()
+        .cairo_steps
+        .observe(pie.execution_resources.n_steps as f64);
+
+    let params = job_params::job_params(&pie.execution_resources, &bankai.config.atlantic.job_sizing)?;
+    for reason in &params.reasons {
+        debug!("📐 {}", reason);
+    }
+    Ok((pie, params))
+}
+
+/// Checks a proof fetched from Atlantic against the outputs stored with its epoch update.
+///
+/// The recursive update program hash is taken from the configuration, or computed from the
+/// compiled program if no hash is configured.
+///
+/// # Arguments
+/// * `bankai` - The Bankai client
+/// * `proof` - The Stone proof JSON
+/// * `expected` - The outputs stored with the epoch update
+/// * `program_path` - Path to the compiled recursive update program
+///
+/// # Returns
+/// * `Result<(), ProverError>` - `ProverError::Verification` if the proof does not match
+pub fn verify_fetched_proof(
+    bankai: &BankaiClient,
+    proof: &Value,
+    expected: &RecursiveEpochOutput,
+    program_path: &str,
+) -> Result<(), ProverError> {
+    let program_hash = recursive_program_hash(bankai, program_path)?;
+    verify::verify_proof_output(proof, program_hash, expected)?;
+    info!("🔏 Proof public input matches the expected program and outputs");
+    Ok(())
+}
+
+/// Computes the fact the proof of an epoch update registers on Starknet, from its outputs.
+///
+/// # Arguments
+/// * `bankai` - The Bankai client
+/// * `update` - The epoch update
+/// * `program_path` - Path to the compiled recursive update program
+///
+/// # Returns
+/// * `Result<EpochFact, ProverError>` - The program, output and fact hashes
+pub fn epoch_fact(
+    bankai: &BankaiClient,
+    update: &EpochUpdate,
+    program_path: &str,
+) -> Result<EpochFact, ProverError> {
+    let outputs = update.outputs.as_ref().ok_or_else(|| {
+        ProverError::NotFound(format!("Epoch update {} has no stored outputs", update.uuid))
+    })?;
+    let program_hash = recursive_program_hash(bankai, program_path)?;
+    Ok(verify::epoch_fact(program_hash, outputs))
+}
+
+/// Returns the configured recursive update program hash, or computes it from the program
+fn recursive_program_hash(bankai: &BankaiClient, program_path: &str) -> Result<Felt252, ProverError> {
+    let configured_hash = bankai
+        .config
+        .program
+        .expected_hash()
+        .map_err(|e| ProverError::Config(e.to_string()))?;
+    match configured_hash {
+        Some(hash) => Ok(hash),
+        None => {
+            let program = cairo_runner::load_program(&program_path.into())?;
+            Ok(cairo_runner::program_hash::compute_program_hash(&program)?)
+        }
+    }
+}
+
+/// Fetches the Atlantic query of an epoch update and stores its status, step, error and phases.
+///
+/// A phase is recorded each time the query is seen in a new step, timed by Atlantic's
+/// `createdAt` and `completedAt` where it reports them, see `observe_phase`. The Atlantic queue
+/// and proving time metrics are observed when the query leaves `RECEIVED` and when it reaches
+/// `DONE`.
+///
+/// # Arguments
+/// * `bankai` - The Bankai client
+/// * `update` - The epoch update with a submitted Atlantic query
+///
+/// # Returns
+/// * `Result<AtlanticQuery, ProverError>` - The current state of the query
+pub async fn track_atlantic_query(
+    bankai: &BankaiClient,
+    update: &EpochUpdate,
+) -> Result<AtlanticQuery, ProverError> {
+    let atlantic_id = update.atlantic_id.as_deref().ok_or_else(|| {
+        ProverError::NotFound(format!("Epoch update {} has no Atlantic query", update.uuid))
+    })?;
+    let query = bankai.atlantic_client.get_query(atlantic_id).await?;
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let mut phases = update.atlantic_phases.clone();
+    let observed = phases.len();
+    observe_phase(&mut phases, &query, now);
+    if phases.len() > observed && observed > 0 {
+        // Durations are measured from the start of the first phase, i.e. `createdAt`, each
+        // transition is only observed once since the phases are persisted
+        let received = AtlanticStatus::Received.as_str();
+        let first = phases[0].started_at;
+        let started_at = phases[observed].started_at;
+        if phases[..observed].iter().all(|phase| phase.name == received) {
+            metrics()
+                .atlantic_queue_seconds
+                .observe(started_at.saturating_sub(first) as f64);
+        }
+        if query.status == AtlanticStatus::Done {
+            metrics()
+                .atlantic_proving_seconds
+                .observe(started_at.saturating_sub(first) as f64);
+        }
+    }
+    bankai.db.update_atlantic_query(&update.uuid, &query, &phases).await?;
+
+    Ok(query)
+}
+
+/// Looks up an epoch update by UUID or epoch number.
+///
+/// # Arguments
+/// * `bankai` - The Bankai client
+/// * `target` - UUID or epoch number, or `None` for the latest update
+/// * `status` - Only consider updates with this status when looking up by epoch or latest
+///
+/// # Returns
+/// * `Result<EpochUpdate, ProverError>` - The newest matching epoch update
+pub async fn find_epoch_update(
+    bankai: &BankaiClient,
+    target: Option<RetryTarget>,
+    status: Option<Status>,
+) -> Result<EpochUpdate, ProverError> {
+    let epoch = match target {
+        Some(RetryTarget::Uuid(uuid)) => {
+            return bankai
+                .db
+                .get_epoch_update_by_uuid(&uuid)
+                .await?
+                .ok_or_else(|| ProverError::NotFound(format!("No epoch update with UUID {}", uuid)))
+        }
+        Some(RetryTarget::Epoch(epoch)) => Some(epoch),
+        None => None,
+    };
+
+    let filter = EpochUpdateFilter {
+        status: status.clone(),
+        from_epoch: epoch,
+        to_epoch: epoch,
+        error_reason: None,
+    };
+    bankai
+        .db
+        .list_epoch_updates(&filter, 1, 0)
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| {
+            let status = status.map(|s| format!(" {}", s)).unwrap_or_default();
+            match epoch {
+                Some(epoch) => ProverError::NotFound(format!("No{} epoch update for epoch {}", status, epoch)),
+                None => ProverError::NotFound(format!("No{} epoch update found", status)),
+            }
+        })
+}
+
+/// Resets an errored epoch update and resubmits it to the prover.
+///
+/// The inputs stored with the epoch update are reused unless `refetch` is set or none were
+/// stored, in which case they are fetched from the beacon node again. The epoch update keeps
+/// its UUID, so the `slot_number` uniqueness constraint does not get in the way, and every run
+/// is recorded as a separate attempt.
+///
+/// # Arguments
+/// * `bankai` - The Bankai client
+/// * `target` - UUID or epoch number of the errored epoch update
+/// * `refetch` - Ignore stored inputs and fetch them again
+/// * `program_path` - Path to the compiled recursive update program
+///
+/// # Returns
+/// * `Result<String, ProverError>` - The Atlantic query ID of the new attempt
+pub async fn retry_epoch_update(
+    bankai: &BankaiClient,
+    target: RetryTarget,
+    refetch: bool,
+    program_path: &str,
+) -> Result<String, ProverError> {
+    let errored = find_errored_epoch_update(bankai, &target).await?;
+    info!(
+        "🔍 Retrying epoch update - Epoch: {}, Slot: {}, UUID: {}",
+        errored.epoch_number, errored.slot_number, errored.uuid
+    );
+
+    let stored = if refetch {
+        None
+    } else {
+        bankai.db.get_inputs(&errored.uuid).await?
+    };
+
+    let inputs = match stored {
+        Some(inputs) => {
+            info!("♻️  Reusing stored proving inputs");
+            inputs
+        }
+        None => {
+            info!("📥 Fetching proving inputs...");
+            let previous = bankai
+                .db
+                .get_previous_epoch_update(errored.slot_number as u64)
+                .await?;
+            if let Some(previous) = &previous {
+                if previous.status != Status::Done.as_str() {
+                    return Err(ProverError::NotRetryable(format!(
+                        "Previous epoch update {} is not done yet (status: {})",
+                        previous.uuid, previous.status
+                    )));
+                }
+            }
+
+            RecursiveEpochInputs::for_epoch(
+                &bankai.client,
+                &bankai.db,
+                previous,
+                errored.epoch_number as u64,
+            )
+            .await?
+        }
+    };
+    let update = RecursiveEpochUpdate::try_from(inputs).map_err(EpochUpdateError::from)?;
+
+    bankai
+        .db
+        .reset_epoch_update(&errored.uuid, &update.outputs)
+        .await?;
+
+    prove_epoch_update(bankai, &errored.uuid, update, program_path).await
+}
+
+async fn find_errored_epoch_update(
+    bankai: &BankaiClient,
+    target: &RetryTarget,
+) -> Result<EpochUpdate, ProverError> {
+    match target {
+        RetryTarget::Uuid(uuid) => {
+            let update = bankai
+                .db
+                .get_epoch_update_by_uuid(uuid)
+                .await?
+                .ok_or_else(|| ProverError::NotFound(format!("No epoch update with UUID {}", uuid)))?;
+            if update.status != Status::Error.as_str() {
+                return Err(ProverError::NotRetryable(format!(
+                    "Epoch update {} has status {}, only errored updates can be retried",
+                    uuid, update.status
+                )));
+            }
+            Ok(update)
+        }
+        RetryTarget::Epoch(epoch) => bankai
+            .db
+            .get_errored_epoch_update_by_epoch(*epoch)
+            .await?
+            .ok_or_else(|| ProverError::NotFound(format!("No errored epoch update for epoch {}", epoch))),
+    }
+}
+
+/// Possible errors that can occur while proving an epoch update
+#[derive(Debug, Error)]
+pub enum ProverError {
+    /// Database error
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+    /// Error building the epoch update inputs
+    #[error("Epoch update error: {0}")]
+    EpochUpdate(#[from] EpochUpdateError),
+    /// Error running the Cairo program
+    #[error("Cairo runner failed: {0}")]
+    CairoRunner(#[from] cairo_runner::error::Error),
+    /// Error submitting to Atlantic
+    #[error("Atlantic submission failed: {0}")]
+    Atlantic(#[from] AtlanticError),
+    /// The requested epoch update does not exist
+    #[error("Not found: {0}")]
+    NotFound(String),
+    /// The requested epoch update cannot be retried
+    #[error("Not retryable: {0}")]
+    NotRetryable(String),
+    /// Invalid prover configuration
+    #[error("Configuration error: {0}")]
+    Config(String),
+    /// The fetched proof does not prove the expected epoch update
+    #[error("Proof verification failed: {0}")]
+    Verification(#[from] verify::ProofVerificationError),
+    /// Error wrapping a proof for Starknet verification
+    #[error("Wrapping failed: {0}")]
+    Wrap(String),
+}
@@ -0,0 +1,368 @@
+//! Stone Proof Output Verification
+//!
+//! Before a proof fetched from Atlantic is accepted, its public input is checked against the
+//! epoch update it is supposed to prove. This mirrors the checks the next recursive update
+//! performs in Cairo: the proven program must be the bootloader, and the bootloader output must
+//! be the recursive update program hash followed by the stored `RecursiveEpochOutput`.
+//! Catching a mismatch here keeps a wrong proof out of the database instead of failing the
+//! next epoch update halfway through.
+//!
+//! The same program and output hashes determine the fact that Integrity registers on Starknet
+//! once the proof is verified there, so the fact hash of an epoch update can be computed locally.
+
+use std::collections::HashMap;
+
+use cairo_vm::Felt252;
+use serde_json::Value;
+use starknet_crypto::poseidon_hash_many;
+use stone_verifier_hints::OwnedPublicInput;
+use thiserror::Error;
+
+use crate::fetcher::recursive_epoch_input::RecursiveEpochOutput;
+
+/// Program hash of the bootloader that runs the recursive update on Atlantic
+pub const BOOTLOADER_PROGRAM_HASH: &str =
+    "0x5ab580b04e3532b6b18f81cfa654a05e29dd8e2352d88df1e765a84072db07";
+
+/// Number of tasks the bootloader reports in its output
+const BOOTLOADER_N_TASKS: u64 = 1;
+
+/// Values the public input of a proof commits to
+#[derive(Debug, Clone)]
+pub struct ProofPublicOutput {
+    /// Poseidon hash of the proven program
+    pub program_hash: Felt252,
+    /// Poseidon hash of the output segment
+    pub output_hash: Felt252,
+    /// Raw output segment
+    pub output: Vec<Felt252>,
+}
+
+/// Extracts the program hash and the output of a Stone proof from its public input.
+///
+/// The program is the public memory between the start of the `program` segment and the
+/// return frame at the start of the `execution` segment, and the output is the public memory
+/// covered by the `output` segment, exactly as `verify_cairo_proof` reads them.
+///
+/// # Arguments
+/// * `proof` - The Stone proof JSON, as returned by the proof registry
+///
+/// # Returns
+/// * `Result<ProofPublicOutput, ProofVerificationError>` - The hashes and output of the proof
+pub fn extract_public_output(proof: &Value) -> Result<ProofPublicOutput, ProofVerificationError> {
+    let public_input = proof
+        .get("public_input")
+        .ok_or(ProofVerificationError::MissingPublicInput)?;
+    let public_input: OwnedPublicInput = serde_json::from_value(public_input.clone())?;
+
+    let memory: HashMap<usize, Felt252> = public_input
+        .public_memory
+        .iter()
+        .filter_map(|entry| entry.value.map(|value| (entry.address, value)))
+        .collect();
+    let segment = |name: &'static str| {
+        public_input
+            .memory_segments
+            .get(name)
+            .ok_or(ProofVerificationError::MissingSegment(name))
+    };
+    let read_range = |start: usize, end: usize| {
+        (start..end)
+            .map(|address| {
+                memory
+                    .get(&address)
+                    .copied()
+                    .ok_or(ProofVerificationError::MissingMemory(address))
+            })
+            .collect::<Result<Vec<_>, _>>()
+    };
+
+    let initial_pc = segment("program")?.begin_addr;
+    let initial_fp = segment("execution")?.begin_addr;
+    if initial_fp < initial_pc + 2 {
+        return Err(ProofVerificationError::InvalidSegments(format!(
+            "execution segment starts at {} before the end of the program segment at {}",
+            initial_fp, initial_pc
+        )));
+    }
+    let program = read_range(initial_pc, initial_fp - 2)?;
+
+    let output_segment = segment("output")?;
+    if output_segment.stop_ptr < output_segment.begin_addr {
+        return Err(ProofVerificationError::InvalidSegments(format!(
+            "output segment ends at {} before it begins at {}",
+            output_segment.stop_ptr, output_segment.begin_addr
+        )));
+    }
+    let output = read_range(output_segment.begin_addr, output_segment.stop_ptr)?;
+
+    Ok(ProofPublicOutput {
+        program_hash: poseidon_hash_many(&program),
+        output_hash: poseidon_hash_many(&output),
+        output,
+    })
+}
+
+/// Builds the bootloader output expected for a proof of `expected`
+///
+/// # Arguments
+/// * `recursive_program_hash` - Program hash of the recursive update program
+/// * `expected` - The outputs stored with the epoch update
+pub fn expected_bootloader_output(
+    recursive_program_hash: Felt252,
+    expected: &RecursiveEpochOutput,
+) -> Vec<Felt252> {
+    let outputs = expected.to_felts();
+    let mut bootloader_output = vec![
+        Felt252::from(BOOTLOADER_N_TASKS),
+        // The task output size includes its own header
+        Felt252::from(outputs.len() + 2),
+        recursive_program_hash,
+    ];
+    bootloader_output.extend(outputs);
+    bootloader_output
+}
+
+/// Verifies that a Stone proof proves the expected epoch update.
+///
+/// # Arguments
+/// * `proof` - The Stone proof JSON
+/// * `recursive_program_hash` - Program hash of the recursive update program
+/// * `expected` - The outputs stored with the epoch update
+///
+/// # Returns
+/// * `Result<(), ProofVerificationError>` - An error describing the first mismatch
+pub fn verify_proof_output(
+    proof: &Value,
+    recursive_program_hash: Felt252,
+    expected: &RecursiveEpochOutput,
+) -> Result<(), ProofVerificationError> {
+    let public_output = extract_public_output(proof)?;
+
+    let bootloader_hash = bootloader_program_hash();
+    if public_output.program_hash != bootloader_hash {
+        return Err(ProofVerificationError::ProgramHashMismatch {
+            expected: bootloader_hash,
+            actual: public_output.program_hash,
+        });
+    }
+
+    let expected_output = expected_bootloader_output(recursive_program_hash, expected);
+    let expected_hash = poseidon_hash_many(&expected_output);
+    if public_output.output_hash != expected_hash {
+        return Err(ProofVerificationError::OutputHashMismatch {
+            expected: expected_hash,
+            actual: public_output.output_hash,
+            first_difference: first_difference(&expected_output, &public_output.output),
+        });
+    }
+
+    Ok(())
+}
+
+/// Hashes identifying the proof of an epoch update and the fact it registers on Starknet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EpochFact {
+    /// Program hash of the bootloader
+    pub program_hash: Felt252,
+    /// Poseidon hash of the bootloader output
+    pub output_hash: Felt252,
+    /// Fact hash registered by the Integrity verifier
+    pub fact_hash: Felt252,
+}
+
+/// Computes the fact hash Integrity registers for a verified proof.
+///
+/// # Arguments
+/// * `program_hash` - Poseidon hash of the proven program
+/// * `output_hash` - Poseidon hash of its output
+pub fn compute_fact_hash(program_hash: Felt252, output_hash: Felt252) -> Felt252 {
+    poseidon_hash_many(&[program_hash, output_hash])
+}
+
+/// Computes the fact of the proof of an epoch update from its outputs.
+///
+/// This is the output hash `handle_recursive_case` expects from the proof of the previous
+/// update, combined with the bootloader program hash.
+///
+/// # Arguments
+/// * `recursive_program_hash` - Program hash of the recursive update program
+/// * `outputs` - The outputs stored with the epoch update
+pub fn epoch_fact(recursive_program_hash: Felt252, outputs: &RecursiveEpochOutput) -> EpochFact {
+    let program_hash = bootloader_program_hash();
+    let output_hash =
+        poseidon_hash_many(&expected_bootloader_output(recursive_program_hash, outputs));
+    EpochFact {
+        program_hash,
+        output_hash,
+        fact_hash: compute_fact_hash(program_hash, output_hash),
+    }
+}
+
+/// Computes the fact of a Stone proof from its public input.
+///
+/// # Arguments
+/// * `proof` - The Stone proof JSON
+pub fn proof_fact(proof: &Value) -> Result<EpochFact, ProofVerificationError> {
+    let public_output = extract_public_output(proof)?;
+    Ok(EpochFact {
+        program_hash: public_output.program_hash,
+        output_hash: public_output.output_hash,
+        fact_hash: compute_fact_hash(public_output.program_hash, public_output.output_hash),
+    })
+}
+
+fn bootloader_program_hash() -> Felt252 {
+    Felt252::from_hex(BOOTLOADER_PROGRAM_HASH).expect("bootloader program hash constant is valid hex")
+}
+
+/// Describes the first position where the proven output differs from the expected one
+fn first_difference(expected: &[Felt252], actual: &[Felt252]) -> String {
+    if let Some((index, (expected, actual))) = expected
+        .iter()
+        .zip(actual)
+        .enumerate()
+        .find(|(_, (expected, actual))| expected != actual)
+    {
+        return format!(
+            "output[{}] is {:#x}, expected {:#x}",
+            index, actual, expected
+        );
+    }
+    format!(
+        "output has {} values, expected {}",
+        actual.len(),
+        expected.len()
+    )
+}
+
+/// Possible errors that can occur while verifying a proof's public input
+#[derive(Debug, Error)]
+pub enum ProofVerificationError {
+    /// The proof JSON has no `public_input`
+    #[error("Proof has no public_input")]
+    MissingPublicInput,
+    /// The public input could not be parsed
+    #[error("Failed to parse proof public_input: {0}")]
+    InvalidPublicInput(#[from] serde_json::Error),
+    /// A required memory segment is not listed in the public input
+    #[error("Proof public_input has no {0} segment")]
+    MissingSegment(&'static str),
+    /// The memory segments are inconsistent
+    #[error("Invalid memory segments in proof public_input: {0}")]
+    InvalidSegments(String),
+    /// An address of the program or output is not part of the public memory
+    #[error("Address {0} is missing from the proof public memory")]
+    MissingMemory(usize),
+    /// The proof is not a proof of the bootloader
+    #[error("Proof program hash mismatch: expected {expected:#x}, got {actual:#x}")]
+    ProgramHashMismatch { expected: Felt252, actual: Felt252 },
+    /// The proof does not prove the stored epoch update outputs
+    #[error("Proof output hash mismatch: expected {expected:#x}, got {actual:#x} ({first_difference})")]
+    OutputHashMismatch {
+        expected: Felt252,
+        actual: Felt252,
+        first_difference: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::FixedBytes;
+    use serde_json::json;
+
+    use super::*;
+
+    fn outputs() -> RecursiveEpochOutput {
+        RecursiveEpochOutput {
+            beacon_header_root: FixedBytes::from([1u8; 32]),
+            beacon_state_root: FixedBytes::from([2u8; 32]),
+            beacon_height: 7_134_976,
+            n_signers: 480,
+            execution_header_root: FixedBytes::from([3u8; 32]),
+            execution_header_height: 8_000_000,
+            current_committee_hash: FixedBytes::from([4u8; 32]),
+            next_committee_hash: FixedBytes::from([5u8; 32]),
+        }
+    }
+
+    /// A proof whose public memory holds `program` from address 1, followed by the return
+    /// frame, and `output` in the output segment
+    fn proof(program: &[Felt252], output: &[Felt252]) -> Value {
+        let program_end = 1 + program.len();
+        let execution = program_end + 2;
+        let output_begin = execution + 10;
+        let mut public_memory: Vec<Value> = program
+            .iter()
+            .enumerate()
+            .map(|(i, value)| json!({ "address": 1 + i, "value": format!("{:#x}", value), "page": 0 }))
+            .collect();
+        // The return frame is public but not part of the program
+        for address in program_end..execution {
+            public_memory.push(json!({ "address": address, "value": "0x0", "page": 0 }));
+        }
+        public_memory.extend(output.iter().enumerate().map(|(i, value)| {
+            json!({ "address": output_begin + i, "value": format!("{:#x}", value), "page": 0 })
+        }));
+        json!({
+            "public_input": {
+                "layout": "dynamic",
+                "rc_min": 0,
+                "rc_max": 65535,
+                "n_steps": 1024,
+                "memory_segments": {
+                    "program": { "begin_addr": 1, "stop_ptr": program_end },
+                    "execution": { "begin_addr": execution, "stop_ptr": output_begin },
+                    "output": { "begin_addr": output_begin, "stop_ptr": output_begin + output.len() },
+                },
+                "public_memory": public_memory,
+            }
+        })
+    }
+
+    #[test]
+    fn extracts_program_and_output_ranges() {
+        let program = [Felt252::from(10), Felt252::from(11), Felt252::from(12)];
+        let output = [Felt252::from(20), Felt252::from(21)];
+
+        let public_output = extract_public_output(&proof(&program, &output)).unwrap();
+        assert_eq!(public_output.program_hash, poseidon_hash_many(&program));
+        assert_eq!(public_output.output, output.to_vec());
+        assert_eq!(public_output.output_hash, poseidon_hash_many(&output));
+    }
+
+    #[test]
+    fn proof_fact_matches_epoch_fact_for_the_expected_output() {
+        let recursive_program_hash = Felt252::from(0x1234);
+        let output = expected_bootloader_output(recursive_program_hash, &outputs());
+        assert_eq!(output.len(), 16);
+        assert_eq!(output[1], Felt252::from(15));
+
+        let from_proof = proof_fact(&proof(&[Felt252::ONE], &output)).unwrap();
+        let from_outputs = epoch_fact(recursive_program_hash, &outputs());
+        assert_eq!(from_proof.output_hash, from_outputs.output_hash);
+        assert_eq!(
+            compute_fact_hash(from_outputs.program_hash, from_proof.output_hash),
+            from_outputs.fact_hash
+        );
+    }
+
+    #[test]
+    fn rejects_proofs_of_other_programs() {
+        let output = expected_bootloader_output(Felt252::from(0x1234), &outputs());
+        let result = verify_proof_output(&proof(&[Felt252::ONE], &output), Felt252::from(0x1234), &outputs());
+        assert!(
+            matches!(result, Err(ProofVerificationError::ProgramHashMismatch { .. })),
+            "{:?}",
+            result
+        );
+    }
+
+    #[test]
+    fn rejects_incomplete_public_memory() {
+        let mut proof = proof(&[Felt252::ONE], &[Felt252::TWO]);
+        proof["public_input"]["public_memory"].as_array_mut().unwrap().pop();
+        let result = extract_public_output(&proof);
+        assert!(matches!(result, Err(ProofVerificationError::MissingMemory(_))), "{:?}", result);
+    }
+}
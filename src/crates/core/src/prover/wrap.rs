@@ -0,0 +1,186 @@
+//! Starknet Verification of Proven Epoch Updates
+//!
+//! Once an epoch update is done, its Stone proof is wrapped by Atlantic with the
+//! `recursive_with_poseidon` layout and verified on Starknet, which registers a fact for it.
+//! The wrapper query ID, its status and the resulting fact hash are stored with the epoch
+//! update, so an interrupted submission resumes from the stored query instead of submitting
+//! the proof again.
+
+use std::str::FromStr;
+
+use cairo_vm::Felt252;
+use tracing::{error, info};
+
+use crate::{
+    clients::atlantic::{AtlanticError, AtlanticStatus, StarkProof},
+    db::{EpochUpdate, Status, WrapStatus},
+    utils::config::ConfigError,
+    BankaiClient,
+};
+
+use super::{epoch_fact, find_epoch_update, ProverError, RetryTarget};
+
+/// State of the Starknet verification after a wrap step
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WrapOutcome {
+    /// The wrapped proof is still being processed by Atlantic
+    Pending { wrap_atlantic_id: String },
+    /// The proof was verified on Starknet
+    Verified { fact_hash: Option<String> },
+}
+
+/// Finds the done epoch update to wrap.
+///
+/// # Arguments
+/// * `bankai` - The Bankai client
+/// * `target` - UUID or epoch number, or `None` for the latest done epoch update
+///
+/// # Returns
+/// * `Result<EpochUpdate, ProverError>` - The epoch update, if it is done
+pub async fn find_wrap_target(
+    bankai: &BankaiClient,
+    target: Option<RetryTarget>,
+) -> Result<EpochUpdate, ProverError> {
+    let update = find_epoch_update(bankai, target, Some(Status::Done)).await?;
+    if update.status != Status::Done.as_str() {
+        return Err(ProverError::NotRetryable(format!(
+            "Epoch update {} has status {}, only done updates can be verified on Starknet",
+            update.uuid, update.status
+        )));
+    }
+    Ok(update)
+}
+
+/// Submits the proof of a done epoch update for Starknet verification, or resumes a submitted one.
+///
+/// Updates without a wrapped proof are submitted. Every submission is a paid Atlantic query, so
+/// one whose last wrap failed is only submitted again if `resubmit_failed` is set. A submitted
+/// query is checked once, or polled until it finishes if `wait` is set. Failures are stored in
+/// `wrap_error` and leave the epoch update itself done.
+///
+/// # Arguments
+/// * `bankai` - The Bankai client
+/// * `update` - The done epoch update
+/// * `wait` - Poll Atlantic until the verification finishes
+/// * `resubmit_failed` - Submit the proof again if its last wrap failed
+///
+/// # Returns
+/// * `Result<WrapOutcome, ProverError>` - Whether the proof is verified yet
+pub async fn wrap_epoch_update(
+    bankai: &BankaiClient,
+    update: &EpochUpdate,
+    wait: bool,
+    resubmit_failed: bool,
+) -> Result<WrapOutcome, ProverError> {
+    let wrap_status = update
+        .wrap_status
+        .as_deref()
+        .map(WrapStatus::from_str)
+        .transpose()
+        .map_err(ProverError::Wrap)?;
+    if wrap_status == Some(WrapStatus::Error) && !resubmit_failed {
+        return Err(ProverError::NotRetryable(format!(
+            "Wrapped proof of epoch update {} failed: {}. Run wrap to submit it again",
+            update.uuid,
+            update.wrap_error.as_deref().unwrap_or("no reason given")
+        )));
+    }
+
+    let result = async {
+        let wrap_atlantic_id = match (wrap_status, &update.wrap_atlantic_id) {
+            (Some(WrapStatus::Verified), _) => {
+                info!("✅ Epoch update {} is already verified on Starknet", update.uuid);
+                return Ok(WrapOutcome::Verified {
+                    fact_hash: update.fact_hash.clone(),
+                });
+            }
+            (Some(WrapStatus::Submitted), Some(wrap_atlantic_id)) => {
+                info!("♻️  Resuming wrapped proof query {}", wrap_atlantic_id);
+                wrap_atlantic_id.clone()
+            }
+            _ => submit(bankai, update).await?,
+        };
+
+        let done = if wait {
+            // A timeout leaves the query submitted so the next run resumes it
+            match bankai.atlantic_client.wait_for_batch(&wrap_atlantic_id).await {
+                Err(AtlanticError::AtlanticPoolingTimeout(_)) => false,
+                done => done?,
+            }
+        } else {
+            let query = bankai.atlantic_client.get_query(&wrap_atlantic_id).await?;
+            match query.status {
+                AtlanticStatus::Done => true,
+                AtlanticStatus::Failed => {
+                    return Err(ProverError::Wrap(format!(
+                        "Atlantic failed to verify wrapped proof query {}: {}",
+                        wrap_atlantic_id,
+                        query.error_reason.as_deref().unwrap_or("no reason given")
+                    )))
+                }
+                _ => false,
+            }
+        };
+        if !done {
+            info!("⏳ Wrapped proof query {} is still in progress", wrap_atlantic_id);
+            return Ok(WrapOutcome::Pending { wrap_atlantic_id });
+        }
+
+        // The fact registered on Starknet must be the one of this epoch update, which the next
+        // recursive update and the contract rely on
+        let expected = epoch_fact(bankai, update, &bankai.config.program.recursive_update)?.fact_hash;
+        let fact_hash = match bankai.atlantic_client.fetch_fact_hash(&wrap_atlantic_id).await? {
+            Some(reported) if Felt252::from_hex(&reported).ok() != Some(expected) => {
+                return Err(ProverError::Wrap(format!(
+                    "Atlantic registered fact hash {} for epoch {}, expected {:#x}",
+                    reported, update.epoch_number, expected
+                )));
+            }
+            _ => format!("{:#x}", expected),
+        };
+        bankai.db.finish_wrap(&update.uuid, Some(&fact_hash)).await?;
+        info!("🎉 Epoch {} verified on Starknet, fact hash: {}", update.epoch_number, fact_hash);
+        Ok::<_, ProverError>(WrapOutcome::Verified { fact_hash: Some(fact_hash) })
+    }
+    .await;
+
+    if let Err(e) = &result {
+        let error_msg = format!("Starknet verification failed: {}", e);
+        error!("❌ {}", error_msg);
+        if let Err(db_err) = bankai.db.update_wrap_error(&update.uuid, &error_msg).await {
+            error!("💥 Failed to update wrap error in database: {}", db_err);
+        }
+    }
+
+    result
+}
+
+/// Submits the stored proof of `update` to Atlantic and records the wrapper query
+async fn submit(bankai: &BankaiClient, update: &EpochUpdate) -> Result<String, ProverError> {
+    let wrapper = bankai.config.program.wrapper.clone().ok_or_else(|| {
+        ProverError::Config(ConfigError::Missing("program.wrapper (WRAPPER_PROGRAM)").to_string())
+    })?;
+    let proof_id = update.proof_id.ok_or_else(|| {
+        ProverError::NotFound(format!("Epoch update {} has no stored proof", update.uuid))
+    })?;
+    let proof = bankai
+        .db
+        .get_proof(proof_id)
+        .await
+        .map_err(|e| ProverError::Wrap(format!("Failed to load proof {}: {}", proof_id, e)))?
+        .ok_or_else(|| ProverError::NotFound(format!("Proof {} not found", proof_id)))?;
+    let proof = StarkProof {
+        proof: serde_json::from_str(&proof.proof)
+            .map_err(|e| ProverError::Wrap(format!("Stored proof {} is invalid JSON: {}", proof_id, e)))?,
+    };
+
+    info!("🚀 Submitting wrapped proof of epoch {} to Atlantic...", update.epoch_number);
+    let wrap_atlantic_id = bankai
+        .atlantic_client
+        .submit_wrapped_proof(proof, wrapper, format!("epoch_{}", update.epoch_number))
+        .await?;
+    bankai.db.start_wrap(&update.uuid, &wrap_atlantic_id).await?;
+    info!("✅ Wrapped proof submitted with ID: {}", wrap_atlantic_id);
+
+    Ok(wrap_atlantic_id)
+}
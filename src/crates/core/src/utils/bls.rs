@@ -0,0 +1,177 @@
+//! Panic-free decoding of BLS12-381 points.
+//!
+//! Validator keys and curve points come from beacon node providers and from stored JSON, so
+//! every decoding step reports which key or coordinate was malformed instead of panicking.
+
+use bls12_381::{G1Affine, G2Affine};
+use thiserror::Error;
+
+/// Size of a compressed G1 point and of a single field element coordinate
+pub const COORDINATE_SIZE: usize = 48;
+
+/// Decodes a hex encoded, compressed validator public key
+///
+/// # Arguments
+/// * `index` - Position of the key in the committee, used in errors
+/// * `pubkey` - Hex encoded key, with or without `0x` prefix
+///
+/// # Returns
+/// * `Result<G1Affine, PointError>` - The decoded point or error
+pub fn decode_validator_pubkey(index: usize, pubkey: &str) -> Result<G1Affine, PointError> {
+    let hex_str = pubkey.strip_prefix("0x").unwrap_or(pubkey);
+    let mut bytes = [0u8; COORDINATE_SIZE];
+    hex::decode_to_slice(hex_str, &mut bytes)
+        .map_err(|source| PointError::ValidatorHex { index, source })?;
+
+    decode_compressed_pubkey(index, &bytes)
+}
+
+/// Decodes a compressed validator public key
+///
+/// # Arguments
+/// * `index` - Position of the key in the committee, used in errors
+/// * `bytes` - The compressed key
+pub fn decode_compressed_pubkey(index: usize, bytes: &[u8; COORDINATE_SIZE]) -> Result<G1Affine, PointError> {
+    Option::from(G1Affine::from_compressed(bytes)).ok_or(PointError::InvalidValidatorKey { index })
+}
+
+/// Decodes a hex encoded field element coordinate of an uncompressed point
+///
+/// # Arguments
+/// * `coordinate` - Name of the coordinate, used in errors
+/// * `value` - Hex encoded coordinate, with or without `0x` prefix
+///
+/// # Returns
+/// * `Result<[u8; 48], PointError>` - The big-endian coordinate bytes or error
+pub fn decode_coordinate(coordinate: &'static str, value: &str) -> Result<[u8; COORDINATE_SIZE], PointError> {
+    let hex_str = value.strip_prefix("0x").unwrap_or(value);
+    let bytes = hex::decode(hex_str).map_err(|source| PointError::CoordinateHex { coordinate, source })?;
+
+    bytes
+        .try_into()
+        .map_err(|bytes: Vec<u8>| PointError::CoordinateLength {
+            coordinate,
+            len: bytes.len(),
+        })
+}
+
+/// Decodes a compressed G1 point, e.g. an aggregate committee key, checking that it is on the
+/// curve and in the subgroup
+pub fn g1_from_compressed(bytes: &[u8; COORDINATE_SIZE]) -> Result<G1Affine, PointError> {
+    Option::from(G1Affine::from_compressed(bytes)).ok_or(PointError::NotOnCurve("G1"))
+}
+
+/// Decodes an uncompressed G1 point, checking that it is on the curve and in the subgroup
+pub fn g1_from_uncompressed(bytes: &[u8; 2 * COORDINATE_SIZE]) -> Result<G1Affine, PointError> {
+    Option::from(G1Affine::from_uncompressed(bytes)).ok_or(PointError::NotOnCurve("G1"))
+}
+
+/// Decodes an uncompressed G2 point, checking that it is on the curve and in the subgroup
+pub fn g2_from_uncompressed(bytes: &[u8; 4 * COORDINATE_SIZE]) -> Result<G2Affine, PointError> {
+    Option::from(G2Affine::from_uncompressed(bytes)).ok_or(PointError::NotOnCurve("G2"))
+}
+
+/// Possible errors that can occur while decoding keys and curve points
+#[derive(Debug, Error)]
+pub enum PointError {
+    /// A validator public key is not valid hex or has the wrong length
+    #[error("Invalid hex in public key of validator {index}: {source}")]
+    ValidatorHex {
+        index: usize,
+        source: hex::FromHexError,
+    },
+    /// A validator public key is not a valid compressed G1 point
+    #[error("Public key of validator {index} is not a valid compressed G1 point")]
+    InvalidValidatorKey { index: usize },
+    /// A coordinate is not valid hex
+    #[error("Invalid hex in {coordinate} coordinate: {source}")]
+    CoordinateHex {
+        coordinate: &'static str,
+        source: hex::FromHexError,
+    },
+    /// A coordinate does not have 48 bytes
+    #[error("{coordinate} coordinate has {len} bytes, expected 48")]
+    CoordinateLength { coordinate: &'static str, len: usize },
+    /// The coordinates do not describe a point on the curve in the prime order subgroup
+    #[error("{0} point is not on the curve or not in the subgroup")]
+    NotOnCurve(&'static str),
+}
+
+#[cfg(test)]
+mod tests {
+    use bls12_381::G1Projective;
+
+    use super::*;
+
+    fn generator_hex() -> String {
+        format!("0x{}", hex::encode(G1Affine::generator().to_compressed()))
+    }
+
+    #[test]
+    fn decodes_validator_pubkey_with_and_without_prefix() {
+        let key = generator_hex();
+        assert_eq!(decode_validator_pubkey(0, &key).unwrap(), G1Affine::generator());
+        assert_eq!(decode_validator_pubkey(0, &key[2..]).unwrap(), G1Affine::generator());
+    }
+
+    #[test]
+    fn rejects_malformed_validator_pubkey_hex() {
+        let result = decode_validator_pubkey(3, "0xnothex");
+        assert!(matches!(result, Err(PointError::ValidatorHex { index: 3, .. })), "{:?}", result);
+
+        let short = &generator_hex()[..90];
+        let result = decode_validator_pubkey(4, short);
+        assert!(matches!(result, Err(PointError::ValidatorHex { index: 4, .. })), "{:?}", result);
+    }
+
+    #[test]
+    fn rejects_invalid_compressed_keys() {
+        // Compression flag unset
+        let result = decode_compressed_pubkey(5, &[0u8; COORDINATE_SIZE]);
+        assert!(matches!(result, Err(PointError::InvalidValidatorKey { index: 5 })), "{:?}", result);
+
+        // x larger than the field modulus
+        let mut bytes = [0xffu8; COORDINATE_SIZE];
+        bytes[0] = 0x9f;
+        let result = decode_compressed_pubkey(6, &bytes);
+        assert!(matches!(result, Err(PointError::InvalidValidatorKey { index: 6 })), "{:?}", result);
+        assert!(matches!(g1_from_compressed(&bytes), Err(PointError::NotOnCurve("G1"))));
+    }
+
+    #[test]
+    fn decodes_compressed_aggregate_key() {
+        let aggregate = G1Affine::from(G1Projective::generator() + G1Projective::generator());
+        assert_eq!(g1_from_compressed(&aggregate.to_compressed()).unwrap(), aggregate);
+    }
+
+    #[test]
+    fn rejects_malformed_coordinates() {
+        let result = decode_coordinate("x", "0xzz");
+        assert!(matches!(result, Err(PointError::CoordinateHex { coordinate: "x", .. })), "{:?}", result);
+
+        let result = decode_coordinate("y", &format!("0x{}", "01".repeat(47)));
+        assert!(
+            matches!(result, Err(PointError::CoordinateLength { coordinate: "y", len: 47 })),
+            "{:?}",
+            result
+        );
+
+        let x = G1Affine::generator().to_uncompressed();
+        let coordinate = decode_coordinate("x", &format!("0x{}", hex::encode(&x[..COORDINATE_SIZE]))).unwrap();
+        assert_eq!(&coordinate[..], &x[..COORDINATE_SIZE]);
+    }
+
+    #[test]
+    fn rejects_off_curve_points() {
+        let mut g1 = G1Affine::generator().to_uncompressed();
+        assert_eq!(g1_from_uncompressed(&g1).unwrap(), G1Affine::generator());
+        // Moving y off the curve
+        g1[2 * COORDINATE_SIZE - 1] ^= 1;
+        assert!(matches!(g1_from_uncompressed(&g1), Err(PointError::NotOnCurve("G1"))));
+
+        let mut g2 = G2Affine::generator().to_uncompressed();
+        assert_eq!(g2_from_uncompressed(&g2).unwrap(), G2Affine::generator());
+        g2[4 * COORDINATE_SIZE - 1] ^= 1;
+        assert!(matches!(g2_from_uncompressed(&g2), Err(PointError::NotOnCurve("G2"))));
+    }
+}
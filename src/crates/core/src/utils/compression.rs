@@ -0,0 +1,24 @@
+use serde::{de::DeserializeOwned, Serialize};
+
+/// zstd level used for everything Bankai persists. Favors speed, the JSON compresses well anyway.
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// Serializes `value` to JSON and compresses it with zstd
+pub fn compress_json<T: Serialize>(value: &T) -> std::io::Result<Vec<u8>> {
+    let json = serde_json::to_vec(value)?;
+    compress(&json)
+}
+
+/// Decompresses zstd data and deserializes the contained JSON
+pub fn decompress_json<T: DeserializeOwned>(data: &[u8]) -> std::io::Result<T> {
+    let json = decompress(data)?;
+    Ok(serde_json::from_slice(&json)?)
+}
+
+pub fn compress(data: &[u8]) -> std::io::Result<Vec<u8>> {
+    zstd::encode_all(data, COMPRESSION_LEVEL)
+}
+
+pub fn decompress(data: &[u8]) -> std::io::Result<Vec<u8>> {
+    zstd::decode_all(data)
+}
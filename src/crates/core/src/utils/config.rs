@@ -1,23 +1,477 @@
-#[derive(Clone, Debug)]
+//! Bankai Configuration
+//!
+//! The configuration is built in layers, each one overriding the previous:
+//! 1. Built-in defaults
+//! 2. A TOML file (`bankai.toml` in the working directory, or an explicit path)
+//! 3. Environment variables such as `BEACON_RPC_URL` or `DATABASE_URL`
+//! 4. CLI flags, applied by the caller before `validate`
+
+use std::{
+    env, fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use cairo_runner::RunnerConfig;
+use cairo_vm::Felt252;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{db::Backend, prover::job_params::VERIFIABLE_LAYOUTS, utils::constants};
+
+/// Config file read when no explicit path is given, if it exists
+pub const DEFAULT_CONFIG_PATH: &str = "bankai.toml";
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct BankaiConfig {
-    pub atlantic_endpoint: String,
-    pub database_url: String,
+    pub network: Network,
+    pub beacon: BeaconConfig,
+    pub atlantic: AtlanticConfig,
+    pub database: DatabaseConfig,
+    pub program: ProgramConfig,
+    pub starknet: StarknetConfig,
+}
+
+/// Ethereum network the light client follows
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Network {
+    Mainnet,
+    #[default]
+    Sepolia,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BeaconConfig {
+    /// Base URL of the beacon node RPC
+    pub rpc_url: String,
+    /// Delay before every request, to stay below provider rate limits
+    pub request_delay_ms: u64,
+    /// How many consecutive empty slots are skipped before giving up
+    pub max_skipped_slots: u64,
+    /// Request SSZ encoded blocks where the beacon node supports it, falling back to JSON
+    pub ssz: bool,
+    /// Where committee updates get the next sync committee and its branch from
+    pub sync_committee_source: SyncCommitteeSource,
+}
+
+/// Source of the next sync committee proven in committee updates
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncCommitteeSource {
+    /// Branch computed from the full beacon state, requires the debug state endpoint
+    #[default]
+    StateProof,
+    /// Branch taken from the light client update of the period
+    LightClient,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AtlanticConfig {
+    /// Base URL of the Atlantic API
+    pub endpoint: String,
+    pub api_key: String,
+    /// Base URL of the registry serving finished proofs
+    pub proof_registry: Option<String>,
+    /// Seconds between two status checks while waiting for a query
+    pub poll_interval_secs: u64,
+    /// Number of status checks before waiting for a query times out
+    pub max_poll_attempts: usize,
+    /// How the declared job size and layout are derived from the execution resources
+    pub job_sizing: JobSizingConfig,
+    /// Directory keeping a copy of every submitted PIE as `{uuid}.zip`. Not kept if unset.
+    pub pie_archive_dir: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JobSizingConfig {
+    /// Largest number of Cairo steps submitted as `XS`
+    pub xs_max_steps: usize,
+    /// Largest number of Cairo steps submitted as `S`
+    pub s_max_steps: usize,
+    /// Largest number of Cairo steps submitted as `M`, larger runs are submitted as `L`
+    pub m_max_steps: usize,
+    /// Candidate layouts in order of preference. The first one that has every builtin used by
+    /// the run is submitted. Only layouts the recursive update can verify are accepted, see
+    /// `prover::job_params::VERIFIABLE_LAYOUTS`.
+    pub layouts: Vec<String>,
 }
 
-impl Default for BankaiConfig {
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    /// `sqlite:` or `postgres://` database URL
+    pub url: String,
+    /// Directory for proof blobs. Proofs are stored in the database if unset.
+    pub proof_store_dir: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProgramConfig {
+    /// Compiled recursive update program
+    pub recursive_update: String,
+    /// Expected Poseidon program hash of the compiled program. The runner refuses to run a
+    /// program with a different hash; unset to accept any program.
+    pub recursive_update_hash: Option<String>,
+    /// Compiled Cairo verifier submitted with wrapped proofs for Starknet verification
+    pub wrapper: Option<String>,
+    /// Layout the recursive update program is run with
+    pub layout: String,
+    /// Abort runs of the recursive update program after this many steps
+    pub step_limit: Option<usize>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StarknetConfig {
+    /// Starknet JSON-RPC URL, e.g. a local devnet
+    pub rpc_url: Option<String>,
+    /// Address of the Bankai verifier contract
+    pub contract_address: Option<String>,
+}
+
+impl Default for BeaconConfig {
     fn default() -> Self {
         Self {
-            atlantic_endpoint: "https://staging.atlantic.api.herodotus.cloud".to_string(),
-            database_url: "sqlite:./sqlite_state/bankai.db".to_string(),
+            rpc_url: String::new(),
+            request_delay_ms: 70,
+            max_skipped_slots: constants::MAX_SKIPPED_SLOTS_RETRY_ATTEMPTS,
+            ssz: true,
+            sync_committee_source: SyncCommitteeSource::default(),
         }
     }
 }
 
-impl BankaiConfig {
-    pub fn docker_config() -> Self {
+impl Default for AtlanticConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: "https://staging.atlantic.api.herodotus.cloud".to_string(),
+            api_key: String::new(),
+            proof_registry: None,
+            poll_interval_secs: 30,
+            max_poll_attempts: 120,
+            job_sizing: JobSizingConfig::default(),
+            pie_archive_dir: None,
+        }
+    }
+}
+
+impl Default for JobSizingConfig {
+    fn default() -> Self {
         Self {
-            atlantic_endpoint: "https://staging.atlantic.api.herodotus.cloud".to_string(),
-            database_url: "sqlite:./sqlite_state/bankai.db".to_string(),
+            xs_max_steps: 1 << 21,
+            s_max_steps: 1 << 22,
+            m_max_steps: 1 << 23,
+            layouts: vec!["dynamic".to_string()],
+        }
+    }
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            url: "sqlite:./sqlite_state/bankai.db".to_string(),
+            proof_store_dir: None,
+        }
+    }
+}
+
+impl Default for ProgramConfig {
+    fn default() -> Self {
+        Self {
+            recursive_update: "cairo/build/recursive_update.json".to_string(),
+            recursive_update_hash: Some(
+                "0x6305ea579daa2cd35f92ce5c41fa3467a7b44c4d69f9849844aff9d552620e".to_string(),
+            ),
+            wrapper: None,
+            layout: "all_cairo".to_string(),
+            step_limit: None,
+        }
+    }
+}
+
+impl BankaiConfig {
+    /// Loads the configuration from defaults, the config file and the environment.
+    ///
+    /// # Arguments
+    /// * `path` - Config file to read. Falls back to `BANKAI_CONFIG`, then to `bankai.toml`
+    ///   if it exists. An explicitly given file must exist.
+    ///
+    /// # Returns
+    /// * `Result<Self, ConfigError>` - The merged configuration, not yet validated
+    pub fn load(path: Option<&Path>) -> Result<Self, ConfigError> {
+        let explicit = path
+            .map(Path::to_path_buf)
+            .or_else(|| env::var("BANKAI_CONFIG").ok().map(PathBuf::from));
+
+        let mut config = match explicit {
+            Some(path) => Self::from_file(&path)?,
+            None if Path::new(DEFAULT_CONFIG_PATH).exists() => {
+                Self::from_file(Path::new(DEFAULT_CONFIG_PATH))?
+            }
+            None => Self::default(),
+        };
+        config.apply_env()?;
+
+        Ok(config)
+    }
+
+    /// Parses a TOML config file. Missing keys keep their defaults.
+    pub fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        let contents = std::fs::read_to_string(path).map_err(|e| ConfigError::Read {
+            path: path.to_path_buf(),
+            source: e,
+        })?;
+        toml::from_str(&contents).map_err(|e| ConfigError::Parse {
+            path: path.to_path_buf(),
+            source: e,
+        })
+    }
+
+    /// Overrides values with the environment variables that are set
+    fn apply_env(&mut self) -> Result<(), ConfigError> {
+        self.apply_vars(env_var)
+    }
+
+    /// Overrides values with the variables `var` returns, see `apply_env`
+    fn apply_vars(&mut self, var: impl Fn(&str) -> Option<String>) -> Result<(), ConfigError> {
+        if let Some(network) = var("BANKAI_NETWORK") {
+            self.network = network.parse()?;
+        }
+        if let Some(url) = var("BEACON_RPC_URL") {
+            self.beacon.rpc_url = url;
+        }
+        if let Some(endpoint) = var("ATLANTIC_ENDPOINT") {
+            self.atlantic.endpoint = endpoint;
+        }
+        if let Some(api_key) = var("ATLANTIC_API_KEY") {
+            self.atlantic.api_key = api_key;
+        }
+        if let Some(registry) = var("PROOF_REGISTRY") {
+            self.atlantic.proof_registry = Some(registry);
         }
+        if let Some(dir) = var("PIE_ARCHIVE_DIR") {
+            self.atlantic.pie_archive_dir = Some(dir);
+        }
+        if let Some(url) = var("DATABASE_URL") {
+            self.database.url = url;
+        }
+        if let Some(dir) = var("PROOF_STORE_DIR") {
+            self.database.proof_store_dir = Some(dir);
+        }
+        if let Some(path) = var("RECURSIVE_UPDATE_PROGRAM") {
+            self.program.recursive_update = path;
+        }
+        if let Some(hash) = var("RECURSIVE_UPDATE_PROGRAM_HASH") {
+            self.program.recursive_update_hash = Some(hash);
+        }
+        if let Some(layout) = var("RECURSIVE_UPDATE_LAYOUT") {
+            self.program.layout = layout;
+        }
+        if let Some(limit) = var("RECURSIVE_UPDATE_STEP_LIMIT") {
+            let limit = limit.parse().map_err(|e| ConfigError::Invalid {
+                key: "program.step_limit",
+                reason: format!("{}: {}", limit, e),
+            })?;
+            self.program.step_limit = Some(limit);
+        }
+        if let Some(path) = var("WRAPPER_PROGRAM") {
+            self.program.wrapper = Some(path);
+        }
+        if let Some(url) = var("STARKNET_RPC_URL") {
+            self.starknet.rpc_url = Some(url);
+        }
+        if let Some(address) = var("BANKAI_CONTRACT_ADDRESS") {
+            self.starknet.contract_address = Some(address);
+        }
+        Ok(())
+    }
+
+    /// Checks that all required values are set and well-formed
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.beacon.rpc_url.is_empty() {
+            return Err(ConfigError::Missing("beacon.rpc_url (BEACON_RPC_URL or --rpc-url)"));
+        }
+        if self.atlantic.api_key.is_empty() {
+            return Err(ConfigError::Missing("atlantic.api_key (ATLANTIC_API_KEY)"));
+        }
+        self.validate_local()
+    }
+
+    /// Checks the values needed by commands that work on the database only, e.g. `status` or
+    /// `history`. The beacon node URL and the Atlantic API key may be unset, but are checked
+    /// if they are set.
+    pub fn validate_local(&self) -> Result<(), ConfigError> {
+        if !self.beacon.rpc_url.is_empty() {
+            validate_url("beacon.rpc_url", &self.beacon.rpc_url)?;
+        }
+
+        validate_url("atlantic.endpoint", &self.atlantic.endpoint)?;
+        if let Some(registry) = &self.atlantic.proof_registry {
+            validate_url("atlantic.proof_registry", registry)?;
+        }
+        if self.atlantic.poll_interval_secs == 0 || self.atlantic.max_poll_attempts == 0 {
+            return Err(ConfigError::Invalid {
+                key: "atlantic.poll_interval_secs / atlantic.max_poll_attempts",
+                reason: "must be greater than zero".to_string(),
+            });
+        }
+
+        let sizing = &self.atlantic.job_sizing;
+        if !(sizing.xs_max_steps < sizing.s_max_steps && sizing.s_max_steps < sizing.m_max_steps) {
+            return Err(ConfigError::Invalid {
+                key: "atlantic.job_sizing",
+                reason: "xs_max_steps < s_max_steps < m_max_steps must hold".to_string(),
+            });
+        }
+        if sizing.layouts.is_empty() {
+            return Err(ConfigError::Missing("atlantic.job_sizing.layouts"));
+        }
+        if let Some(layout) = sizing.layouts.iter().find(|layout| !VERIFIABLE_LAYOUTS.contains(&layout.as_str())) {
+            return Err(ConfigError::Invalid {
+                key: "atlantic.job_sizing.layouts",
+                reason: format!(
+                    "{} proofs can not be verified by the recursive update, use one of {}",
+                    layout,
+                    VERIFIABLE_LAYOUTS.join(", ")
+                ),
+            });
+        }
+
+        Backend::from_url(&self.database.url).map_err(|e| ConfigError::Invalid {
+            key: "database.url",
+            reason: e.to_string(),
+        })?;
+
+        if self.program.recursive_update.is_empty() {
+            return Err(ConfigError::Missing("program.recursive_update"));
+        }
+        self.program.expected_hash()?;
+        self.program.runner_config(&self.program.recursive_update)?;
+
+        if let Some(url) = &self.starknet.rpc_url {
+            validate_url("starknet.rpc_url", url)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl ProgramConfig {
+    /// Returns the parsed expected program hash, if one is configured
+    pub fn expected_hash(&self) -> Result<Option<Felt252>, ConfigError> {
+        self.recursive_update_hash
+            .as_deref()
+            .map(|hash| {
+                Felt252::from_hex(hash).map_err(|e| ConfigError::Invalid {
+                    key: "program.recursive_update_hash",
+                    reason: format!("{}: {}", hash, e),
+                })
+            })
+            .transpose()
+    }
+
+    /// Returns the runner configuration for the compiled program at `program_path`.
+    ///
+    /// Relative paths are resolved against the working directory.
+    pub fn runner_config(&self, program_path: &str) -> Result<RunnerConfig, ConfigError> {
+        let layout = cairo_runner::parse_layout(&self.layout).map_err(|e| ConfigError::Invalid {
+            key: "program.layout",
+            reason: e.to_string(),
+        })?;
+        Ok(RunnerConfig {
+            layout,
+            step_limit: self.step_limit,
+            ..RunnerConfig::new(program_path)
+        })
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Network::Mainnet => f.write_str("mainnet"),
+            Network::Sepolia => f.write_str("sepolia"),
+        }
+    }
+}
+
+impl FromStr for Network {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mainnet" => Ok(Network::Mainnet),
+            "sepolia" => Ok(Network::Sepolia),
+            other => Err(ConfigError::Invalid {
+                key: "network",
+                reason: format!("unknown network {}, expected mainnet or sepolia", other),
+            }),
+        }
+    }
+}
+
+fn env_var(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+fn validate_url(key: &'static str, url: &str) -> Result<(), ConfigError> {
+    reqwest::Url::parse(url)
+        .map(|_| ())
+        .map_err(|e| ConfigError::Invalid {
+            key,
+            reason: format!("{}: {}", url, e),
+        })
+}
+
+/// Possible errors that can occur while loading the configuration
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    /// The config file could not be read
+    #[error("Failed to read config file {path}: {source}")]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// The config file is not valid TOML or has unknown keys
+    #[error("Failed to parse config file {path}: {source}")]
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    /// A required value is not set
+    #[error("Missing configuration value: {0}")]
+    Missing(&'static str),
+    /// A value is set but not usable
+    #[error("Invalid configuration value for {key}: {reason}")]
+    Invalid { key: &'static str, reason: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Environment with only `RECURSIVE_UPDATE_STEP_LIMIT` set
+    fn vars(limit: &'static str) -> impl Fn(&str) -> Option<String> {
+        move |name| (name == "RECURSIVE_UPDATE_STEP_LIMIT").then(|| limit.to_string())
+    }
+
+    #[test]
+    fn step_limit_is_read_from_env() {
+        let mut config = BankaiConfig::default();
+        config.apply_vars(vars("12000000")).unwrap();
+        assert_eq!(config.program.step_limit, Some(12_000_000));
+
+        let invalid = BankaiConfig::default().apply_vars(vars("12M"));
+        assert!(
+            matches!(invalid, Err(ConfigError::Invalid { key: "program.step_limit", .. })),
+            "{:?}",
+            invalid
+        );
     }
 }
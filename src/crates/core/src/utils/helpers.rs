@@ -76,13 +76,16 @@ pub fn get_last_slot_for_sync_committee(sync_committee: u64) -> u64 {
     (sync_committee + 1) * SLOTS_PER_SYNC_COMMITTEE - 1
 }
 
-// Since beacon chain RPCs have different response structure (quicknode responds different than nidereal) we use this event extraction logic
+// Since beacon chain RPCs have different response structure (quicknode responds different than nidereal) we use this event extraction logic.
+// Multi-line data is joined as defined for server-sent events, with or without a space after `data:`.
 pub fn extract_json_from_event(event_text: &str) -> Option<String> {
-    for line in event_text.lines() {
-        if line.starts_with("data:") {
-            // Extract the JSON after "data:"
-            return Some(line.trim_start_matches("data:").trim().to_string());
-        }
+    let data = event_text
+        .lines()
+        .filter_map(|line| line.strip_prefix("data:"))
+        .map(str::trim)
+        .collect::<Vec<_>>();
+    if data.is_empty() {
+        return None;
     }
-    None
+    Some(data.join("\n"))
 }
@@ -38,6 +38,31 @@ pub(crate) mod sha256 {
         value
     }
 
+    /// Computes the root of a tree over `leaves`, padded with zero leaves to a power of 2
+    pub fn compute_root(leaves: Vec<FixedBytes<32>>) -> FixedBytes<32> {
+        let mut tree_size = 1;
+        while tree_size < leaves.len() {
+            tree_size *= 2;
+        }
+
+        let mut current_level = leaves;
+        current_level.resize(tree_size, FixedBytes::ZERO);
+
+        while current_level.len() > 1 {
+            current_level = current_level
+                .chunks(2)
+                .map(|pair| {
+                    let mut data = [0u8; 64];
+                    data[0..32].copy_from_slice(pair[0].as_slice());
+                    data[32..64].copy_from_slice(pair[1].as_slice());
+                    FixedBytes::from_slice(&Sha256::digest(data))
+                })
+                .collect();
+        }
+
+        current_level[0]
+    }
+
     #[allow(dead_code)]
     pub fn generate_path(
         leaves: Vec<FixedBytes<32>>,
@@ -225,3 +250,31 @@ pub enum MerkleError {
     #[error("Invalid Merkle tree")]
     InvalidMerkleTree,
 }
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::FixedBytes;
+    use beacon_types::{typenum::U3, FixedVector, Hash256, TreeHash};
+
+    use super::*;
+
+    fn leaves() -> Vec<FixedBytes<32>> {
+        (1..=3u8).map(|byte| FixedBytes::from([byte; 32])).collect()
+    }
+
+    #[test]
+    fn sha256_root_matches_ssz_vector() {
+        let vector: FixedVector<Hash256, U3> = FixedVector::new(leaves()).unwrap();
+        assert_eq!(sha256::compute_root(leaves()), vector.tree_hash_root());
+    }
+
+    #[test]
+    fn sha256_path_leads_to_root() {
+        let root = sha256::compute_root(leaves());
+        for index in 0..3 {
+            let path = sha256::generate_path(leaves(), index).unwrap();
+            assert_eq!(sha256::hash_path(path, leaves()[index], index as u64), root);
+        }
+        assert!(sha256::generate_path(leaves(), 3).is_err());
+    }
+}
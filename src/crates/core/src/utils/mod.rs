@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+pub mod bls;
+pub mod compression;
 pub mod config;
 pub mod constants;
 pub mod hashing;
@@ -0,0 +1,116 @@
+//! `AtlanticClient` against the mock Atlantic API
+
+use bankai_core::clients::atlantic::{AtlanticClient, AtlanticError, AtlanticStatus, JobParams, JobSize};
+use bankai_test_support::atlantic::{AtlanticBehavior, MockAtlantic};
+use serde_json::json;
+use tokio::time::Duration;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+fn params() -> JobParams {
+    JobParams {
+        declared_job_size: JobSize::S,
+        layout: "dynamic".to_string(),
+        n_steps: 3_400_000,
+        builtins: vec!["range_check".to_string()],
+        reasons: Vec::new(),
+    }
+}
+
+#[tokio::test]
+async fn submits_polls_and_fetches_proof() {
+    let atlantic = MockAtlantic::start(AtlanticBehavior {
+        polls_until_done: 2,
+        proofs: vec![json!({ "public_input": { "layout": "dynamic" } })],
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+    let client = AtlanticClient::new(atlantic.config());
+
+    let id = client
+        .submit_stone(vec![7; 200_000], &params(), "epoch_1".to_string())
+        .await
+        .unwrap();
+
+    let submissions = atlantic.submissions();
+    assert_eq!(submissions.len(), 1);
+    assert_eq!(submissions[0].id, id);
+    assert_eq!(submissions[0].fields["declaredJobSize"], "S");
+    assert_eq!(submissions[0].fields["layout"], "dynamic");
+    assert_eq!(submissions[0].fields["externalId"], "epoch_1");
+    assert_eq!(submissions[0].files["pieFile"], 200_000);
+
+    let query = client.get_query(&id).await.unwrap();
+    assert_eq!(query.status, AtlanticStatus::InProgress);
+    assert_eq!(query.step.as_deref(), Some("TRACE_GENERATION"));
+
+    assert!(client.poll_batch_status_until_done(&id, POLL_INTERVAL, 5).await.unwrap());
+    let proof = client.fetch_proof(&id).await.unwrap();
+    assert_eq!(proof.proof["public_input"]["layout"], "dynamic");
+}
+
+#[tokio::test]
+async fn reports_failure_reason() {
+    let atlantic = MockAtlantic::start(AtlanticBehavior {
+        failure: Some("trace generation ran out of memory".to_string()),
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+    let client = AtlanticClient::new(atlantic.config());
+    let id = client
+        .submit_stone(vec![0; 16], &params(), "epoch_2".to_string())
+        .await
+        .unwrap();
+
+    let query = client.get_query(&id).await.unwrap();
+    assert_eq!(query.status, AtlanticStatus::Failed);
+    assert_eq!(query.error_reason.as_deref(), Some("trace generation ran out of memory"));
+
+    match client.poll_batch_status_until_done(&id, POLL_INTERVAL, 5).await {
+        Err(AtlanticError::AtlanticProcessingError(message)) => {
+            assert!(message.contains("ran out of memory"), "{}", message)
+        }
+        other => panic!("expected a processing error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn rejected_submission_is_an_error() {
+    let atlantic = MockAtlantic::start(AtlanticBehavior {
+        rejected_submissions: 1,
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+    let client = AtlanticClient::new(atlantic.config());
+
+    let rejected = client.submit_stone(vec![0; 16], &params(), "epoch_3".to_string()).await;
+    assert!(matches!(rejected, Err(AtlanticError::InvalidResponse(_))), "{:?}", rejected);
+    assert!(atlantic.submissions().is_empty());
+
+    client
+        .submit_stone(vec![0; 16], &params(), "epoch_3".to_string())
+        .await
+        .unwrap();
+    assert_eq!(atlantic.submissions().len(), 1);
+}
+
+#[tokio::test]
+async fn times_out_while_in_progress() {
+    let atlantic = MockAtlantic::start(AtlanticBehavior {
+        polls_until_done: 10,
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+    let client = AtlanticClient::new(atlantic.config());
+    let id = client
+        .submit_stone(vec![0; 16], &params(), "epoch_4".to_string())
+        .await
+        .unwrap();
+
+    let result = client.poll_batch_status_until_done(&id, POLL_INTERVAL, 3).await;
+    assert!(matches!(result, Err(AtlanticError::AtlanticPoolingTimeout(_))), "{:?}", result);
+}
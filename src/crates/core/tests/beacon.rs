@@ -0,0 +1,296 @@
+//! `BeaconRpcClient` against the mock beacon API
+
+use bankai_core::{
+    clients::beacon_chain::{BeaconError, BeaconRpcClient},
+    utils::config::BankaiConfig,
+};
+use bankai_test_support::beacon::{BeaconFixtures, MockBeacon};
+use bls12_381::{G1Affine, Scalar};
+use serde_json::{json, Value};
+
+fn header(slot: u64) -> Value {
+    json!({
+        "execution_optimistic": false,
+        "finalized": true,
+        "data": {
+            "root": format!("0x{:064x}", slot),
+            "canonical": true,
+            "header": {
+                "message": {
+                    "slot": slot.to_string(),
+                    "proposer_index": "1",
+                    "parent_root": format!("0x{:064x}", slot - 1),
+                    "state_root": format!("0x{:064x}", slot + 1),
+                    "body_root": format!("0x{:064x}", slot + 2),
+                },
+                "signature": format!("0x{}", "00".repeat(96)),
+            }
+        }
+    })
+}
+
+async fn client(fixtures: BeaconFixtures) -> (MockBeacon, BeaconRpcClient) {
+    let beacon = MockBeacon::start(fixtures).await.unwrap();
+    let mut config = BankaiConfig::default();
+    config.beacon.rpc_url = beacon.url();
+    config.beacon.request_delay_ms = 0;
+    let client = BeaconRpcClient::new(beacon.url(), config);
+    (beacon, client)
+}
+
+#[tokio::test]
+async fn serves_headers_and_head() {
+    let mut fixtures = BeaconFixtures::default();
+    fixtures.insert("eth/v1/beacon/headers/8192", header(8192));
+    fixtures.insert("eth/v1/beacon/headers/head", header(8200));
+    let (beacon, client) = client(fixtures).await;
+
+    let response = client.get_header(8192).await.unwrap();
+    assert_eq!(response.data.header.message.slot, 8192);
+    assert_eq!(client.get_head_slot().await.unwrap(), 8200);
+    assert_eq!(
+        beacon.requests(),
+        vec!["eth/v1/beacon/headers/8192", "eth/v1/beacon/headers/head"]
+    );
+}
+
+#[tokio::test]
+async fn missing_header_is_an_empty_slot() {
+    let (_beacon, client) = client(BeaconFixtures::default()).await;
+
+    let result = client.get_header(8193).await;
+    assert!(matches!(result, Err(BeaconError::EmptySlot(8193))), "{:?}", result);
+}
+
+#[tokio::test]
+async fn fixtures_round_trip_through_files() {
+    let mut fixtures = BeaconFixtures::default();
+    fixtures.insert("eth/v1/beacon/headers/8192", header(8192));
+    fixtures.insert(
+        "eth/v1/beacon/states/8193/sync_committees?epoch=512",
+        json!({ "data": { "validators": ["1", "2"] } }),
+    );
+
+    let dir = std::env::temp_dir().join(format!("bankai_fixtures_{}", std::process::id()));
+    fixtures.save(&dir).unwrap();
+    let loaded = BeaconFixtures::load(&dir).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(loaded.len(), 2);
+    assert_eq!(
+        loaded.get("eth/v1/beacon/states/8193/sync_committees?epoch=512"),
+        fixtures.get("eth/v1/beacon/states/8193/sync_committees?epoch=512")
+    );
+}
+
+fn block(slot: u64) -> Value {
+    json!({
+        "version": "deneb",
+        "execution_optimistic": false,
+        "finalized": true,
+        "data": {
+            "message": {
+                "slot": slot.to_string(),
+                "body": {
+                    "sync_aggregate": {
+                        "sync_committee_bits": format!("0x{}", "ff".repeat(64)),
+                        "sync_committee_signature": format!("0x{}", "ab".repeat(96)),
+                    }
+                }
+            }
+        }
+    })
+}
+
+fn pubkey(index: u64) -> String {
+    let point = G1Affine::from(G1Affine::generator() * Scalar::from(index + 1));
+    format!("0x{}", hex::encode(point.to_compressed()))
+}
+
+fn validators(indexes: impl Iterator<Item = u64>, status: impl Fn(u64) -> &'static str) -> Value {
+    let data: Vec<Value> = indexes
+        .map(|index| {
+            json!({
+                "index": index.to_string(),
+                "balance": "32000000000",
+                "status": status(index),
+                "validator": { "pubkey": pubkey(index), "effective_balance": "32000000000" },
+            })
+        })
+        .collect();
+    json!({ "execution_optimistic": false, "finalized": true, "data": data })
+}
+
+fn sync_committee(indexes: &[u64]) -> Value {
+    let validators: Vec<String> = indexes.iter().map(u64::to_string).collect();
+    json!({ "execution_optimistic": false, "finalized": true, "data": { "validators": validators } })
+}
+
+async fn ssz_client(fixtures: BeaconFixtures) -> (MockBeacon, BeaconRpcClient) {
+    let beacon = MockBeacon::start(fixtures).await.unwrap();
+    let mut config = BankaiConfig::default();
+    config.beacon.rpc_url = beacon.url();
+    config.beacon.request_delay_ms = 0;
+    config.beacon.ssz = true;
+    let client = BeaconRpcClient::new(beacon.url(), config);
+    (beacon, client)
+}
+
+#[tokio::test]
+async fn reads_sync_aggregate_from_the_next_block() {
+    let mut fixtures = BeaconFixtures::default();
+    fixtures.insert("eth/v1/beacon/headers/8193", header(8193));
+    fixtures.insert("eth/v2/beacon/blocks/8193", block(8193));
+    let (beacon, client) = client(fixtures).await;
+
+    let aggregate = client.get_sync_aggregate(8192).await.unwrap();
+    assert_eq!(aggregate.sync_committee_bits.len(), 64);
+    assert_eq!(aggregate.sync_committee_signature.0, [0xab; 96]);
+    assert_eq!(
+        beacon.requests(),
+        vec!["eth/v1/beacon/headers/8193", "eth/v2/beacon/blocks/8193"]
+    );
+}
+
+#[tokio::test]
+async fn skips_empty_slots_before_the_signing_block() {
+    let mut fixtures = BeaconFixtures::default();
+    fixtures.insert("eth/v1/beacon/headers/8195", header(8195));
+    fixtures.insert("eth/v2/beacon/blocks/8195", block(8195));
+    let (_beacon, client) = client(fixtures).await;
+
+    assert!(client.get_sync_aggregate(8192).await.is_ok());
+
+    let (_beacon, client) = client(BeaconFixtures::default()).await;
+    let result = client.get_sync_aggregate(8192).await;
+    assert!(matches!(result, Err(BeaconError::EmptySlot(_))), "{:?}", result);
+}
+
+#[tokio::test]
+async fn falls_back_to_json_when_the_node_has_no_ssz() {
+    let mut fixtures = BeaconFixtures::default();
+    fixtures.insert("eth/v1/beacon/headers/8193", header(8193));
+    fixtures.insert("eth/v2/beacon/blocks/8193", block(8193));
+    let (beacon, client) = ssz_client(fixtures).await;
+
+    assert!(client.get_sync_aggregate(8192).await.is_ok());
+    // The JSON answer to the SSZ request is used as is
+    assert_eq!(
+        beacon.requests(),
+        vec!["eth/v1/beacon/headers/8193", "eth/v2/beacon/blocks/8193"]
+    );
+}
+
+#[tokio::test]
+async fn falls_back_to_json_when_ssz_does_not_decode() {
+    let mut fixtures = BeaconFixtures::default();
+    fixtures.insert("eth/v1/beacon/headers/8193", header(8193));
+    fixtures.insert("eth/v2/beacon/blocks/8193", block(8193));
+    fixtures.insert_ssz("eth/v2/beacon/blocks/8193", "deneb", vec![0u8; 16]);
+    let (beacon, client) = ssz_client(fixtures).await;
+
+    let aggregate = client.get_sync_aggregate(8192).await.unwrap();
+    assert_eq!(aggregate.sync_committee_signature.0, [0xab; 96]);
+    assert_eq!(
+        beacon.requests(),
+        vec![
+            "eth/v1/beacon/headers/8193",
+            "eth/v2/beacon/blocks/8193",
+            "eth/v2/beacon/blocks/8193"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn fetches_committee_keys_in_chunks() {
+    // 300 distinct validators, listed twice like validators sitting in a committee twice
+    let indexes: Vec<u64> = (0..300).chain(0..212).collect();
+    let mut fixtures = BeaconFixtures::default();
+    fixtures.insert("eth/v1/beacon/states/8193/sync_committees", sync_committee(&indexes));
+    fixtures.insert("eth/v1/beacon/states/8193/validators", validators(0..300, |_| "active_ongoing"));
+    let (beacon, client) = client(fixtures).await;
+
+    let committee = client.get_sync_committee_validator_pubs(8192).await.unwrap();
+    assert_eq!(committee.validator_pubs.len(), 512);
+    assert_eq!(
+        format!("0x{}", hex::encode(committee.validator_pubs[299].to_compressed())),
+        pubkey(299)
+    );
+    assert_eq!(committee.validator_pubs[300], committee.validator_pubs[0]);
+
+    let chunks: Vec<usize> = beacon
+        .post_bodies()
+        .iter()
+        .map(|body| body["ids"].as_array().unwrap().len())
+        .collect();
+    assert_eq!(chunks, vec![128, 128, 44]);
+}
+
+#[tokio::test]
+async fn fetches_the_next_committee_by_epoch() {
+    let indexes: Vec<u64> = (0..512).collect();
+    let mut fixtures = BeaconFixtures::default();
+    fixtures.insert(
+        "eth/v1/beacon/states/8200/sync_committees?epoch=512",
+        sync_committee(&indexes),
+    );
+    fixtures.insert("eth/v1/beacon/states/8200/validators", validators(0..512, |_| "active_ongoing"));
+    let (_beacon, client) = client(fixtures).await;
+
+    let committee = client.get_next_sync_committee_validator_pubs(8200).await.unwrap();
+    assert_eq!(committee.validator_pubs.len(), 512);
+}
+
+#[tokio::test]
+async fn missing_validator_is_an_error() {
+    let mut fixtures = BeaconFixtures::default();
+    fixtures.insert("eth/v1/beacon/states/8193/sync_committees", sync_committee(&[1, 2, 3]));
+    fixtures.insert("eth/v1/beacon/states/8193/validators", validators(1..3, |_| "active_ongoing"));
+    let (_beacon, client) = client(fixtures).await;
+
+    let result = client.get_sync_committee_validator_pubs(8192).await;
+    assert!(
+        matches!(result, Err(BeaconError::ValidatorNotFound { index: 3, slot: 8193 })),
+        "{:?}",
+        result
+    );
+}
+
+#[tokio::test]
+async fn pending_validator_is_an_error() {
+    let mut fixtures = BeaconFixtures::default();
+    fixtures.insert("eth/v1/beacon/states/8193/sync_committees", sync_committee(&[1, 2, 3]));
+    fixtures.insert(
+        "eth/v1/beacon/states/8193/validators",
+        validators(1..4, |index| if index == 2 { "pending_queued" } else { "active_ongoing" }),
+    );
+    let (_beacon, client) = client(fixtures).await;
+
+    let result = client.get_sync_committee_validator_pubs(8192).await;
+    assert!(
+        matches!(
+            &result,
+            Err(BeaconError::InactiveValidator { index: 2, slot: 8193, status }) if status == "pending_queued"
+        ),
+        "{:?}",
+        result
+    );
+}
+
+#[tokio::test]
+async fn exited_committee_member_is_kept() {
+    let mut fixtures = BeaconFixtures::default();
+    fixtures.insert("eth/v1/beacon/states/8193/sync_committees", sync_committee(&[1, 2, 3]));
+    fixtures.insert(
+        "eth/v1/beacon/states/8193/validators",
+        validators(1..4, |index| match index {
+            2 => "exited_unslashed",
+            3 => "withdrawal_possible",
+            _ => "active_ongoing",
+        }),
+    );
+    let (_beacon, client) = client(fixtures).await;
+
+    let committee = client.get_sync_committee_validator_pubs(8192).await.unwrap();
+    assert_eq!(committee.validator_pubs.len(), 3);
+}
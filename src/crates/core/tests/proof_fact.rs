@@ -0,0 +1,31 @@
+//! Fact hash of a real Atlantic proof
+//!
+//! `proof_fact` must compute the fact hash Integrity registered on Starknet for the proof,
+//! otherwise `wrap` rejects every verified epoch update. The unit tests in `prover::verify`
+//! only use synthetic public inputs, so this test checks the computation against a recorded
+//! proof. It needs `tests/fixtures/atlantic_proof.json`, see the fixture README, and runs with
+//! `cargo test -p bankai-core --test proof_fact -- --ignored`.
+
+use std::path::PathBuf;
+
+use bankai_core::prover::verify::{proof_fact, BOOTLOADER_PROGRAM_HASH};
+use cairo_vm::Felt252;
+use serde_json::Value;
+
+fn fixture() -> Value {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/atlantic_proof.json");
+    let fixture = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("Failed to read {}: {}, see tests/fixtures/README.md", path.display(), e));
+    serde_json::from_str(&fixture).unwrap()
+}
+
+#[test]
+#[ignore = "needs a recorded Atlantic proof in tests/fixtures/atlantic_proof.json"]
+fn proof_fact_matches_registered_fact_hash() {
+    let fixture = fixture();
+    let registered = Felt252::from_hex(fixture["fact_hash"].as_str().unwrap()).unwrap();
+
+    let fact = proof_fact(&fixture["proof"]).unwrap();
+    assert_eq!(fact.program_hash, Felt252::from_hex(BOOTLOADER_PROGRAM_HASH).unwrap());
+    assert_eq!(fact.fact_hash, registered, "computed {:#x}, registered {:#x}", fact.fact_hash, registered);
+}
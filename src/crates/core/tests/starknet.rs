@@ -0,0 +1,36 @@
+//! `StarknetClient` against a deployed Bankai contract
+//!
+//! The entry points and the layout of their results are decoded by hand, so this reads a
+//! contract with at least one verified epoch and committee and checks that every call decodes.
+//! It runs against `STARKNET_RPC_URL` and `BANKAI_CONTRACT_ADDRESS`, e.g. a devnet with the
+//! contract deployed, and is skipped if they are not set.
+
+use bankai_core::{clients::starknet::StarknetClient, utils::config::StarknetConfig};
+
+#[tokio::test]
+async fn reads_deployed_contract() {
+    let (Ok(rpc_url), Ok(contract_address)) =
+        (std::env::var("STARKNET_RPC_URL"), std::env::var("BANKAI_CONTRACT_ADDRESS"))
+    else {
+        eprintln!("STARKNET_RPC_URL or BANKAI_CONTRACT_ADDRESS is not set, skipping the contract test");
+        return;
+    };
+    let client = StarknetClient::new(&StarknetConfig {
+        rpc_url: Some(rpc_url),
+        contract_address: Some(contract_address),
+    })
+    .unwrap();
+
+    let slot = client.get_latest_epoch_slot().await.unwrap();
+    assert!(slot > 0, "the contract has no verified epoch");
+    let epoch = client.get_epoch_proof(slot).await.unwrap();
+    assert!(!epoch.is_empty(), "no epoch stored for the latest slot {}", slot);
+    assert!(!epoch.header_root.is_zero());
+    assert!(epoch.execution_header_height > 0);
+    // Slots without a verified epoch decode to an empty epoch
+    assert!(client.get_epoch_proof(slot + 1).await.unwrap().is_empty());
+
+    let committee_id = client.get_latest_committee_id().await.unwrap();
+    assert!(!client.get_committee_hash(committee_id).await.unwrap().is_zero());
+    assert!(client.get_committee_hash(committee_id + 1).await.unwrap().is_zero());
+}
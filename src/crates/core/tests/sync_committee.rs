@@ -0,0 +1,81 @@
+//! Light client committee updates against the mock beacon API
+
+use bankai_core::{
+    clients::beacon_chain::BeaconRpcClient,
+    fetcher::sync_committee_input::{SyncCommitteeData, SyncCommitteeError},
+    utils::{config::BankaiConfig, constants::SLOTS_PER_SYNC_COMMITTEE},
+};
+use bankai_test_support::beacon::{BeaconFixtures, MockBeacon};
+use serde_json::Value;
+
+const ELECTRA_UPDATE: &str = include_str!("fixtures/light_client_update_electra.json");
+const DENEB_UPDATE: &str = include_str!("fixtures/light_client_update_deneb.json");
+
+async fn client(period: u64, update: Value) -> (MockBeacon, BeaconRpcClient) {
+    let mut fixtures = BeaconFixtures::default();
+    fixtures.insert(
+        format!("eth/v1/beacon/light_client/updates?start_period={}&count=1", period),
+        update,
+    );
+    let beacon = MockBeacon::start(fixtures).await.unwrap();
+    let mut config = BankaiConfig::default();
+    config.beacon.rpc_url = beacon.url();
+    config.beacon.request_delay_ms = 0;
+    let client = BeaconRpcClient::new(beacon.url(), config);
+    (beacon, client)
+}
+
+#[tokio::test]
+async fn verifies_electra_update_at_its_gindex() {
+    let (_beacon, client) = client(870, serde_json::from_str(ELECTRA_UPDATE).unwrap()).await;
+
+    let (data, state_root) = SyncCommitteeData::from_light_client(&client, 870 * SLOTS_PER_SYNC_COMMITTEE)
+        .await
+        .unwrap();
+    assert_eq!(data.beacon_slot, 7134976);
+    assert_eq!(data.next_sync_committee_branch.len(), 6);
+    assert_eq!(data.compute_state_root(), state_root);
+}
+
+#[tokio::test]
+async fn verifies_deneb_update_at_its_gindex() {
+    let (_beacon, client) = client(600, serde_json::from_str(DENEB_UPDATE).unwrap()).await;
+
+    let (data, state_root) = SyncCommitteeData::from_light_client(&client, 600 * SLOTS_PER_SYNC_COMMITTEE)
+        .await
+        .unwrap();
+    assert_eq!(data.beacon_slot, 4923136);
+    assert_eq!(data.next_sync_committee_branch.len(), 5);
+    assert_eq!(data.compute_state_root(), state_root);
+}
+
+#[tokio::test]
+async fn rejects_branch_of_the_wrong_fork() {
+    let mut update: Value = serde_json::from_str(ELECTRA_UPDATE).unwrap();
+    update[0]["data"]["next_sync_committee_branch"]
+        .as_array_mut()
+        .unwrap()
+        .pop();
+    let (_beacon, client) = client(870, update).await;
+
+    let result = SyncCommitteeData::from_light_client(&client, 870 * SLOTS_PER_SYNC_COMMITTEE).await;
+    assert!(
+        matches!(&result, Err(SyncCommitteeError::LightClient(message)) if message.contains("expected 6")),
+        "{:?}",
+        result
+    );
+}
+
+#[tokio::test]
+async fn rejects_branch_not_proving_the_attested_state() {
+    let mut update: Value = serde_json::from_str(ELECTRA_UPDATE).unwrap();
+    update[0]["data"]["next_sync_committee_branch"][0] = Value::String(format!("0x{}", "11".repeat(32)));
+    let (_beacon, client) = client(870, update).await;
+
+    let result = SyncCommitteeData::from_light_client(&client, 870 * SLOTS_PER_SYNC_COMMITTEE).await;
+    assert!(
+        matches!(result, Err(SyncCommitteeError::StateRootMismatch { slot: 7134976, .. })),
+        "{:?}",
+        result
+    );
+}
@@ -3,3 +3,5 @@ mod types;
 mod vars;
 pub mod verifier_hints;
 mod verifier_utils;
+
+pub use types::OwnedPublicInput;
@@ -0,0 +1,28 @@
+//! Records beacon API fixtures by proxying a real node.
+//!
+//! Point the CLI at the printed URL, run the flow to record, then stop with Ctrl-C to write
+//! the responses below the output directory:
+//!
+//! ```bash
+//! cargo run -p bankai-test-support --example record_beacon -- https://beacon.example fixtures/flow/beacon
+//! ```
+
+use bankai_test_support::beacon::MockBeacon;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1);
+    let (Some(upstream), Some(out_dir)) = (args.next(), args.next()) else {
+        eprintln!("Usage: record_beacon <upstream beacon URL> <output directory>");
+        std::process::exit(1);
+    };
+
+    let beacon = MockBeacon::record(upstream).await?;
+    println!("Recording on {}, press Ctrl-C to save", beacon.url());
+    tokio::signal::ctrl_c().await?;
+
+    let fixtures = beacon.fixtures();
+    fixtures.save(&out_dir)?;
+    println!("Saved {} routes to {}", fixtures.len(), out_dir);
+    Ok(())
+}
@@ -0,0 +1,225 @@
+//! Mock Atlantic API
+//!
+//! Implements the parts of the Atlantic API used by `AtlanticClient`: submitting queries to
+//! `POST /atlantic-query`, reading them from `GET /atlantic-query/{id}` and fetching finished
+//! proofs from the registry at `GET /proofs/{id}/proof.json`. Queries advance through the
+//! configured steps on every status check, so a client polling them sees the same sequence as
+//! against the real service.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use axum::{
+    extract::{Multipart, Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use bankai_core::utils::config::AtlanticConfig;
+use serde_json::{json, Value};
+
+use crate::{Server, TestSupportError};
+
+/// API key the mock expects, see `MockAtlantic::config`
+pub const API_KEY: &str = "test-api-key";
+
+/// How the mock answers submissions and status checks
+#[derive(Debug, Clone)]
+pub struct AtlanticBehavior {
+    /// Status checks answered with `IN_PROGRESS` before a query is `DONE`
+    pub polls_until_done: usize,
+    /// Steps reported while a query is in progress, one per status check
+    pub steps: Vec<String>,
+    /// Fail every query with this reason once it would be done
+    pub failure: Option<String>,
+    /// Number of submissions rejected with HTTP 500 before submissions are accepted
+    pub rejected_submissions: usize,
+    /// Proofs served by the registry, by submission order. Later queries get the last one.
+    pub proofs: Vec<Value>,
+    /// Fact hash reported by finished queries
+    pub integrity_fact_hash: Option<String>,
+}
+
+impl Default for AtlanticBehavior {
+    fn default() -> Self {
+        Self {
+            polls_until_done: 0,
+            steps: vec!["TRACE_GENERATION".to_string(), "PROOF_GENERATION".to_string()],
+            failure: None,
+            rejected_submissions: 0,
+            proofs: Vec::new(),
+            integrity_fact_hash: None,
+        }
+    }
+}
+
+/// A query received by the mock
+#[derive(Debug, Clone)]
+pub struct Submission {
+    /// ID the query was assigned
+    pub id: String,
+    /// Text fields of the form, e.g. `declaredJobSize` and `layout`
+    pub fields: HashMap<String, String>,
+    /// Size of each uploaded file, by form field
+    pub files: HashMap<String, usize>,
+}
+
+#[derive(Debug, Default)]
+struct AtlanticState {
+    behavior: AtlanticBehavior,
+    submissions: Vec<Submission>,
+    /// Status checks so far, by query ID
+    polls: HashMap<String, usize>,
+    rejected: usize,
+}
+
+type SharedState = Arc<Mutex<AtlanticState>>;
+
+/// Local stand-in for the Atlantic API and proof registry
+#[derive(Debug)]
+pub struct MockAtlantic {
+    server: Server,
+    state: SharedState,
+}
+
+impl MockAtlantic {
+    /// Starts the mock on a free port
+    ///
+    /// # Arguments
+    /// * `behavior` - How submissions and status checks are answered
+    pub async fn start(behavior: AtlanticBehavior) -> Result<Self, TestSupportError> {
+        let state = Arc::new(Mutex::new(AtlanticState {
+            behavior,
+            ..Default::default()
+        }));
+        let router = Router::new()
+            .route("/atlantic-query", post(submit))
+            .route("/atlantic-query/:id", get(query))
+            .route("/proofs/:id/proof.json", get(proof))
+            .with_state(state.clone());
+
+        Ok(Self {
+            server: Server::start(router).await?,
+            state,
+        })
+    }
+
+    /// Base URL of the API
+    pub fn url(&self) -> String {
+        self.server.url()
+    }
+
+    /// Client configuration pointing at the mock, polling once per second
+    pub fn config(&self) -> AtlanticConfig {
+        AtlanticConfig {
+            endpoint: self.url(),
+            api_key: API_KEY.to_string(),
+            proof_registry: Some(format!("{}/proofs", self.url())),
+            poll_interval_secs: 1,
+            max_poll_attempts: 30,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the queries received so far, oldest first
+    pub fn submissions(&self) -> Vec<Submission> {
+        lock(&self.state).submissions.clone()
+    }
+
+    /// Replaces the behavior for the following requests
+    pub fn set_behavior(&self, behavior: AtlanticBehavior) {
+        lock(&self.state).behavior = behavior;
+    }
+}
+
+fn lock(state: &SharedState) -> MutexGuard<'_, AtlanticState> {
+    state.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+async fn submit(State(state): State<SharedState>, mut form: Multipart) -> Response {
+    let mut fields = HashMap::new();
+    let mut files = HashMap::new();
+    loop {
+        let field = match form.next_field().await {
+            Ok(Some(field)) => field,
+            Ok(None) => break,
+            Err(e) => return error(StatusCode::BAD_REQUEST, e.to_string()),
+        };
+        let name = field.name().unwrap_or_default().to_string();
+        let is_file = field.file_name().is_some();
+        let bytes = match field.bytes().await {
+            Ok(bytes) => bytes,
+            Err(e) => return error(StatusCode::BAD_REQUEST, e.to_string()),
+        };
+        if is_file {
+            files.insert(name, bytes.len());
+        } else {
+            fields.insert(name, String::from_utf8_lossy(&bytes).into_owned());
+        }
+    }
+
+    let mut state = lock(&state);
+    if state.rejected < state.behavior.rejected_submissions {
+        state.rejected += 1;
+        return error(StatusCode::INTERNAL_SERVER_ERROR, "Submission rejected by mock".to_string());
+    }
+    let id = format!("mock-query-{}", state.submissions.len());
+    state.submissions.push(Submission {
+        id: id.clone(),
+        fields,
+        files,
+    });
+    Json(json!({ "atlanticQueryId": id })).into_response()
+}
+
+async fn query(State(state): State<SharedState>, Path(id): Path<String>) -> Response {
+    let mut state = lock(&state);
+    let Some(submission) = state.submissions.iter().find(|s| s.id == id).cloned() else {
+        return error(StatusCode::NOT_FOUND, format!("Query {} not found", id));
+    };
+    let polls = state.polls.entry(id.clone()).or_default();
+    let seen = *polls;
+    *polls += 1;
+
+    let behavior = &state.behavior;
+    let (status, step, error_reason) = if seen < behavior.polls_until_done {
+        let step = behavior.steps.get(seen % behavior.steps.len().max(1)).cloned();
+        ("IN_PROGRESS", step, None)
+    } else if let Some(reason) = &behavior.failure {
+        ("FAILED", None, Some(reason.clone()))
+    } else {
+        ("DONE", None, None)
+    };
+
+    Json(json!({
+        "atlanticQuery": {
+            "id": id,
+            "externalId": submission.fields.get("externalId"),
+            "status": status,
+            "step": step,
+            "steps": behavior.steps,
+            "errorReason": error_reason,
+            "layout": submission.fields.get("layout"),
+            "declaredJobSize": submission.fields.get("declaredJobSize"),
+            "integrityFactHash": if status == "DONE" { behavior.integrity_fact_hash.clone() } else { None },
+        }
+    }))
+    .into_response()
+}
+
+async fn proof(State(state): State<SharedState>, Path(id): Path<String>) -> Response {
+    let state = lock(&state);
+    let index = state.submissions.iter().position(|s| s.id == id);
+    let proofs = &state.behavior.proofs;
+    match index.and_then(|index| proofs.get(index).or(proofs.last())) {
+        Some(proof) => Json(proof.clone()).into_response(),
+        None => error(StatusCode::NOT_FOUND, format!("No proof for query {}", id)),
+    }
+}
+
+fn error(status: StatusCode, message: String) -> Response {
+    (status, Json(json!({ "message": message }))).into_response()
+}
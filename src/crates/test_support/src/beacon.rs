@@ -0,0 +1,326 @@
+//! Mock Beacon API
+//!
+//! Serves recorded JSON responses of a beacon node by route, e.g.
+//! `eth/v1/beacon/headers/123` or `eth/v1/beacon/states/123/sync_committees?epoch=4`. Routes
+//! without a fixture are answered with a 404 body like a beacon node's, which the client treats
+//! as an empty slot. Validators are looked up through `POST .../validators` with the requested
+//! IDs, so their fixture is the list of all validators the flow needs.
+//!
+//! Fixtures are stored as one file per route below a directory, with the query string separated
+//! by `@` instead of `?`, and can be recorded from a real node with `MockBeacon::record`. SSZ
+//! responses, served to requests accepting `application/octet-stream`, are kept in memory only.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use axum::{
+    body::Bytes,
+    extract::{OriginalUri, State},
+    http::{header, HeaderMap, Method, StatusCode},
+    response::{IntoResponse, Response},
+    Json, Router,
+};
+use serde_json::{json, Value};
+
+use crate::{Server, TestSupportError};
+
+/// Recorded beacon API responses by route
+#[derive(Debug, Clone, Default)]
+pub struct BeaconFixtures {
+    routes: HashMap<String, Value>,
+    /// SSZ responses and the fork they are encoded for, by route
+    ssz_routes: HashMap<String, (String, Vec<u8>)>,
+}
+
+impl BeaconFixtures {
+    /// Loads every `*.json` file below `dir`, keyed by its relative path
+    pub fn load(dir: impl AsRef<Path>) -> Result<Self, TestSupportError> {
+        let mut fixtures = Self::default();
+        let dir = dir.as_ref();
+        let mut pending = vec![dir.to_path_buf()];
+        while let Some(current) = pending.pop() {
+            for entry in fs::read_dir(&current)? {
+                let path = entry?.path();
+                if path.is_dir() {
+                    pending.push(path);
+                    continue;
+                }
+                let Some(route) = route_of(dir, &path) else {
+                    continue;
+                };
+                let contents = fs::read_to_string(&path)?;
+                let value = serde_json::from_str(&contents).map_err(|source| TestSupportError::Fixture {
+                    path: path.display().to_string(),
+                    source,
+                })?;
+                fixtures.routes.insert(route, value);
+            }
+        }
+        Ok(fixtures)
+    }
+
+    /// Writes every fixture below `dir`, see `load`
+    pub fn save(&self, dir: impl AsRef<Path>) -> Result<(), TestSupportError> {
+        for (route, value) in &self.routes {
+            let path = path_of(dir.as_ref(), route);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let contents = serde_json::to_string_pretty(value).map_err(|source| TestSupportError::Fixture {
+                path: path.display().to_string(),
+                source,
+            })?;
+            fs::write(path, contents)?;
+        }
+        Ok(())
+    }
+
+    /// Adds or replaces the response of `route`, e.g. `eth/v1/beacon/headers/head`
+    pub fn insert(&mut self, route: impl Into<String>, value: Value) {
+        self.routes.insert(route.into(), value);
+    }
+
+    /// Adds or replaces the SSZ response of `route`, sent with `fork` as `Eth-Consensus-Version`
+    pub fn insert_ssz(&mut self, route: impl Into<String>, fork: impl Into<String>, body: Vec<u8>) {
+        self.ssz_routes.insert(route.into(), (fork.into(), body));
+    }
+
+    /// Returns the response recorded for `route`
+    pub fn get(&self, route: &str) -> Option<&Value> {
+        self.routes.get(route)
+    }
+
+    /// Number of recorded routes
+    pub fn len(&self) -> usize {
+        self.routes.len()
+    }
+
+    /// Returns true if no route is recorded
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty()
+    }
+
+    /// Adds validators to the fixture of `route`, keeping the ones already recorded
+    fn merge_validators(&mut self, route: String, response: &Value) {
+        let entry = self.routes.entry(route).or_insert_with(|| json!({ "data": [] }));
+        if let (Some(known), Some(new)) = (entry["data"].as_array_mut(), response["data"].as_array()) {
+            for validator in new {
+                if !known.iter().any(|v| v["index"] == validator["index"]) {
+                    known.push(validator.clone());
+                }
+            }
+        }
+    }
+}
+
+/// Route of a fixture file, `None` for files that are not fixtures
+fn route_of(dir: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(dir).ok()?.to_str()?;
+    let route = relative.strip_suffix(".json")?;
+    Some(route.replace(std::path::MAIN_SEPARATOR, "/").replacen('@', "?", 1))
+}
+
+fn path_of(dir: &Path, route: &str) -> PathBuf {
+    dir.join(format!("{}.json", route.replacen('?', "@", 1)))
+}
+
+#[derive(Debug)]
+struct BeaconState {
+    fixtures: BeaconFixtures,
+    /// Upstream node to record missing routes from
+    upstream: Option<String>,
+    client: reqwest::Client,
+    /// Requested routes, in order
+    requests: Vec<String>,
+    /// Bodies of POST requests, in order
+    post_bodies: Vec<Value>,
+}
+
+type SharedState = Arc<Mutex<BeaconState>>;
+
+const SSZ_CONTENT_TYPE: &str = "application/octet-stream";
+const CONSENSUS_VERSION_HEADER: header::HeaderName = header::HeaderName::from_static("eth-consensus-version");
+
+/// Local stand-in for a beacon node
+#[derive(Debug)]
+pub struct MockBeacon {
+    server: Server,
+    state: SharedState,
+}
+
+impl MockBeacon {
+    /// Starts the mock on a free port, serving `fixtures`
+    pub async fn start(fixtures: BeaconFixtures) -> Result<Self, TestSupportError> {
+        Self::start_with(fixtures, None).await
+    }
+
+    /// Starts the mock as a recording proxy: routes without a fixture are fetched from
+    /// `upstream` and added to the fixtures, see `fixtures` to save them afterwards.
+    pub async fn record(upstream: impl Into<String>) -> Result<Self, TestSupportError> {
+        Self::start_with(BeaconFixtures::default(), Some(upstream.into())).await
+    }
+
+    async fn start_with(
+        fixtures: BeaconFixtures,
+        upstream: Option<String>,
+    ) -> Result<Self, TestSupportError> {
+        let state = Arc::new(Mutex::new(BeaconState {
+            fixtures,
+            upstream: upstream.map(|url| url.trim_end_matches('/').to_string()),
+            client: reqwest::Client::new(),
+            requests: Vec::new(),
+            post_bodies: Vec::new(),
+        }));
+        let router = Router::new().fallback(handle).with_state(state.clone());
+
+        Ok(Self {
+            server: Server::start(router).await?,
+            state,
+        })
+    }
+
+    /// Base URL of the beacon API
+    pub fn url(&self) -> String {
+        self.server.url()
+    }
+
+    /// Returns the fixtures, including the ones recorded so far
+    pub fn fixtures(&self) -> BeaconFixtures {
+        lock(&self.state).fixtures.clone()
+    }
+
+    /// Returns the routes requested so far, in order
+    pub fn requests(&self) -> Vec<String> {
+        lock(&self.state).requests.clone()
+    }
+
+    /// Returns the JSON bodies of the POST requests so far, in order
+    pub fn post_bodies(&self) -> Vec<Value> {
+        lock(&self.state).post_bodies.clone()
+    }
+}
+
+fn lock(state: &SharedState) -> MutexGuard<'_, BeaconState> {
+    state.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+async fn handle(
+    State(state): State<SharedState>,
+    method: Method,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let path = uri.path().trim_start_matches('/');
+    let route = match uri.query() {
+        Some(query) => format!("{}?{}", path, query),
+        None => path.to_string(),
+    };
+    let accepts_ssz = headers
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.contains(SSZ_CONTENT_TYPE));
+    let (recorded, ssz, upstream, client) = {
+        let mut state = lock(&state);
+        state.requests.push(route.clone());
+        if method == Method::POST {
+            let body = serde_json::from_slice(&body).unwrap_or(Value::Null);
+            state.post_bodies.push(body);
+        }
+        (
+            state.fixtures.get(&route).cloned(),
+            state.fixtures.ssz_routes.get(&route).cloned(),
+            state.upstream.clone(),
+            state.client.clone(),
+        )
+    };
+    if let Some((fork, body)) = ssz.filter(|_| accepts_ssz) {
+        return (
+            [(header::CONTENT_TYPE, SSZ_CONTENT_TYPE.to_string()), (CONSENSUS_VERSION_HEADER, fork)],
+            body,
+        )
+            .into_response();
+    }
+    let is_validators = method == Method::POST && path.ends_with("/validators");
+
+    if let Some(upstream) = upstream {
+        if recorded.is_none() || is_validators {
+            return match forward(&client, &upstream, &method, &route, body).await {
+                Ok(value) => {
+                    let mut state = lock(&state);
+                    if is_validators {
+                        state.fixtures.merge_validators(route, &value);
+                    } else if value.get("code").is_none() {
+                        state.fixtures.insert(route, value.clone());
+                    }
+                    Json(value).into_response()
+                }
+                Err(e) => not_found(&format!("upstream error: {}", e)),
+            };
+        }
+    }
+
+    match recorded {
+        Some(value) if is_validators => Json(filter_validators(&value, &body)).into_response(),
+        Some(value) => Json(value).into_response(),
+        None => not_found(&route),
+    }
+}
+
+async fn forward(
+    client: &reqwest::Client,
+    upstream: &str,
+    method: &Method,
+    route: &str,
+    body: Bytes,
+) -> Result<Value, reqwest::Error> {
+    let url = format!("{}/{}", upstream, route);
+    let request = if *method == Method::POST {
+        client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.to_vec())
+    } else {
+        client.get(url)
+    };
+    request
+        .header(reqwest::header::ACCEPT, "application/json")
+        .send()
+        .await?
+        .json()
+        .await
+}
+
+/// Answers a validators lookup with the recorded validators whose index was requested
+fn filter_validators(recorded: &Value, body: &[u8]) -> Value {
+    let ids: Vec<String> = serde_json::from_slice::<Value>(body)
+        .ok()
+        .and_then(|body| body["ids"].as_array().cloned())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|id| id.as_str().map(str::to_string))
+        .collect();
+    let data: Vec<Value> = recorded["data"]
+        .as_array()
+        .map(|validators| {
+            validators
+                .iter()
+                .filter(|v| v["index"].as_str().is_some_and(|index| ids.iter().any(|id| id == index)))
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default();
+    json!({ "execution_optimistic": false, "finalized": true, "data": data })
+}
+
+fn not_found(what: &str) -> Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(json!({ "code": 404, "message": format!("NOT_FOUND: {}", what) })),
+    )
+        .into_response()
+}
@@ -0,0 +1,68 @@
+//! Test Support
+//!
+//! Local HTTP stand-ins for the services Bankai talks to, so clients and the CLI can be tested
+//! without network access:
+//!
+//! * `atlantic::MockAtlantic` accepts `atlantic-query` submissions, reports their status and
+//!   serves canned proofs from a proof registry, with configurable failures.
+//! * `beacon::MockBeacon` serves recorded beacon API responses, e.g. headers, blocks, sync
+//!   committees and validators, from `beacon::BeaconFixtures`.
+//!
+//! Every server binds to a free port on localhost and stops when it is dropped.
+
+pub mod atlantic;
+pub mod beacon;
+
+use std::net::SocketAddr;
+
+use axum::Router;
+use thiserror::Error;
+use tokio::{net::TcpListener, task::JoinHandle};
+
+/// A running server, aborted when dropped
+#[derive(Debug)]
+struct Server {
+    addr: SocketAddr,
+    handle: JoinHandle<()>,
+}
+
+impl Server {
+    /// Serves `router` on a free port on localhost
+    async fn start(router: Router) -> Result<Self, TestSupportError> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let handle = tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, router).await {
+                tracing::error!("Mock server on {} failed: {}", addr, e);
+            }
+        });
+        Ok(Self { addr, handle })
+    }
+
+    fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+/// Possible errors that can occur while starting a mock server or handling fixtures
+#[derive(Debug, Error)]
+pub enum TestSupportError {
+    /// IO error, e.g. while binding the server or reading fixtures
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    /// A fixture is not valid JSON
+    #[error("Invalid fixture {path}: {source}")]
+    Fixture {
+        path: String,
+        source: serde_json::Error,
+    },
+    /// The upstream beacon node could not be reached while recording
+    #[error("Upstream error: {0}")]
+    Upstream(#[from] reqwest::Error),
+}